@@ -1,6 +1,13 @@
-use std::{convert::TryInto, env, io, process};
-
-use cloudformatious::{ApplyStackInput, Client, DeleteStackInput, TemplateSource};
+use std::{
+    convert::TryInto,
+    env,
+    io::{self, IsTerminal},
+    process,
+};
+
+use cloudformatious::{
+    change_set::render::Render, ApplyStackInput, Client, DeleteStackInput, TemplateSource,
+};
 use futures_util::StreamExt;
 
 const USAGE: &str = "Usage: cargo run --example cli -- <apply|delete> <stack_name> [template_body]";
@@ -33,7 +40,10 @@ async fn try_main() -> Result<(), Box<dyn std::error::Error>> {
 
             let change_set = apply.change_set().await?;
             eprintln!("=== Change set ===");
-            eprintln!("{:#?}", change_set);
+            eprintln!(
+                "{}",
+                Render::new(&change_set).set_color(io::stderr().is_terminal())
+            );
             eprintln!();
 
             confirm("Continue [Y/n]?").await?;
@@ -81,7 +91,7 @@ async fn confirm(prompt: &str) -> io::Result<()> {
         io::stdin().read_line(&mut input)?;
         match input.as_str().trim() {
             "Y" | "y" | "" => Ok(()),
-            _ => Err(io::Error::new(io::ErrorKind::Other, "Quitting")),
+            _ => Err(io::Error::other("Quitting")),
         }
     })
     .await
@@ -1,25 +1,45 @@
 //! An operation to 'apply' a CloudFormation template to an AWS environment.
 
-use std::{fmt, future::Future, pin::Pin, task};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task,
+};
 
 use async_stream::try_stream;
 use aws_sdk_cloudformation::{
-    error::SdkError, operation::create_change_set::builders::CreateChangeSetFluentBuilder,
+    error::{ProvideErrorMetadata, SdkError},
+    operation::create_change_set::builders::CreateChangeSetFluentBuilder,
     types::Stack,
 };
 use aws_smithy_types_convert::date_time::DateTimeExt;
 use chrono::{DateTime, Utc};
-use futures_util::{Stream, TryFutureExt, TryStreamExt};
+use futures_util::{Stream, StreamExt, TryFutureExt, TryStreamExt};
 
+#[cfg(feature = "s3-staging")]
+use crate::TemplateBucket;
 use crate::{
     change_set::{
-        create_change_set, execute_change_set, ChangeSet, ChangeSetType, ChangeSetWithType,
-        CreateChangeSetError, ExecuteChangeSetError,
+        create_change_set, execute_change_set, ChangeSet, ChangeSetPolicy, ChangeSetType,
+        ChangeSetWithType, CreateChangeSetError, ExecuteChangeSetError, PolicyViolation,
     },
-    stack::StackOperationError,
-    BlockedStackStatus, ChangeSetStatus, StackEvent, StackFailure, StackStatus, StackWarning, Tag,
+    stack::{StackEventsError, StackOperationError},
+    template::{Diagnostic, ParameterError, Severity, Template},
+    unexpected_response::{Expected, UnexpectedResponse},
+    BlockedStackStatus, ChangeSetStatus, DeleteStack, DeleteStackError, DeleteStackInput,
+    PollingConfig, StackEvent, StackFailure, StackStatus, StackWarning, Tag,
 };
 
+pub(crate) const DESCRIBE_STACKS: &str = "DescribeStacks";
+const GET_TEMPLATE_SUMMARY: &str = "GetTemplateSummary";
+
 /// The input for the `apply_stack` operation.
 ///
 /// You can create an apply stack input via the [`new`](Self::new) associated function. Setters are
@@ -36,7 +56,7 @@ use crate::{
 ///     .set_capabilities([Capability::Iam])
 ///     .set_client_request_token("hello")
 ///     .set_notification_arns(["arn:foo"])
-///     .set_parameters([Parameter { key: "hello".to_string(), value: "world".to_string() }])
+///     .set_parameters([Parameter::new("hello", "world")])
 ///     .set_resource_types(["AWS::IAM::Role"])
 ///     .set_role_arn("arn:foo")
 ///     .set_tags([Tag { key: "hello".to_string(), value: "world".to_string() }]);
@@ -46,13 +66,24 @@ use crate::{
 /// # }
 /// ```
 #[derive(Clone, Debug)]
-#[allow(clippy::module_name_repetitions)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
 pub struct ApplyStackInput {
     /// Capabilities to explicitly acknowledge.
     ///
     /// See [`Capability`] for more information.
     pub capabilities: Vec<Capability>,
 
+    /// A policy to evaluate against the change set before it's executed.
+    ///
+    /// If the change set violates the policy it is deleted without being executed, and the
+    /// operation fails with [`ApplyStackError::PolicyViolation`]. See [`ChangeSetPolicy`] for more
+    /// information.
+    ///
+    /// This has no effect on [`Client::create_change_set`](crate::Client::create_change_set), but
+    /// [`ChangeSetPolicy::evaluate`] can be used to check the returned change set.
+    pub change_set_policy: Option<ChangeSetPolicy>,
+
     /// A unique identifier for this `apply_stack` operation.
     ///
     /// Specify this token if you plan to retry requests so that AWS CloudFormation knows that
@@ -70,6 +101,31 @@ pub struct ApplyStackInput {
     /// debugging failing stack operations.
     pub disable_rollback: bool,
 
+    /// Whether or not to create change sets for nested stacks.
+    ///
+    /// By default, the change set for a stack with nested stacks only describes changes to the
+    /// `AWS::CloudFormation::Stack` resources themselves. When this is set, change sets are also
+    /// created for each nested stack, and are included in the returned change set as
+    /// [`nested_change_set`](crate::change_set::ResourceChange::nested_change_set)s.
+    pub include_nested_stacks: bool,
+
+    /// Whether or not to include the before and after values of changed properties in the change
+    /// set.
+    ///
+    /// When this is set, the [`ResourceChange`](crate::change_set::ResourceChange)s and
+    /// [`ResourceChangeDetail`](crate::change_set::ResourceChangeDetail)s of the change set
+    /// include the resources' configuration and the changed values before and after the change.
+    pub include_property_values: bool,
+
+    /// Whether or not to acknowledge the capabilities that the template requires.
+    ///
+    /// When this is set, the capabilities returned by [`Template::required_capabilities`] are
+    /// added to [`capabilities`](Self::capabilities). If the template can't be parsed, the
+    /// operation fails with [`ApplyStackError::InvalidTemplate`].
+    ///
+    /// Templates with an [`S3`](TemplateSource::S3) source are not inspected.
+    pub infer_capabilities: bool,
+
     /// The Simple Notification Service (SNS) topic ARNs to publish stack related events.
     ///
     /// You can find your SNS topic ARNs using the SNS console or your Command Line Interface (CLI).
@@ -78,12 +134,33 @@ pub struct ApplyStackInput {
     /// A list of input parameters for the stack.
     ///
     /// If you don't specify a key and value for a particular parameter, AWS CloudFormation uses the
-    /// default value that is specified in your template.
-    ///
-    /// Note that, unlike when directly updating a stack, it is not possible to reuse previous
-    /// values of parameters.
+    /// default value that is specified in your template, unless
+    /// [`use_previous_parameters`](Self::use_previous_parameters) is set.
     pub parameters: Vec<Parameter>,
 
+    /// Whether or not to recreate the stack if it's in the `ROLLBACK_COMPLETE` state.
+    ///
+    /// A stack whose creation fails and is rolled back settles in `ROLLBACK_COMPLETE`, and cannot
+    /// be updated (see [`BlockedStackStatus::RollbackComplete`]). The only option is to delete the
+    /// stack, which is what this does before creating it again. The stack events from the
+    /// deletion are included in [`ApplyStack::events`].
+    ///
+    /// This has no effect on [`Client::create_change_set`](crate::Client::create_change_set),
+    /// which will still return [`ApplyStackError::Blocked`].
+    pub recreate_if_rollback_complete: bool,
+
+    /// Existing resources to import into the stack.
+    ///
+    /// When set, the operation imports the given resources rather than creating or updating
+    /// resources. The template must describe the stack's existing resources as well as the
+    /// resources being imported, each of which must have a `DeletionPolicy`, and can't otherwise
+    /// add, modify, or remove resources. The stack will be created if it doesn't exist.
+    ///
+    /// For more information, see [Bringing existing resources into CloudFormation management][1].
+    ///
+    /// [1]: https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/resource-import.html
+    pub resources_to_import: Option<Vec<ResourceToImport>>,
+
     /// The template resource types that you have permissions to work with for this `apply_stack`
     /// operation, such as `AWS::EC2::Instance`, `AWS::EC2::*`, or `Custom::MyCustomInstance`.
     ///
@@ -131,6 +208,19 @@ pub struct ApplyStackInput {
     /// maximum number of 50 tags can be specified.
     pub tags: Vec<Tag>,
 
+    /// An S3 location in which to stage templates that are too large to be passed inline.
+    ///
+    /// CloudFormation only accepts [`Inline`](TemplateSource::Inline) template bodies of up to
+    /// 51,200 bytes. When this is set, larger inline bodies are uploaded to the bucket (keyed by
+    /// their content, so unchanged templates are only uploaded once) and the change set is
+    /// created from the resulting [`S3`](TemplateSource::S3) URL instead. Uploads use the S3
+    /// client of the [`Client`](crate::Client), see
+    /// [`Client::set_s3_client`](crate::Client::set_s3_client).
+    ///
+    /// This requires the `s3-staging` feature.
+    #[cfg(feature = "s3-staging")]
+    pub template_bucket: Option<TemplateBucket>,
+
     /// Source for the template body to apply.
     ///
     /// For more information about templates, go to [Template Anatomy][1] in the AWS CloudFormation
@@ -138,6 +228,34 @@ pub struct ApplyStackInput {
     ///
     /// [1]: https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/template-anatomy.html
     pub template_source: TemplateSource,
+
+    /// Whether or not to keep the existing values of parameters that aren't given in
+    /// [`parameters`](Self::parameters).
+    ///
+    /// When this is set and the stack already exists, the stack is described before creating the
+    /// change set, and each of its parameters that is still declared by the new template, but
+    /// isn't given in `parameters`, is added with [`Parameter::use_previous_value`]. This makes it
+    /// possible to update a stack without resending every parameter, e.g. secrets that are not
+    /// available when applying the stack.
+    ///
+    /// If the template is parsed anyway (i.e. [`validate_template`](Self::validate_template) or
+    /// [`infer_capabilities`](Self::infer_capabilities) is set), its declared parameters are
+    /// taken from the parsed template. Otherwise they're looked up with the `GetTemplateSummary`
+    /// API, after the template has been staged to the `template_bucket` (if it's set and the
+    /// template is too large to send inline).
+    pub use_previous_parameters: bool,
+
+    /// Whether or not to check the template and parameters before creating the change set.
+    ///
+    /// When this is set, the template is parsed and checked with [`Template::validate`], and the
+    /// [`parameters`](Self::parameters) are checked against the template with
+    /// [`Template::check_parameters`]. If there are any errors (warnings are ignored), the
+    /// operation fails with [`ApplyStackError::InvalidTemplate`] or
+    /// [`ApplyStackError::InvalidParameters`] without making any changes, and before the template
+    /// is uploaded to the `template_bucket`.
+    ///
+    /// Templates with an [`S3`](TemplateSource::S3) source are not checked.
+    pub validate_template: bool,
 }
 
 impl ApplyStackInput {
@@ -148,13 +266,23 @@ impl ApplyStackInput {
             template_source,
 
             capabilities: Vec::new(),
+            change_set_policy: None,
             client_request_token: None,
             disable_rollback: false,
+            include_nested_stacks: false,
+            include_property_values: false,
+            infer_capabilities: false,
             notification_arns: Vec::new(),
             parameters: Vec::new(),
+            recreate_if_rollback_complete: false,
+            resources_to_import: None,
             resource_types: None,
             role_arn: None,
             tags: Vec::new(),
+            #[cfg(feature = "s3-staging")]
+            template_bucket: None,
+            use_previous_parameters: false,
+            validate_template: false,
         }
     }
 
@@ -167,6 +295,15 @@ impl ApplyStackInput {
         self
     }
 
+    /// Set the value for `change_set_policy`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_change_set_policy(mut self, change_set_policy: ChangeSetPolicy) -> Self {
+        self.change_set_policy = Some(change_set_policy);
+        self
+    }
+
     /// Set the value for `client_request_token`.
     ///
     /// **Note:** this consumes and returns `self` for chaining.
@@ -185,6 +322,33 @@ impl ApplyStackInput {
         self
     }
 
+    /// Set the value for `include_nested_stacks`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_include_nested_stacks(mut self, include_nested_stacks: bool) -> Self {
+        self.include_nested_stacks = include_nested_stacks;
+        self
+    }
+
+    /// Set the value for `include_property_values`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_include_property_values(mut self, include_property_values: bool) -> Self {
+        self.include_property_values = include_property_values;
+        self
+    }
+
+    /// Set the value for `infer_capabilities`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_infer_capabilities(mut self, infer_capabilities: bool) -> Self {
+        self.infer_capabilities = infer_capabilities;
+        self
+    }
+
     /// Set the value for `notification_arns`.
     ///
     /// **Note:** this consumes and returns `self` for chaining.
@@ -211,6 +375,30 @@ impl ApplyStackInput {
         self
     }
 
+    /// Set the value for `recreate_if_rollback_complete`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_recreate_if_rollback_complete(
+        mut self,
+        recreate_if_rollback_complete: bool,
+    ) -> Self {
+        self.recreate_if_rollback_complete = recreate_if_rollback_complete;
+        self
+    }
+
+    /// Set the value for `resources_to_import`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_resources_to_import(
+        mut self,
+        resources_to_import: impl Into<Vec<ResourceToImport>>,
+    ) -> Self {
+        self.resources_to_import = Some(resources_to_import.into());
+        self
+    }
+
     /// Set the value for `resource_types`.
     ///
     /// **Note:** this consumes and returns `self` for chaining.
@@ -242,14 +430,47 @@ impl ApplyStackInput {
         self
     }
 
+    /// Set the value for `template_bucket`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[cfg(feature = "s3-staging")]
+    #[must_use]
+    pub fn set_template_bucket(mut self, template_bucket: TemplateBucket) -> Self {
+        self.template_bucket = Some(template_bucket);
+        self
+    }
+
+    /// Set the value for `use_previous_parameters`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_use_previous_parameters(mut self, use_previous_parameters: bool) -> Self {
+        self.use_previous_parameters = use_previous_parameters;
+        self
+    }
+
+    /// Set the value for `validate_template`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_validate_template(mut self, validate_template: bool) -> Self {
+        self.validate_template = validate_template;
+        self
+    }
+
     fn configure(
         self,
+        template_source: ResolvedTemplateSource,
         op: CreateChangeSetFluentBuilder,
     ) -> (ChangeSetType, CreateChangeSetFluentBuilder) {
-        let change_set_type = ChangeSetType::Create;
-        let (template_body, template_url) = match self.template_source {
-            TemplateSource::Inline { body } => (Some(body), None),
-            TemplateSource::S3 { url } => (None, Some(url)),
+        let change_set_type = if self.resources_to_import.is_some() {
+            ChangeSetType::Import
+        } else {
+            ChangeSetType::Create
+        };
+        let (template_body, template_url) = match template_source {
+            ResolvedTemplateSource::Inline { body } => (Some(body), None),
+            ResolvedTemplateSource::S3 { url } => (None, Some(url)),
         };
         let input = op
             .set_capabilities(Some(
@@ -260,6 +481,7 @@ impl ApplyStackInput {
             ))
             .change_set_name(format!("apply-stack-{}", Utc::now().timestamp_millis()))
             .change_set_type(change_set_type.into_sdk())
+            .include_nested_stacks(self.include_nested_stacks)
             .set_notification_arns(Some(self.notification_arns))
             .set_parameters(Some(
                 self.parameters
@@ -267,6 +489,12 @@ impl ApplyStackInput {
                     .map(Parameter::into_sdk)
                     .collect(),
             ))
+            .set_resources_to_import(self.resources_to_import.map(|resources_to_import| {
+                resources_to_import
+                    .into_iter()
+                    .map(ResourceToImport::into_sdk)
+                    .collect()
+            }))
             .set_resource_types(self.resource_types)
             .set_role_arn(self.role_arn)
             .stack_name(self.stack_name)
@@ -294,7 +522,7 @@ impl ApplyStackInput {
 ///   - If you have IAM resources, you can specify either capability.
 ///   - If you have IAM resources with custom names, you *must* specify `CAPABILITY_NAMED_IAM`.
 ///   - If you don't specify either of these capabilities, AWS CloudFormation returns an
-///    `InsufficientCapabilities` error.
+///     `InsufficientCapabilities` error.
 ///
 ///   If you stack template contains these resources, we recommend that you review all
 ///   permissions associated with them and edit their permissions if necessary.
@@ -302,6 +530,7 @@ impl ApplyStackInput {
 ///   - `AWS::IAM::AccessKey`
 ///   - `AWS::IAM::Group`
 ///   - `AWS::IAM::InstanceProfile`
+///   - `AWS::IAM::ManagedPolicy`
 ///   - `AWS::IAM::Policy`
 ///   - `AWS::IAM::Role`
 ///   - `AWS::IAM::User`
@@ -336,7 +565,8 @@ impl ApplyStackInput {
 /// [2]: http://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/template-macros.html
 /// [`AWS::Include`]: http://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/create-reusable-transform-function-snippets-and-add-to-your-template-with-aws-include-transform.html
 /// [`AWS::Serverless`]: http://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/transform-aws-serverless.html
-#[derive(Clone, Copy, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[derive(Clone, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[non_exhaustive]
 pub enum Capability {
     /// Acknowledge IAM resources (*without* custom names only).
     #[display("CAPABILITY_IAM")]
@@ -349,6 +579,13 @@ pub enum Capability {
     /// Acknowledge macro expansion.
     #[display("CAPABILITY_AUTO_EXPAND")]
     AutoExpand,
+
+    /// A capability that this version of the library doesn't recognise.
+    ///
+    /// This carries the raw capability string, so that new capabilities introduced by
+    /// CloudFormation can be read from change sets and passed through to operations.
+    #[display("{0}")]
+    Unknown(String),
 }
 
 impl Capability {
@@ -357,28 +594,97 @@ impl Capability {
             Self::Iam => aws_sdk_cloudformation::types::Capability::CapabilityIam,
             Self::NamedIam => aws_sdk_cloudformation::types::Capability::CapabilityNamedIam,
             Self::AutoExpand => aws_sdk_cloudformation::types::Capability::CapabilityAutoExpand,
+            Self::Unknown(capability) => capability.as_str().into(),
         }
     }
 }
 
 /// An input parameter for an `apply_stack` operation.
-///
-/// Note that, unlike when directly updating a stack, it is not possible to reuse previous
-/// values of parameters.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parameter {
     /// The key associated with the parameter.
     pub key: String,
 
     /// The input value associated with the parameter.
+    ///
+    /// This is ignored if [`use_previous_value`](Self::use_previous_value) is set.
     pub value: String,
+
+    /// Whether or not to keep the value the parameter has in the existing stack.
+    ///
+    /// This is useful for values that aren't available when applying the stack, such as secrets
+    /// that were provided when the stack was created. For [SSM parameter types][1] this keeps the
+    /// name of the SSM parameter, which is resolved again when the change set is created.
+    ///
+    /// The stack must already exist, and the parameter must be declared in both the current and
+    /// the new template. See also [`ApplyStackInput::use_previous_parameters`].
+    ///
+    /// [1]: https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/parameters-section-structure.html#aws-ssm-parameter-types
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub use_previous_value: bool,
 }
 
 impl Parameter {
+    /// Construct a parameter with the given key and value.
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+            use_previous_value: false,
+        }
+    }
+
+    /// Construct a parameter that keeps the stack's existing value for the given key.
+    ///
+    /// See [`use_previous_value`](Self::use_previous_value) for more information.
+    pub fn previous(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: String::new(),
+            use_previous_value: true,
+        }
+    }
+
     fn into_sdk(self) -> aws_sdk_cloudformation::types::Parameter {
-        aws_sdk_cloudformation::types::Parameter::builder()
-            .parameter_key(self.key)
-            .parameter_value(self.value)
+        let builder = aws_sdk_cloudformation::types::Parameter::builder().parameter_key(self.key);
+        if self.use_previous_value {
+            builder.use_previous_value(true)
+        } else {
+            builder.parameter_value(self.value)
+        }
+        .build()
+    }
+}
+
+/// An existing resource to import into a stack.
+///
+/// See [`ApplyStackInput::resources_to_import`] for more information.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResourceToImport {
+    /// The type of resource to import, e.g. `AWS::S3::Bucket`.
+    ///
+    /// This must be a resource type that supports import operations.
+    pub resource_type: String,
+
+    /// The logical ID of the resource in the template.
+    pub logical_resource_id: String,
+
+    /// The properties that uniquely identify the resource, e.g. `BucketName` for an
+    /// `AWS::S3::Bucket`.
+    ///
+    /// The identifying properties of a resource type can be retrieved with the
+    /// `GetTemplateSummary` API.
+    pub resource_identifier: HashMap<String, String>,
+}
+
+impl ResourceToImport {
+    fn into_sdk(self) -> aws_sdk_cloudformation::types::ResourceToImport {
+        aws_sdk_cloudformation::types::ResourceToImport::builder()
+            .resource_type(self.resource_type)
+            .logical_resource_id(self.logical_resource_id)
+            .set_resource_identifier(Some(self.resource_identifier))
             .build()
     }
 }
@@ -390,8 +696,11 @@ impl Parameter {
 /// - As a JSON string, inline with the request.
 /// - As a URL to a template file on S3.
 ///
+/// Templates can also be read from a local file (or directory), which is then passed inline.
+///
 /// See the variant documentation for more information.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TemplateSource {
     /// Structure containing the template body with a minimum length of 1 byte and a maximum length
     /// of 51,200 bytes.
@@ -409,6 +718,20 @@ pub enum TemplateSource {
     ///
     /// [1]: https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/template-anatomy.html
     S3 { url: String },
+
+    /// Path to a local file, or directory, containing the template body.
+    ///
+    /// The file is read when the operation starts, and its contents are used as an
+    /// [`Inline`](Self::Inline) template body (so the same size limit applies, unless
+    /// `ApplyStackInput::template_bucket` is set). Both JSON and YAML templates are supported.
+    ///
+    /// If the path is a directory, the template is read from the first of `template.yaml`,
+    /// `template.yml`, or `template.json` that exists in the directory.
+    ///
+    /// If the file can't be read the operation fails with [`ApplyStackError::TemplateFile`]. The
+    /// path of the file is also included in [`ApplyStackError::InvalidTemplate`] and
+    /// [`ApplyStackError::CreateChangeSetFailed`] errors.
+    File { path: PathBuf },
 }
 
 impl TemplateSource {
@@ -423,10 +746,100 @@ impl TemplateSource {
     pub fn s3(url: impl Into<String>) -> Self {
         Self::S3 { url: url.into() }
     }
+
+    /// Construct a [`File`](Self::File) template source for the given `path`.
+    #[must_use]
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::File { path: path.into() }
+    }
+
+    /// Read the template body of a [`File`](Self::File) source.
+    ///
+    /// The path of the template file that was read, if any, is returned alongside the source.
+    async fn read(&self) -> Result<(ResolvedTemplateSource, Option<PathBuf>), ApplyStackError> {
+        let path = match self {
+            Self::Inline { body } => {
+                return Ok((ResolvedTemplateSource::Inline { body: body.clone() }, None))
+            }
+            Self::S3 { url } => return Ok((ResolvedTemplateSource::S3 { url: url.clone() }, None)),
+            Self::File { path } => path,
+        };
+
+        let path = match tokio::fs::metadata(path).await {
+            Ok(metadata) if metadata.is_dir() => find_template_file(path).await?,
+            Ok(_) => path.clone(),
+            Err(error) => {
+                return Err(ApplyStackError::TemplateFile {
+                    path: path.clone(),
+                    error,
+                })
+            }
+        };
+        match tokio::fs::read_to_string(&path).await {
+            Ok(body) => Ok((ResolvedTemplateSource::Inline { body }, Some(path))),
+            Err(error) => Err(ApplyStackError::TemplateFile { path, error }),
+        }
+    }
+}
+
+/// The names of the files that templates are read from, when a [`TemplateSource::File`] is a
+/// directory.
+const TEMPLATE_FILE_NAMES: [&str; 3] = ["template.yaml", "template.yml", "template.json"];
+
+/// Find the template file in a `directory`, see [`TemplateSource::File`].
+async fn find_template_file(directory: &Path) -> Result<PathBuf, ApplyStackError> {
+    for name in TEMPLATE_FILE_NAMES {
+        let path = directory.join(name);
+        match tokio::fs::try_exists(&path).await {
+            Ok(true) => return Ok(path),
+            Ok(false) => {}
+            Err(error) => return Err(ApplyStackError::TemplateFile { path, error }),
+        }
+    }
+    Err(ApplyStackError::TemplateFile {
+        path: directory.to_path_buf(),
+        error: std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "directory contains none of {}",
+                TEMPLATE_FILE_NAMES.join(", ")
+            ),
+        ),
+    })
+}
+
+/// The S3 client used to stage templates in an [`ApplyStackInput`]'s `template_bucket`.
+///
+/// This is empty without the `s3-staging` feature, so that clients and operations are constructed
+/// the same way with or without it.
+#[derive(Clone, Debug)]
+pub(crate) struct S3Client(#[cfg(feature = "s3-staging")] pub(crate) aws_sdk_s3::Client);
+
+impl S3Client {
+    #[cfg(feature = "s3-staging")]
+    pub(crate) fn new(config: &aws_config::SdkConfig) -> Self {
+        Self(aws_sdk_s3::Client::new(config))
+    }
+
+    #[cfg(not(feature = "s3-staging"))]
+    pub(crate) fn new(_config: &aws_config::SdkConfig) -> Self {
+        Self()
+    }
+}
+
+/// A [`TemplateSource`] that can be passed to CloudFormation, i.e. with any template file read.
+#[derive(Debug)]
+pub(crate) enum ResolvedTemplateSource {
+    /// See [`TemplateSource::Inline`].
+    Inline { body: String },
+
+    /// See [`TemplateSource::S3`].
+    S3 { url: String },
 }
 
 /// The output of the `apply_stack` operation.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::module_name_repetitions)]
 pub struct ApplyStackOutput {
     /// The unique ID of the change set.
@@ -446,6 +859,9 @@ pub struct ApplyStackOutput {
     /// A list of output structures.
     pub outputs: Vec<StackOutput>,
 
+    /// The stack's parameters.
+    pub parameters: Vec<StackParameter>,
+
     /// Unique identifier for the stack.
     pub stack_id: String,
 
@@ -460,57 +876,81 @@ pub struct ApplyStackOutput {
 }
 
 impl ApplyStackOutput {
-    fn from_raw(stack: Stack, change_set_id: String) -> Self {
-        Self {
+    /// Get the stack's outputs as a map from output keys to values.
+    ///
+    /// With the `serde` feature, [`outputs_as`](Self::outputs_as) can be used to deserialize the
+    /// outputs into a struct.
+    #[must_use]
+    pub fn outputs_map(&self) -> HashMap<&str, &str> {
+        self.outputs
+            .iter()
+            .map(|output| (output.key.as_str(), output.value.as_str()))
+            .collect()
+    }
+
+    fn from_raw(stack: Stack, change_set_id: String) -> Result<Self, UnexpectedResponse> {
+        Ok(Self {
             change_set_id,
             creation_time: stack
                 .creation_time
-                .expect("Stack without creation_time")
+                .expected(DESCRIBE_STACKS, "Stack without creation_time")?
                 .to_chrono_utc()
-                .expect("invalid creation_time"),
+                .expected(DESCRIBE_STACKS, "Stack with invalid creation_time")?,
             description: stack.description,
             last_updated_time: stack
                 .last_updated_time
                 .as_ref()
                 .map(DateTimeExt::to_chrono_utc)
                 .transpose()
-                .expect("invalid last_updated_time"),
+                .expected(DESCRIBE_STACKS, "Stack with invalid last_updated_time")?,
             outputs: stack
                 .outputs
-                .map(|outputs| {
-                    outputs
-                        .into_iter()
-                        .map(|output| StackOutput {
-                            description: output.description,
-                            export_name: output.export_name,
-                            key: output.output_key.expect("StackOutput without output_key"),
-                            value: output
-                                .output_value
-                                .expect("StackOutput without output_value"),
-                        })
-                        .collect()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|output| {
+                    Ok(StackOutput {
+                        description: output.description,
+                        export_name: output.export_name,
+                        key: output
+                            .output_key
+                            .expected(DESCRIBE_STACKS, "StackOutput without output_key")?,
+                        value: output
+                            .output_value
+                            .expected(DESCRIBE_STACKS, "StackOutput without output_value")?,
+                    })
                 })
-                .unwrap_or_default(),
-            stack_id: stack.stack_id.expect("Stack without stack_id"),
-            stack_name: stack.stack_name.expect("Stack without stack_name"),
+                .collect::<Result<_, _>>()?,
+            parameters: stack
+                .parameters
+                .unwrap_or_default()
+                .into_iter()
+                .map(StackParameter::from_sdk)
+                .collect::<Result<_, _>>()?,
+            stack_id: stack
+                .stack_id
+                .expected(DESCRIBE_STACKS, "Stack without stack_id")?,
+            stack_name: stack
+                .stack_name
+                .expected(DESCRIBE_STACKS, "Stack without stack_name")?,
             stack_status: stack
                 .stack_status
-                .expect("Stack without stack_status")
+                .expected(DESCRIBE_STACKS, "Stack without stack_status")?
                 .as_str()
                 .parse()
-                .expect("invalid stack status"),
+                .expected(DESCRIBE_STACKS, "Stack with invalid stack_status")?,
             tags: stack
                 .tags
                 .unwrap_or_default()
                 .into_iter()
-                .map(Tag::from_sdk)
-                .collect(),
-        }
+                .map(|tag| Tag::from_sdk(DESCRIBE_STACKS, tag))
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
 /// An output from an `apply_stack` operation.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StackOutput {
     /// User defined description associated with the output.
     pub description: Option<String>,
@@ -525,6 +965,41 @@ pub struct StackOutput {
     pub value: String,
 }
 
+/// A parameter of a stack applied by an `apply_stack` operation.
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StackParameter {
+    /// The key associated with the parameter.
+    pub key: String,
+
+    /// The value associated with the parameter.
+    ///
+    /// The values of parameters with `NoEcho` are masked as `****`.
+    pub value: Option<String>,
+
+    /// The value that the parameter's SSM parameter resolved to.
+    ///
+    /// This is only set for [SSM parameter types][1], whose `value` is the name of an SSM
+    /// parameter.
+    ///
+    /// [1]: https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/parameters-section-structure.html#aws-ssm-parameter-types
+    pub resolved_value: Option<String>,
+}
+
+impl StackParameter {
+    fn from_sdk(
+        parameter: aws_sdk_cloudformation::types::Parameter,
+    ) -> Result<Self, UnexpectedResponse> {
+        Ok(Self {
+            key: parameter
+                .parameter_key
+                .expected(DESCRIBE_STACKS, "Parameter without parameter_key")?,
+            value: parameter.parameter_value,
+            resolved_value: parameter.resolved_value,
+        })
+    }
+}
+
 /// Errors emitted by an `apply_stack` operation.
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
@@ -537,14 +1012,50 @@ pub enum ApplyStackError {
     /// **Note:** the inner error will always be some variant of [`SdkError`], but since they are
     /// generic over the type of service errors we either need a variant per API used, or `Box`. If
     /// you do need to programmatically match a particular API error you can use [`Box::downcast`].
-    CloudFormationApi(Box<dyn std::error::Error>),
+    CloudFormationApi(Box<dyn std::error::Error + Send + Sync>),
+
+    /// An S3 API error occurred while staging the template in the
+    /// [`template_bucket`](ApplyStackInput::template_bucket).
+    ///
+    /// This is likely to be due to the bucket not existing, or missing S3 permissions. As with
+    /// [`CloudFormationApi`](Self::CloudFormationApi), the inner error will always be some variant
+    /// of [`SdkError`].
+    #[cfg(feature = "s3-staging")]
+    S3Api(Box<dyn std::error::Error + Send + Sync>),
+
+    /// The template file of a [`TemplateSource::File`] could not be read.
+    TemplateFile {
+        /// The path of the template file.
+        path: PathBuf,
+
+        /// The error that occurred when reading the file.
+        error: std::io::Error,
+    },
 
     /// The stack cannot be modified as it's in a blocked state.
+    ///
+    /// A stack in the `UPDATE_ROLLBACK_FAILED` state can be unblocked with
+    /// [`Client::continue_update_rollback`](crate::Client::continue_update_rollback).
     Blocked {
         /// The blocked status that the stack is in.
         status: BlockedStackStatus,
     },
 
+    /// The template failed the checks enabled by [`ApplyStackInput::validate_template`].
+    InvalidTemplate {
+        /// The path of the template file, if the template came from a [`TemplateSource::File`].
+        template_path: Option<PathBuf>,
+
+        /// The problems found in the template.
+        diagnostics: Vec<Diagnostic>,
+    },
+
+    /// The parameters failed the checks enabled by [`ApplyStackInput::validate_template`].
+    InvalidParameters {
+        /// The problems found with the parameters.
+        errors: Vec<ParameterError>,
+    },
+
     /// The change set failed to create.
     ///
     /// Change sets are created asynchronously and may settle in a `FAILED` state. Trying to execute
@@ -559,11 +1070,33 @@ pub enum ApplyStackError {
 
         /// The reason the change set failed to create.
         status_reason: String,
+
+        /// The path of the template file, if the template came from a [`TemplateSource::File`].
+        template_path: Option<PathBuf>,
+    },
+
+    /// The change set violated the [`ChangeSetPolicy`] attached to the input.
+    ///
+    /// The change set has been deleted without being executed, so the stack is unchanged.
+    PolicyViolation {
+        /// The changes that violated the policy.
+        violations: Vec<PolicyViolation>,
     },
 
     /// The apply stack operation failed.
     Failure(StackFailure),
 
+    /// The stack update was cancelled with [`ApplyStack::cancel`] (or an
+    /// [`ApplyStackCancelHandle`]).
+    ///
+    /// The stack has been rolled back to its previous state, unless the failure's `stack_status`
+    /// is `UPDATE_ROLLBACK_FAILED`, in which case its `resource_events` describe the resources
+    /// that failed to roll back.
+    Cancelled {
+        /// The failure the stack settled in after rolling back.
+        failure: StackFailure,
+    },
+
     /// The apply stack operation succeeded with warnings.
     ///
     /// It is possible for resource errors to occur even when the overall operation succeeds, such
@@ -598,38 +1131,127 @@ pub enum ApplyStackError {
         /// Details of what went wrong.
         warning: StackWarning,
     },
+
+    /// CloudFormation returned a response we didn't expect.
+    ///
+    /// This could be due to a missing field, or a value (such as a status) that we don't
+    /// recognise. It likely indicates a change in CloudFormation's behaviour that this library
+    /// hasn't caught up with.
+    UnexpectedResponse {
+        /// The CloudFormation API that returned the response, e.g. `DescribeStackEvents`.
+        api: &'static str,
+
+        /// A description of what was unexpected about the response.
+        detail: String,
+    },
 }
 
 impl ApplyStackError {
-    fn from_sdk_error<E: std::error::Error + 'static>(error: SdkError<E>) -> Self {
+    pub(crate) fn from_sdk_error<E: std::error::Error + Send + Sync + 'static>(
+        error: SdkError<E>,
+    ) -> Self {
         Self::CloudFormationApi(error.into())
     }
 }
 
+impl From<UnexpectedResponse> for ApplyStackError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse {
+            api: error.api,
+            detail: error.detail,
+        }
+    }
+}
+
+impl From<StackEventsError> for ApplyStackError {
+    fn from(error: StackEventsError) -> Self {
+        match error {
+            StackEventsError::Api(error) => Self::from_sdk_error(*error),
+            StackEventsError::UnexpectedResponse(error) => error.into(),
+        }
+    }
+}
+
 impl fmt::Display for ApplyStackError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::CloudFormationApi(error) => {
                 write!(f, "CloudFormation API error: {error:#?}")
             }
+            #[cfg(feature = "s3-staging")]
+            Self::S3Api(error) => {
+                write!(f, "S3 API error while staging template: {error:#?}")
+            }
+            Self::TemplateFile { path, error } => {
+                write!(
+                    f,
+                    "failed to read template file {}: {error}",
+                    path.display()
+                )
+            }
             Self::Blocked { status } => {
                 write!(
                     f,
                     "stack operation failed because the stack is in a blocked state: {status}",
                 )
             }
+            Self::InvalidTemplate {
+                template_path,
+                diagnostics,
+            } => {
+                match template_path {
+                    Some(path) => write!(f, "Template {} is invalid: ", path.display())?,
+                    None => write!(f, "Template is invalid: ")?,
+                }
+                for (i, diagnostic) in diagnostics.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{diagnostic}")?;
+                }
+                Ok(())
+            }
+            Self::InvalidParameters { errors } => {
+                write!(f, "Parameters are invalid: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{error}")?;
+                }
+                Ok(())
+            }
             Self::CreateChangeSetFailed {
                 id,
                 status,
                 status_reason,
+                template_path,
             } => {
+                write!(f, "Change set {id}")?;
+                if let Some(path) = template_path {
+                    write!(f, " for template {}", path.display())?;
+                }
                 write!(
                     f,
-                    "Change set {id} failed to create; terminal status: {status} ({status_reason})",
+                    " failed to create; terminal status: {status} ({status_reason})",
                 )
             }
+            Self::PolicyViolation { violations } => {
+                write!(f, "Change set violated policy: ")?;
+                for (i, violation) in violations.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{violation}")?;
+                }
+                Ok(())
+            }
             Self::Failure(failure) => write!(f, "{failure}"),
+            Self::Cancelled { failure } => write!(f, "Stack update was cancelled: {failure}"),
             Self::Warning { warning, .. } => write!(f, "{warning}"),
+            Self::UnexpectedResponse { api, detail } => {
+                write!(f, "unexpected response from {api}: {detail}")
+            }
         }
     }
 }
@@ -638,10 +1260,18 @@ impl std::error::Error for ApplyStackError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::CloudFormationApi(error) => Some(error.as_ref()),
+            #[cfg(feature = "s3-staging")]
+            Self::S3Api(error) => Some(error.as_ref()),
+            Self::TemplateFile { error, .. } => Some(error),
             Self::Blocked { .. }
+            | Self::InvalidTemplate { .. }
+            | Self::InvalidParameters { .. }
             | Self::CreateChangeSetFailed { .. }
+            | Self::PolicyViolation { .. }
             | Self::Failure { .. }
-            | Self::Warning { .. } => None,
+            | Self::Cancelled { .. }
+            | Self::Warning { .. }
+            | Self::UnexpectedResponse { .. } => None,
         }
     }
 }
@@ -650,102 +1280,170 @@ impl std::error::Error for ApplyStackError {
 ///
 /// This implements `Future`, which will simply wait for the operation to conclude. If you want to
 /// observe progress, see [`ApplyStack::events`].
-pub struct ApplyStack<'client> {
+pub struct ApplyStack {
     /// The stream of internal events that drives the different levels of the API.
     ///
     /// This might not be the best way of driving things, but it works with a few rough edges in the
     /// form of various possible panics that could arise from unanticipated execution patterns (e.g.
     /// attempting to await multiple times, or calling APIs out of order).
-    event_stream: Pin<Box<dyn Stream<Item = Result<ApplyStackEvent, ApplyStackError>> + 'client>>,
+    event_stream: Pin<Box<dyn Stream<Item = Result<ApplyStackEvent, ApplyStackError>> + Send>>,
 
     /// The `ApplyStackOutput` is moved here once it's been emitted by the stream.
     output: Option<Result<ApplyStackOutput, ApplyStackError>>,
+
+    /// The handle used to cancel the operation.
+    cancel_handle: ApplyStackCancelHandle,
 }
 
-impl<'client> ApplyStack<'client> {
+impl ApplyStack {
     pub(crate) fn new(
-        client: &'client aws_sdk_cloudformation::Client,
+        client: aws_sdk_cloudformation::Client,
+        s3: S3Client,
+        polling: PollingConfig,
         input: ApplyStackInput,
     ) -> Self {
         let disable_rollback = input.disable_rollback;
+        let change_set_policy = input.change_set_policy.clone();
+        let cancel_handle = ApplyStackCancelHandle {
+            client: client.clone(),
+            stack_name: input.stack_name.clone(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
 
-        let event_stream = try_stream! {
-            let (stack_id, change_set_id, change_set_type) =
-                match create_change_set_internal(client, input).await? {
-                    Ok(ChangeSetWithType {
-                        change_set,
-                        change_set_type,
-                    }) => {
-                        let stack_id = change_set.stack_id.clone();
-                        let change_set_id = change_set.change_set_id.clone();
-                        yield ApplyStackEvent::ChangeSet(change_set);
-                        (stack_id, change_set_id, change_set_type)
-                    }
-                    Err(ChangeSetWithType { change_set, .. }) => {
-                        let stack_id = change_set.stack_id.clone();
-                        let change_set_id = change_set.change_set_id.clone();
-                        yield ApplyStackEvent::ChangeSet(change_set);
-
-                        let output = describe_output(client, stack_id, change_set_id).await?;
-                        yield ApplyStackEvent::Output(output);
-                        return;
+        let event_stream = {
+            let cancelled = Arc::clone(&cancel_handle.cancelled);
+            try_stream! {
+                let recreate = input.recreate_if_rollback_complete.then(|| input.clone());
+                let change_set = match (
+                    create_change_set_internal(&client, &s3, polling, input).await,
+                    recreate,
+                ) {
+                    (
+                        Err(ApplyStackError::Blocked {
+                            status: BlockedStackStatus::RollbackComplete,
+                        }),
+                        Some(input),
+                    ) => {
+                        let mut delete_input = DeleteStackInput::new(&input.stack_name);
+                        delete_input.role_arn.clone_from(&input.role_arn);
+
+                        let mut delete = DeleteStack::new(client.clone(), polling, delete_input);
+                        while let Some(event) = delete.events().next().await {
+                            yield ApplyStackEvent::Event(event);
+                        }
+                        match delete.await {
+                            // The stack is gone, which is all we need to retry.
+                            Ok(()) | Err(DeleteStackError::Warning(_)) => {}
+                            Err(DeleteStackError::CloudFormationApi(error)) => {
+                                Err(ApplyStackError::CloudFormationApi(error))?;
+                            }
+                            Err(DeleteStackError::Failure(failure)) => {
+                                Err(ApplyStackError::Failure(failure))?;
+                            }
+                            Err(DeleteStackError::UnexpectedResponse { api, detail }) => {
+                                Err(ApplyStackError::UnexpectedResponse { api, detail })?;
+                            }
+                        }
+
+                        create_change_set_internal(&client, &s3, polling, input).await?
                     }
+                    (result, _) => result?,
                 };
-
-            let mut operation =
-                execute_change_set(client, stack_id.clone(), change_set_id.clone(), change_set_type, disable_rollback)
-                    .await
-                    .map_err(|error| match error {
-                        ExecuteChangeSetError::ExecuteApi(error) => ApplyStackError::from_sdk_error(error),
-                        ExecuteChangeSetError::Blocked { status } => ApplyStackError::Blocked { status },
-                    })?;
-            while let Some(event) = operation
-                .try_next()
-                .await
-                .map_err(ApplyStackError::from_sdk_error)?
-            {
-                yield ApplyStackEvent::Event(event);
-            }
-
-            let warning = match operation.verify() {
-                Err(StackOperationError::Failure(failure)) => {
-                    Err(ApplyStackError::Failure(failure))?;
-                    unreachable!()
-                }
-                Ok(()) => None,
-                Err(StackOperationError::Warning(warning)) => Some(warning),
-            };
-
-            let output = describe_output(client, stack_id, change_set_id).await?;
-
-            match warning {
-                Some(warning) => {
-                    Err(ApplyStackError::Warning { output, warning })?;
-                    unreachable!()
+                let (stack_id, change_set_id, change_set_type) =
+                    match change_set {
+                        Ok(ChangeSetWithType {
+                            change_set,
+                            change_set_type,
+                        }) => {
+                            let violations = change_set_policy
+                                .map(|policy| policy.evaluate(&change_set))
+                                .unwrap_or_default();
+                            if !violations.is_empty() {
+                                client
+                                    .delete_change_set()
+                                    .change_set_name(&change_set.change_set_id)
+                                    .send()
+                                    .await
+                                    .map_err(ApplyStackError::from_sdk_error)?;
+                                Err(ApplyStackError::PolicyViolation { violations })?;
+                            }
+
+                            let stack_id = change_set.stack_id.clone();
+                            let change_set_id = change_set.change_set_id.clone();
+                            yield ApplyStackEvent::ChangeSet(change_set);
+                            (stack_id, change_set_id, change_set_type)
+                        }
+                        Err(change_set) => {
+                            let stack_id = change_set.stack_id.clone();
+                            let change_set_id = change_set.change_set_id.clone();
+                            yield ApplyStackEvent::ChangeSet(change_set);
+
+                            let output = describe_output(&client, stack_id, change_set_id).await?;
+                            yield ApplyStackEvent::Output(output);
+                            return;
+                        }
+                    };
+
+                for await event in execute_change_set_internal(
+                    &client,
+                    polling,
+                    stack_id,
+                    change_set_id,
+                    change_set_type,
+                    disable_rollback,
+                ) {
+                    match event {
+                        Err(ApplyStackError::Failure(failure)) if was_cancelled(&cancelled, &failure) => {
+                            Err(ApplyStackError::Cancelled { failure })?;
+                        }
+                        event => yield event?,
+                    }
                 }
-                None => yield ApplyStackEvent::Output(output),
-            };
+            }
         };
         Self {
             event_stream: Box::pin(event_stream),
             output: None,
+            cancel_handle,
         }
     }
 
+    /// Cancel the stack update.
+    ///
+    /// This is a shorthand for `apply.cancel_handle().cancel()`, see
+    /// [`ApplyStackCancelHandle::cancel`]. Since the operation must keep being polled while the
+    /// cancellation is in flight, a handle is usually more convenient.
+    ///
+    /// # Errors
+    ///
+    /// See [`ApplyStackCancelHandle::cancel`].
+    pub async fn cancel(&self) -> Result<(), ApplyStackError> {
+        self.cancel_handle.cancel().await
+    }
+
+    /// Get a handle that can be used to cancel the stack update.
+    ///
+    /// The handle can be cloned and used from another task, while the operation itself is being
+    /// polled.
+    #[must_use]
+    pub fn cancel_handle(&self) -> ApplyStackCancelHandle {
+        self.cancel_handle.clone()
+    }
+
     /// Get the `ChangeSet` that will be applied.
     ///
     /// The change set will not be executed if you never poll again.
-    pub fn change_set(&mut self) -> ApplyStackChangeSet<'client, '_> {
+    pub fn change_set(&mut self) -> ApplyStackChangeSet<'_> {
         ApplyStackChangeSet(self)
     }
 
     /// Get a `Stream` of `StackEvent`s.
-    pub fn events(&mut self) -> ApplyStackEvents<'client, '_> {
+    pub fn events(&mut self) -> ApplyStackEvents<'_> {
         ApplyStackEvents(self)
     }
 }
 
-impl Future for ApplyStack<'_> {
+impl Future for ApplyStack {
     type Output = Result<ApplyStackOutput, ApplyStackError>;
 
     fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context) -> task::Poll<Self::Output> {
@@ -761,25 +1459,108 @@ impl Future for ApplyStack<'_> {
                 }
                 task::Poll::Ready(Some(Ok(
                     ApplyStackEvent::ChangeSet(_) | ApplyStackEvent::Event(_),
-                ))) => continue,
+                ))) => {}
                 task::Poll::Ready(Some(Ok(ApplyStackEvent::Output(output)))) => {
                     self.output.replace(Ok(output));
-                    continue;
                 }
                 task::Poll::Ready(Some(Err(error))) => {
                     self.output.replace(Err(error));
-                    continue;
                 }
             }
         }
     }
 }
 
+/// A handle for cancelling an [`ApplyStack`] operation, see [`ApplyStack::cancel_handle`].
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ApplyStackCancelHandle {
+    /// The client used to cancel the operation.
+    client: aws_sdk_cloudformation::Client,
+
+    /// The stack being applied.
+    stack_name: String,
+
+    /// Set once cancellation has succeeded, so that the resulting failure can be reported as such.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ApplyStackCancelHandle {
+    /// Cancel the stack update.
+    ///
+    /// This calls `CancelUpdateStack`, which causes CloudFormation to roll the stack back to its
+    /// previous state. The operation should continue to be polled: [`ApplyStack::events`] will
+    /// include the events of the rollback, and the operation will resolve to
+    /// [`ApplyStackError::Cancelled`] once the rollback has finished.
+    ///
+    /// Only stack updates can be cancelled, so this is only meaningful once the change set has been
+    /// executed against an existing stack.
+    ///
+    /// ```no_run
+    /// use cloudformatious::{ApplyStackError, ApplyStackInput, TemplateSource};
+    /// use futures_util::StreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = aws_config::load_from_env().await;
+    /// let client = cloudformatious::Client::new(&config);
+    /// let input = ApplyStackInput::new("my-stack", TemplateSource::inline("{}"));
+    /// let mut apply = client.apply_stack(input);
+    ///
+    /// let cancel = apply.cancel_handle();
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    ///     cancel.cancel().await
+    /// });
+    ///
+    /// while let Some(event) = apply.events().next().await {
+    ///     eprintln!("{}", event.resource_status());
+    /// }
+    /// match apply.await {
+    ///     Err(ApplyStackError::Cancelled { failure }) => {
+    ///         eprintln!("cancelled: {}", failure.stack_status);
+    ///     }
+    ///     result => {
+    ///         result?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApplyStackError::CloudFormationApi`] if the `CancelUpdateStack` request fails, for
+    /// example because the stack is not in the `UPDATE_IN_PROGRESS` state. The operation itself is
+    /// unaffected in that case, and a failure will be reported as [`ApplyStackError::Failure`].
+    pub async fn cancel(&self) -> Result<(), ApplyStackError> {
+        self.client
+            .cancel_update_stack()
+            .stack_name(&self.stack_name)
+            .send()
+            .await
+            .map_err(ApplyStackError::from_sdk_error)?;
+        // The rollback only starts once the request has succeeded, so it can't be observed as
+        // complete before this is set.
+        self.cancelled.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Whether a `failure` is the result of a successful [`ApplyStackCancelHandle::cancel`].
+fn was_cancelled(cancelled: &AtomicBool, failure: &StackFailure) -> bool {
+    cancelled.load(Ordering::SeqCst)
+        && matches!(
+            failure.stack_status,
+            StackStatus::UpdateRollbackComplete | StackStatus::UpdateRollbackFailed
+        )
+}
+
 /// Return value of [`ApplyStack::change_set`].
 #[allow(clippy::module_name_repetitions)]
-pub struct ApplyStackChangeSet<'client, 'apply>(&'apply mut ApplyStack<'client>);
+pub struct ApplyStackChangeSet<'apply>(&'apply mut ApplyStack);
 
-impl Future for ApplyStackChangeSet<'_, '_> {
+impl Future for ApplyStackChangeSet<'_> {
     type Output = Result<ChangeSet, ApplyStackError>;
 
     fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context) -> task::Poll<Self::Output> {
@@ -794,15 +1575,13 @@ impl Future for ApplyStackChangeSet<'_, '_> {
                 task::Poll::Ready(Some(Ok(ApplyStackEvent::ChangeSet(change_set)))) => {
                     return task::Poll::Ready(Ok(change_set));
                 }
-                task::Poll::Ready(Some(Ok(ApplyStackEvent::Event(_)))) => {
-                    panic!("saw stack event before change set");
-                }
+                // Events from deleting a stack that's being recreated precede the change set.
+                task::Poll::Ready(Some(Ok(ApplyStackEvent::Event(_)))) => {}
                 task::Poll::Ready(Some(Ok(ApplyStackEvent::Output(_)))) => {
                     panic!("saw output before change set");
                 }
                 task::Poll::Ready(Some(Err(error))) => {
                     self.0.output.replace(Err(error));
-                    continue;
                 }
             }
         }
@@ -811,9 +1590,9 @@ impl Future for ApplyStackChangeSet<'_, '_> {
 
 /// Return value of [`ApplyStack::events`].
 #[allow(clippy::module_name_repetitions)]
-pub struct ApplyStackEvents<'client, 'apply>(&'apply mut ApplyStack<'client>);
+pub struct ApplyStackEvents<'apply>(&'apply mut ApplyStack);
 
-impl Stream for ApplyStackEvents<'_, '_> {
+impl Stream for ApplyStackEvents<'_> {
     type Item = StackEvent;
 
     fn poll_next(
@@ -824,7 +1603,7 @@ impl Stream for ApplyStackEvents<'_, '_> {
             match self.0.event_stream.as_mut().poll_next(ctx) {
                 task::Poll::Pending => return task::Poll::Pending,
                 task::Poll::Ready(None) => return task::Poll::Ready(None),
-                task::Poll::Ready(Some(Ok(ApplyStackEvent::ChangeSet(_)))) => continue,
+                task::Poll::Ready(Some(Ok(ApplyStackEvent::ChangeSet(_)))) => {}
                 task::Poll::Ready(Some(Ok(ApplyStackEvent::Event(event)))) => {
                     return task::Poll::Ready(Some(event))
                 }
@@ -842,7 +1621,7 @@ impl Stream for ApplyStackEvents<'_, '_> {
 }
 
 /// Events emitted by an `apply_stack` operation internally.
-enum ApplyStackEvent {
+pub(crate) enum ApplyStackEvent {
     /// The change set has been created.
     ChangeSet(ChangeSet),
 
@@ -853,33 +1632,293 @@ enum ApplyStackEvent {
     Output(ApplyStackOutput),
 }
 
-async fn create_change_set_internal(
+#[cfg_attr(not(feature = "s3-staging"), allow(unused_variables))]
+pub(crate) async fn create_change_set_internal(
+    client: &aws_sdk_cloudformation::Client,
+    s3: &S3Client,
+    polling: PollingConfig,
+    mut input: ApplyStackInput,
+) -> Result<Result<ChangeSetWithType, ChangeSet>, ApplyStackError> {
+    let (template_source, template_path) = input.template_source.read().await?;
+    let template = match &template_source {
+        ResolvedTemplateSource::Inline { body }
+            if input.validate_template || input.infer_capabilities =>
+        {
+            Some(
+                Template::parse(body).map_err(|error| ApplyStackError::InvalidTemplate {
+                    template_path: template_path.clone(),
+                    diagnostics: error.diagnostics,
+                })?,
+            )
+        }
+        _ => None,
+    };
+    if let (true, Some(template)) = (input.infer_capabilities, &template) {
+        for capability in template.required_capabilities() {
+            if !input.capabilities.contains(&capability) {
+                input.capabilities.push(capability);
+            }
+        }
+    }
+    // Parameters can only use previous values when updating, so check whether the stack exists.
+    let uses_previous_values = input
+        .parameters
+        .iter()
+        .any(|parameter| parameter.use_previous_value);
+    let previous_parameters = if input.use_previous_parameters || uses_previous_values {
+        describe_previous_parameters(client, polling, &input.stack_name).await?
+    } else {
+        None
+    };
+    let stack_exists = previous_parameters.is_some();
+    let previous_parameters = previous_parameters.filter(|_| input.use_previous_parameters);
+
+    if let (Some(previous_parameters), Some(template)) = (&previous_parameters, &template) {
+        let declared_parameters: Vec<_> = template
+            .parameters
+            .iter()
+            .map(|parameter| parameter.logical_id.clone())
+            .collect();
+        add_previous_parameters(
+            &mut input.parameters,
+            previous_parameters,
+            &declared_parameters,
+        );
+    }
+
+    if let (true, Some(template)) = (input.validate_template, &template) {
+        check_template(template, template_path.as_deref(), &input.parameters)?;
+    }
+
+    #[cfg(feature = "s3-staging")]
+    let template_source = match &input.template_bucket {
+        Some(template_bucket) => template_bucket
+            .stage(&s3.0, template_source)
+            .await
+            .map_err(ApplyStackError::S3Api)?,
+        None => template_source,
+    };
+
+    // Templates that weren't parsed are summarised by CloudFormation instead, once they've been
+    // staged in case they're too large to send inline.
+    if let (Some(previous_parameters), None) = (&previous_parameters, &template) {
+        let declared_parameters =
+            describe_declared_parameters(client, polling, &template_source).await?;
+        add_previous_parameters(
+            &mut input.parameters,
+            previous_parameters,
+            &declared_parameters,
+        );
+    }
+
+    let include_property_values = input.include_property_values;
+    let (mut change_set_type, mut input) =
+        input.configure(template_source, client.create_change_set());
+    if stack_exists && change_set_type == ChangeSetType::Create {
+        change_set_type = ChangeSetType::Update;
+        input = input.change_set_type(change_set_type.into_sdk());
+    }
+    match create_change_set(
+        client,
+        polling,
+        change_set_type,
+        input,
+        include_property_values,
+    )
+    .await
+    {
+        Ok(change_set) => Ok(Ok(change_set)),
+        Err(error) => map_create_change_set_error(error, template_path).map(Err),
+    }
+}
+
+/// Get the parameter keys of an existing stack, for [`ApplyStackInput::use_previous_parameters`].
+///
+/// `None` is returned if the stack doesn't exist, or has never been created (i.e. it's in the
+/// `REVIEW_IN_PROGRESS` state).
+async fn describe_previous_parameters(
+    client: &aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
+    stack_name: &str,
+) -> Result<Option<Vec<String>>, ApplyStackError> {
+    let output = match polling
+        .retry_throttled(polling.change_set_interval, || {
+            client.describe_stacks().stack_name(stack_name).send()
+        })
+        .await
+    {
+        Ok(output) => output,
+        Err(error)
+            if error
+                .message()
+                .is_some_and(|msg| msg.contains("does not exist")) =>
+        {
+            return Ok(None)
+        }
+        Err(error) => return Err(ApplyStackError::from_sdk_error(error)),
+    };
+    let stack = output
+        .stacks
+        .and_then(|mut stacks| stacks.pop())
+        .expected(DESCRIBE_STACKS, "DescribeStacksOutput without stacks")?;
+    if stack.stack_status == Some(aws_sdk_cloudformation::types::StackStatus::ReviewInProgress) {
+        return Ok(None);
+    }
+
+    let keys = stack
+        .parameters
+        .unwrap_or_default()
+        .into_iter()
+        .map(|parameter| {
+            parameter
+                .parameter_key
+                .expected(DESCRIBE_STACKS, "Parameter without parameter_key")
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Some(keys))
+}
+
+/// Get the parameter keys declared by a template, using the `GetTemplateSummary` API.
+async fn describe_declared_parameters(
     client: &aws_sdk_cloudformation::Client,
-    input: ApplyStackInput,
-) -> Result<Result<ChangeSetWithType, ChangeSetWithType>, ApplyStackError> {
-    let (change_set_type, input) = input.configure(client.create_change_set());
-    let error = match create_change_set(client, change_set_type, input).await {
-        Ok(change_set) => return Ok(Ok(change_set)),
-        Err(error) => error,
+    polling: PollingConfig,
+    template_source: &ResolvedTemplateSource,
+) -> Result<Vec<String>, ApplyStackError> {
+    let request = match template_source {
+        ResolvedTemplateSource::Inline { body } => {
+            client.get_template_summary().template_body(body)
+        }
+        ResolvedTemplateSource::S3 { url } => client.get_template_summary().template_url(url),
     };
+    let summary = polling
+        .retry_throttled(polling.change_set_interval, || request.clone().send())
+        .await
+        .map_err(ApplyStackError::from_sdk_error)?;
+    let keys = summary
+        .parameters
+        .unwrap_or_default()
+        .into_iter()
+        .map(|parameter| {
+            parameter.parameter_key.expected(
+                GET_TEMPLATE_SUMMARY,
+                "ParameterDeclaration without parameter_key",
+            )
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(keys)
+}
+
+/// Add the previous parameters that are still declared, and not given, with
+/// [`Parameter::previous`].
+fn add_previous_parameters(
+    parameters: &mut Vec<Parameter>,
+    previous_parameters: &[String],
+    declared_parameters: &[String],
+) {
+    for key in previous_parameters {
+        let given = parameters.iter().any(|parameter| parameter.key == *key);
+        if !given && declared_parameters.contains(key) {
+            parameters.push(Parameter::previous(key));
+        }
+    }
+}
+
+/// Check a template and its parameters, for [`ApplyStackInput::validate_template`].
+#[allow(clippy::result_large_err)]
+fn check_template(
+    template: &Template,
+    template_path: Option<&Path>,
+    parameters: &[Parameter],
+) -> Result<(), ApplyStackError> {
+    let diagnostics: Vec<_> = template
+        .validate()
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .collect();
+    if !diagnostics.is_empty() {
+        return Err(ApplyStackError::InvalidTemplate {
+            template_path: template_path.map(Path::to_path_buf),
+            diagnostics,
+        });
+    }
+
+    let errors = template.check_parameters(parameters);
+    if !errors.is_empty() {
+        return Err(ApplyStackError::InvalidParameters { errors });
+    }
+
+    Ok(())
+}
+
+/// Map a `CreateChangeSetError` into either the no-op change set or an `ApplyStackError`.
+#[allow(clippy::result_large_err)]
+pub(crate) fn map_create_change_set_error(
+    error: CreateChangeSetError,
+    template_path: Option<PathBuf>,
+) -> Result<ChangeSet, ApplyStackError> {
     match error {
-        CreateChangeSetError::NoChanges(change_set) => Ok(Err(change_set)),
+        CreateChangeSetError::NoChanges(change_set) => Ok(change_set),
         CreateChangeSetError::CreateApi(error) => Err(ApplyStackError::from_sdk_error(error)),
         CreateChangeSetError::PollApi(error) => Err(ApplyStackError::from_sdk_error(error)),
         CreateChangeSetError::Blocked { status } => Err(ApplyStackError::Blocked { status }),
-        CreateChangeSetError::Failed(ChangeSetWithType { change_set, .. }) => {
-            Err(ApplyStackError::CreateChangeSetFailed {
-                id: change_set.change_set_id,
-                status: change_set.status,
-                status_reason: change_set
-                    .status_reason
-                    .expect("ChangeSet failed without reason"),
-            })
+        CreateChangeSetError::Failed(change_set) => Err(ApplyStackError::CreateChangeSetFailed {
+            id: change_set.change_set_id,
+            status: change_set.status,
+            status_reason: change_set
+                .status_reason
+                .unwrap_or_else(|| "no reason reported".to_string()),
+            template_path,
+        }),
+        CreateChangeSetError::UnexpectedResponse(error) => Err(error.into()),
+    }
+}
+
+/// Execute a change set and follow the resulting stack operation to its conclusion.
+///
+/// The stream will emit `ApplyStackEvent::Event`s for the stack events, and finally an
+/// `ApplyStackEvent::Output` if the operation succeeds.
+pub(crate) fn execute_change_set_internal(
+    client: &aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
+    stack_id: String,
+    change_set_id: String,
+    change_set_type: ChangeSetType,
+    disable_rollback: bool,
+) -> impl Stream<Item = Result<ApplyStackEvent, ApplyStackError>> + '_ {
+    try_stream! {
+        let mut operation =
+            execute_change_set(client, polling, stack_id.clone(), change_set_id.clone(), change_set_type, disable_rollback)
+                .await
+                .map_err(|error| match error {
+                    ExecuteChangeSetError::ExecuteApi(error) => ApplyStackError::from_sdk_error(*error),
+                    ExecuteChangeSetError::Blocked { status } => ApplyStackError::Blocked { status },
+                })?;
+        while let Some(event) = operation.try_next().await? {
+            yield ApplyStackEvent::Event(event);
+        }
+
+        let warning = match operation.verify() {
+            Err(StackOperationError::Failure(failure)) => {
+                Err(ApplyStackError::Failure(failure))?;
+                unreachable!()
+            }
+            Ok(()) => None,
+            Err(StackOperationError::Warning(warning)) => Some(warning),
+        };
+
+        let output = describe_output(client, stack_id, change_set_id).await?;
+
+        match warning {
+            Some(warning) => {
+                Err(ApplyStackError::Warning { output, warning })?;
+                unreachable!()
+            }
+            None => yield ApplyStackEvent::Output(output),
         }
     }
 }
 
-async fn describe_output(
+pub(crate) async fn describe_output(
     client: &aws_sdk_cloudformation::Client,
     stack_id: String,
     change_set_id: String,
@@ -891,15 +1930,29 @@ async fn describe_output(
         .map_err(ApplyStackError::from_sdk_error)
         .await?
         .stacks
-        .expect("DescribeStacksOutput without stacks")
-        .pop()
-        .expect("DescribeStacksOutput empty stacks");
-    Ok(ApplyStackOutput::from_raw(stack, change_set_id))
+        .and_then(|mut stacks| stacks.pop())
+        .expected(DESCRIBE_STACKS, "DescribeStacksOutput without stacks")?;
+    Ok(ApplyStackOutput::from_raw(stack, change_set_id)?)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Capability;
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use assert_matches::assert_matches;
+
+    use super::{
+        check_template, was_cancelled, ApplyStackError, Capability, Parameter,
+        ResolvedTemplateSource, StackParameter, TemplateSource,
+    };
+    use crate::{
+        template::{DiagnosticKind, ParameterErrorKind, Template},
+        ChangeSetStatus, StackFailure, StackStatus,
+    };
 
     #[test]
     fn test_parse_display() {
@@ -915,5 +1968,214 @@ mod tests {
             Capability::AutoExpand,
             "CAPABILITY_AUTO_EXPAND".parse().unwrap(),
         );
+        assert_eq!(
+            Capability::Unknown("CAPABILITY_FROBNICATE".to_string()),
+            "CAPABILITY_FROBNICATE".parse().unwrap(),
+        );
+    }
+
+    #[tokio::test]
+    async fn template_files_are_read() {
+        let path = std::env::temp_dir().join(format!(
+            "cloudformatious-template-{}.yaml",
+            std::process::id()
+        ));
+        fs::write(&path, "Resources: {}\n").unwrap();
+
+        let source = TemplateSource::file(&path).read().await;
+        fs::remove_file(&path).unwrap();
+        assert_matches!(source, Ok((ResolvedTemplateSource::Inline { body }, Some(source_path))) => {
+            assert_eq!(body, "Resources: {}\n");
+            assert_eq!(source_path, path);
+        });
+    }
+
+    #[tokio::test]
+    async fn template_directories_are_read() {
+        let directory = std::env::temp_dir().join(format!(
+            "cloudformatious-template-directory-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("template.json"), "{}").unwrap();
+        fs::write(directory.join("template.yml"), "Resources: {}\n").unwrap();
+
+        let source = TemplateSource::file(&directory).read().await;
+        fs::remove_dir_all(&directory).unwrap();
+        assert_matches!(source, Ok((ResolvedTemplateSource::Inline { body }, Some(path))) => {
+            assert_eq!(body, "Resources: {}\n");
+            assert_eq!(path, directory.join("template.yml"));
+        });
+
+        fs::create_dir_all(&directory).unwrap();
+        let error = TemplateSource::file(&directory).read().await.unwrap_err();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "failed to read template file {}: directory contains none of template.yaml, \
+                 template.yml, template.json",
+                directory.display()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn template_file_errors_include_path() {
+        let path = Path::new("does/not/exist/template.json");
+
+        let error = TemplateSource::file(path).read().await.unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("failed to read template file does/not/exist/template.json: "));
+        assert_matches!(error, ApplyStackError::TemplateFile { path: error_path, .. } => {
+            assert_eq!(error_path, path);
+        });
+    }
+
+    #[test]
+    fn check_template_reports_errors() {
+        let source = r#"{
+            "Parameters": {"Name": {"Type": "String"}},
+            "Resources": {"Topic": {"Type": "AWS::SNS::Topic", "Condition": "Never"}}
+        }"#;
+        assert_matches!(
+            check_template(&Template::parse(source).unwrap(), None, &[]),
+            Err(ApplyStackError::InvalidTemplate { template_path: None, diagnostics }) => {
+                assert_matches!(
+                    diagnostics.as_slice(),
+                    [diagnostic] if diagnostic.kind == DiagnosticKind::UndefinedCondition {
+                        condition: "Never".to_string(),
+                    }
+                );
+            }
+        );
+        let error = check_template(
+            &Template::parse(source).unwrap(),
+            Some(Path::new("stacks/network.json")),
+            &[],
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Template stacks/network.json is invalid: "));
+
+        let template = Template::parse(&source.replace(r#", "Condition": "Never""#, "")).unwrap();
+        assert_matches!(
+            check_template(&template, None, &[]),
+            Err(ApplyStackError::InvalidParameters { errors }) => {
+                assert_matches!(
+                    errors.as_slice(),
+                    [error] if error.key == "Name" && error.kind == ParameterErrorKind::Missing
+                );
+            }
+        );
+
+        let parameters = [Parameter::new("Name", "hello")];
+        assert_matches!(check_template(&template, None, &parameters), Ok(()));
+    }
+
+    #[test]
+    fn parameters_into_sdk() {
+        let parameter = Parameter::new("Name", "hello").into_sdk();
+        assert_eq!(parameter.parameter_key(), Some("Name"));
+        assert_eq!(parameter.parameter_value(), Some("hello"));
+        assert_eq!(parameter.use_previous_value(), None);
+
+        let parameter = Parameter::previous("Password").into_sdk();
+        assert_eq!(parameter.parameter_key(), Some("Password"));
+        assert_eq!(parameter.parameter_value(), None);
+        assert_eq!(parameter.use_previous_value(), Some(true));
+    }
+
+    #[test]
+    fn stack_parameters_from_sdk() {
+        let parameter = aws_sdk_cloudformation::types::Parameter::builder()
+            .parameter_key("ImageId")
+            .parameter_value("/aws/service/ami-amazon-linux-latest/al2023-ami-kernel-6.1-x86_64")
+            .resolved_value("ami-0123456789abcdef0")
+            .build();
+        assert_eq!(
+            StackParameter::from_sdk(parameter).unwrap(),
+            StackParameter {
+                key: "ImageId".to_string(),
+                value: Some(
+                    "/aws/service/ami-amazon-linux-latest/al2023-ami-kernel-6.1-x86_64".to_string()
+                ),
+                resolved_value: Some("ami-0123456789abcdef0".to_string()),
+            }
+        );
+
+        let parameter = aws_sdk_cloudformation::types::Parameter::builder()
+            .parameter_key("Name")
+            .parameter_value("hello")
+            .build();
+        assert_eq!(
+            StackParameter::from_sdk(parameter).unwrap(),
+            StackParameter {
+                key: "Name".to_string(),
+                value: Some("hello".to_string()),
+                resolved_value: None,
+            }
+        );
+
+        let parameter = aws_sdk_cloudformation::types::Parameter::builder().build();
+        assert!(StackParameter::from_sdk(parameter).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_parameter_without_use_previous_value() {
+        let parameter: Parameter =
+            serde_json::from_str(r#"{"key": "Name", "value": "hello"}"#).unwrap();
+        assert_eq!(parameter.key, "Name");
+        assert_eq!(parameter.value, "hello");
+        assert!(!parameter.use_previous_value);
+    }
+
+    #[test]
+    fn cancelled_failures() {
+        let failure = |stack_status| StackFailure {
+            stack_id: "arn:aws:cloudformation:eu-west-1:123456789012:stack/stack/1".to_string(),
+            stack_status,
+            stack_status_reason: "oh no".to_string(),
+            resource_events: Vec::new(),
+            rollback_started_at: None,
+        };
+
+        let cancelled = AtomicBool::new(false);
+        assert!(!was_cancelled(
+            &cancelled,
+            &failure(StackStatus::UpdateRollbackComplete)
+        ));
+
+        cancelled.store(true, Ordering::SeqCst);
+        assert!(was_cancelled(
+            &cancelled,
+            &failure(StackStatus::UpdateRollbackComplete)
+        ));
+        assert!(was_cancelled(
+            &cancelled,
+            &failure(StackStatus::UpdateRollbackFailed)
+        ));
+        assert!(!was_cancelled(
+            &cancelled,
+            &failure(StackStatus::UpdateFailed)
+        ));
+    }
+
+    #[test]
+    fn create_change_set_failed_includes_template_path() {
+        let error = ApplyStackError::CreateChangeSetFailed {
+            id: "arn:aws:cloudformation:eu-west-1:123456789012:changeSet/cs/1".to_string(),
+            status: ChangeSetStatus::Failed,
+            status_reason: "Template format error".to_string(),
+            template_path: Some(PathBuf::from("stacks/network.json")),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Change set arn:aws:cloudformation:eu-west-1:123456789012:changeSet/cs/1 for template \
+             stacks/network.json failed to create; terminal status: FAILED (Template format error)"
+        );
     }
 }
@@ -0,0 +1,285 @@
+use std::{fmt, future::Future, pin::Pin, task};
+
+use async_stream::try_stream;
+use aws_sdk_cloudformation::{
+    error::SdkError,
+    operation::{
+        cancel_update_stack::builders::CancelUpdateStackFluentBuilder,
+        describe_stacks::DescribeStacksError,
+    },
+};
+use chrono::Utc;
+use futures_util::{Stream, TryStreamExt};
+
+use crate::{
+    stack::{
+        describe_stack_id, StackEventsError, StackOperation, StackOperationError,
+        StackOperationStatus,
+    },
+    unexpected_response::UnexpectedResponse,
+    PollingConfig, StackEvent, StackFailure, StackStatus, StackWarning,
+};
+
+/// The input for the `cancel_update_stack` operation.
+///
+/// You can create a cancel update stack input via the [`new`](Self::new) associated function.
+/// Setters are also available to make constructing sparse inputs more ergonomic.
+///
+/// ```no_run
+/// use cloudformatious::CancelUpdateStackInput;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = aws_config::load_from_env().await;
+/// let client = cloudformatious::Client::new(&config);
+/// let input = CancelUpdateStackInput::new("my-stack").set_client_request_token("hello");
+/// client.cancel_update_stack(input).await?;
+/// // ...
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::module_name_repetitions)]
+pub struct CancelUpdateStackInput {
+    /// A unique identifier for this `CancelUpdateStack` request. Specify this token if you plan to
+    /// retry requests so that AWS CloudFormation knows that you're not attempting to cancel an
+    /// update on a stack with the same name. You might retry `CancelUpdateStack` requests to
+    /// ensure that AWS CloudFormation successfully received them.
+    pub client_request_token: Option<String>,
+
+    /// The name or the unique stack ID that is associated with the stack.
+    pub stack_name: String,
+}
+
+impl CancelUpdateStackInput {
+    /// Construct an input for the given `stack_name`.
+    pub fn new(stack_name: impl Into<String>) -> Self {
+        Self {
+            stack_name: stack_name.into(),
+
+            client_request_token: None,
+        }
+    }
+
+    /// Set the value for `client_request_token`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_client_request_token(mut self, client_request_token: impl Into<String>) -> Self {
+        self.client_request_token = Some(client_request_token.into());
+        self
+    }
+
+    fn configure(self, input: CancelUpdateStackFluentBuilder) -> CancelUpdateStackFluentBuilder {
+        input
+            .set_client_request_token(self.client_request_token)
+            .stack_name(self.stack_name)
+    }
+}
+
+/// Errors emitted by a `cancel_update_stack` operation.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum CancelUpdateStackError {
+    /// A CloudFormation API error occurred.
+    ///
+    /// This is likely to be due to the stack not being in the `UPDATE_IN_PROGRESS` state, or
+    /// missing CloudFormation permissions. The inner error should have a descriptive message.
+    ///
+    /// **Note:** the inner error will always be some variant of [`SdkError`], but since they are
+    /// generic over the type of service errors we either need a variant per API used, or `Box`. If
+    /// you do need to programmatically match a particular API error you can use [`Box::downcast`].
+    CloudFormationApi(Box<dyn std::error::Error + Send + Sync>),
+
+    /// The update was cancelled but the rollback failed.
+    Failure(StackFailure),
+
+    /// The update was cancelled and rolled back with warnings.
+    Warning(StackWarning),
+
+    /// CloudFormation returned a response we didn't expect.
+    ///
+    /// This could be due to a missing field, or a value (such as a status) that we don't
+    /// recognise. It likely indicates a change in CloudFormation's behaviour that this library
+    /// hasn't caught up with.
+    UnexpectedResponse {
+        /// The CloudFormation API that returned the response, e.g. `DescribeStackEvents`.
+        api: &'static str,
+
+        /// A description of what was unexpected about the response.
+        detail: String,
+    },
+}
+
+impl CancelUpdateStackError {
+    fn from_sdk_error<E: std::error::Error + Send + Sync + 'static>(error: SdkError<E>) -> Self {
+        Self::CloudFormationApi(error.into())
+    }
+}
+
+impl From<SdkError<DescribeStacksError>> for CancelUpdateStackError {
+    fn from(error: SdkError<DescribeStacksError>) -> Self {
+        Self::from_sdk_error(error)
+    }
+}
+
+impl From<UnexpectedResponse> for CancelUpdateStackError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse {
+            api: error.api,
+            detail: error.detail,
+        }
+    }
+}
+
+impl From<StackEventsError> for CancelUpdateStackError {
+    fn from(error: StackEventsError) -> Self {
+        match error {
+            StackEventsError::Api(error) => Self::from_sdk_error(*error),
+            StackEventsError::UnexpectedResponse(error) => error.into(),
+        }
+    }
+}
+
+impl fmt::Display for CancelUpdateStackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CloudFormationApi(error) => {
+                write!(f, "CloudFormation API error: {error:#?}")
+            }
+            Self::Failure(failure) => write!(f, "{failure}"),
+            Self::Warning(warning) => write!(f, "{warning}"),
+            Self::UnexpectedResponse { api, detail } => {
+                write!(f, "unexpected response from {api}: {detail}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CancelUpdateStackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CloudFormationApi(error) => Some(error.as_ref()),
+            Self::Failure { .. } | Self::Warning { .. } | Self::UnexpectedResponse { .. } => None,
+        }
+    }
+}
+
+/// An ongoing `cancel_update_stack` operation.
+///
+/// This implements `Future`, which will simply wait for the stack to finish rolling back. If you
+/// want to observe progress, see [`CancelUpdateStack::events`].
+pub struct CancelUpdateStack {
+    event_stream: Pin<Box<dyn Stream<Item = Result<StackEvent, CancelUpdateStackError>> + Send>>,
+    output: Option<Result<(), CancelUpdateStackError>>,
+}
+
+impl CancelUpdateStack {
+    pub(crate) fn new(
+        client: aws_sdk_cloudformation::Client,
+        polling: PollingConfig,
+        input: CancelUpdateStackInput,
+    ) -> Self {
+        let event_stream = try_stream! {
+            let stack_id =
+                describe_stack_id::<CancelUpdateStackError>(&client, &input.stack_name).await?;
+
+            let started_at = Utc::now();
+            input
+                .configure(client.cancel_update_stack())
+                .send()
+                .await
+                .map_err(CancelUpdateStackError::from_sdk_error)?;
+
+            let mut operation =
+                StackOperation::new(&client, polling, stack_id, started_at, check_operation_status);
+            while let Some(event) = operation.try_next().await? {
+                yield event;
+            }
+
+            match operation.verify() {
+                Ok(()) => {}
+                Err(StackOperationError::Failure(failure)) => {
+                    Err(CancelUpdateStackError::Failure(failure))?;
+                    unreachable!()
+                }
+                Err(StackOperationError::Warning(warning)) => {
+                    Err(CancelUpdateStackError::Warning(warning))?;
+                    unreachable!()
+                }
+            }
+        };
+        Self {
+            event_stream: Box::pin(event_stream),
+            output: None,
+        }
+    }
+
+    /// Get a `Stream` of `StackEvent`s.
+    pub fn events(&mut self) -> CancelUpdateStackEvents<'_> {
+        CancelUpdateStackEvents(self)
+    }
+
+    fn poll_next_internal(&mut self, ctx: &mut task::Context) -> task::Poll<Option<StackEvent>> {
+        match self.event_stream.as_mut().poll_next(ctx) {
+            task::Poll::Pending => task::Poll::Pending,
+            task::Poll::Ready(None) => {
+                self.output.get_or_insert(Ok(()));
+                task::Poll::Ready(None)
+            }
+            task::Poll::Ready(Some(Ok(event))) => task::Poll::Ready(Some(event)),
+            task::Poll::Ready(Some(Err(error))) => {
+                self.output.replace(Err(error));
+                task::Poll::Ready(None)
+            }
+        }
+    }
+}
+
+impl Future for CancelUpdateStack {
+    type Output = Result<(), CancelUpdateStackError>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context) -> task::Poll<Self::Output> {
+        loop {
+            match self.poll_next_internal(ctx) {
+                task::Poll::Pending => return task::Poll::Pending,
+                task::Poll::Ready(None) => {
+                    return task::Poll::Ready(
+                        self.output
+                            .take()
+                            .expect("end of stream without err or output"),
+                    )
+                }
+                task::Poll::Ready(Some(_)) => {}
+            }
+        }
+    }
+}
+
+/// Return value of [`CancelUpdateStack::events`].
+#[allow(clippy::module_name_repetitions)]
+pub struct CancelUpdateStackEvents<'cancel>(&'cancel mut CancelUpdateStack);
+
+impl Stream for CancelUpdateStackEvents<'_> {
+    type Item = StackEvent;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut task::Context,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.0.poll_next_internal(ctx)
+    }
+}
+
+fn check_operation_status(stack_status: &StackStatus) -> StackOperationStatus {
+    match stack_status {
+        // The update may still be in progress when we start following events.
+        StackStatus::UpdateInProgress
+        | StackStatus::UpdateRollbackInProgress
+        | StackStatus::UpdateRollbackCompleteCleanupInProgress => StackOperationStatus::InProgress,
+        StackStatus::UpdateRollbackComplete => StackOperationStatus::Complete,
+        StackStatus::UpdateRollbackFailed => StackOperationStatus::Failed,
+        StackStatus::Unknown(_) => StackOperationStatus::unknown(stack_status),
+        _ => StackOperationStatus::Unexpected,
+    }
+}
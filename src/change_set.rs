@@ -1,6 +1,8 @@
 //! Helpers for working with change sets.
 
-use std::{convert::TryFrom, fmt, time::Duration};
+pub mod render;
+
+use std::{convert::TryFrom, fmt, future::Future, pin::Pin, sync::LazyLock};
 
 use aws_sdk_cloudformation::{
     error::{ProvideErrorMetadata, SdkError},
@@ -17,15 +19,17 @@ use tokio::time::{interval_at, Instant};
 
 use crate::{
     stack::{StackOperation, StackOperationStatus},
-    BlockedStackStatus, Capability, ChangeSetStatus, StackStatus, Tag,
+    unexpected_response::{Expected, UnexpectedResponse},
+    BlockedStackStatus, Capability, ChangeSetStatus, PollingConfig, StackStatus, Status, Tag,
 };
 
-const POLL_INTERVAL_CHANGE_SET: Duration = Duration::from_secs(1);
+const DESCRIBE_CHANGE_SET: &str = "DescribeChangeSet";
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum ChangeSetType {
     Create,
     Update,
+    Import,
 }
 
 impl ChangeSetType {
@@ -33,6 +37,7 @@ impl ChangeSetType {
         match self {
             ChangeSetType::Create => aws_sdk_cloudformation::types::ChangeSetType::Create,
             ChangeSetType::Update => aws_sdk_cloudformation::types::ChangeSetType::Update,
+            ChangeSetType::Import => aws_sdk_cloudformation::types::ChangeSetType::Import,
         }
     }
 }
@@ -42,12 +47,14 @@ impl fmt::Display for ChangeSetType {
         match self {
             Self::Create => write!(f, "CREATE"),
             Self::Update => write!(f, "UPDATE"),
+            Self::Import => write!(f, "IMPORT"),
         }
     }
 }
 
 /// A planned set of changes to apply to a CloudFormation stack.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::module_name_repetitions)]
 pub struct ChangeSet {
     /// Capabilities that were explicitly acknowledged when the change set was created.
@@ -111,70 +118,92 @@ pub struct ChangeSet {
 }
 
 impl ChangeSet {
-    fn from_sdk(change_set: DescribeChangeSetOutput) -> Self {
-        Self {
+    fn from_sdk(change_set: DescribeChangeSetOutput) -> Result<Self, UnexpectedResponse> {
+        Ok(Self {
             capabilities: change_set
                 .capabilities
                 .unwrap_or_default()
                 .into_iter()
                 .map(|capability| {
-                    capability
-                        .as_str()
-                        .parse()
-                        .expect("DescribeChangeSetOutput with invalid Capability")
+                    capability.as_str().parse().expected(
+                        DESCRIBE_CHANGE_SET,
+                        "DescribeChangeSetOutput with invalid Capability",
+                    )
                 })
-                .collect(),
-            change_set_id: change_set
-                .change_set_id
-                .expect("DescribeChangeSetOutput without change_set_id"),
-            change_set_name: change_set
-                .change_set_name
-                .expect("DescribeChangeSetOutput without change_set_name"),
+                .collect::<Result<_, _>>()?,
+            change_set_id: change_set.change_set_id.expected(
+                DESCRIBE_CHANGE_SET,
+                "DescribeChangeSetOutput without change_set_id",
+            )?,
+            change_set_name: change_set.change_set_name.expected(
+                DESCRIBE_CHANGE_SET,
+                "DescribeChangeSetOutput without change_set_name",
+            )?,
             changes: change_set
                 .changes
                 .unwrap_or_default()
                 .into_iter()
                 .map(ResourceChange::from_sdk)
-                .collect(),
+                .collect::<Result<_, _>>()?,
             creation_time: change_set
                 .creation_time
-                .expect("DescribeChangeSetOutput without creation_time")
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "DescribeChangeSetOutput without creation_time",
+                )?
                 .to_chrono_utc()
-                .expect("invalid creation_time"),
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "DescribeChangeSetOutput with invalid creation_time",
+                )?,
             description: change_set.description,
             execution_status: change_set
                 .execution_status
-                .expect("DescribeChangeSetOutput without execution_status")
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "DescribeChangeSetOutput without execution_status",
+                )?
                 .as_str()
                 .parse()
-                .expect("DescribeChangeSetOutput with invalid execution_status"),
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "DescribeChangeSetOutput with invalid execution_status",
+                )?,
             notification_arns: change_set.notification_arns.unwrap_or_default(),
             parameters: change_set
                 .parameters
                 .unwrap_or_default()
                 .into_iter()
                 .map(Parameter::from_sdk)
-                .collect(),
-            stack_id: change_set
-                .stack_id
-                .expect("DescribeChangeSetOutput without stack_id"),
-            stack_name: change_set
-                .stack_name
-                .expect("DescribeChangeSetOutput without stack_name"),
+                .collect::<Result<_, _>>()?,
+            stack_id: change_set.stack_id.expected(
+                DESCRIBE_CHANGE_SET,
+                "DescribeChangeSetOutput without stack_id",
+            )?,
+            stack_name: change_set.stack_name.expected(
+                DESCRIBE_CHANGE_SET,
+                "DescribeChangeSetOutput without stack_name",
+            )?,
             status: change_set
                 .status
-                .expect("DescribeChangeSetOutput without status")
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "DescribeChangeSetOutput without status",
+                )?
                 .as_str()
                 .parse()
-                .expect("DescribeChangeSetOutput unexpected status"),
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "DescribeChangeSetOutput with invalid status",
+                )?,
             status_reason: change_set.status_reason,
             tags: change_set
                 .tags
                 .unwrap_or_default()
                 .into_iter()
-                .map(Tag::from_sdk)
-                .collect(),
-        }
+                .map(|tag| Tag::from_sdk(DESCRIBE_CHANGE_SET, tag))
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -203,6 +232,7 @@ pub enum ExecutionStatus {
 
 /// A parameter set for a change set.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parameter {
     /// The key associated with the parameter.
     pub parameter_key: String,
@@ -225,21 +255,24 @@ pub struct Parameter {
 }
 
 impl Parameter {
-    fn from_sdk(param: aws_sdk_cloudformation::types::Parameter) -> Self {
-        Self {
+    fn from_sdk(
+        param: aws_sdk_cloudformation::types::Parameter,
+    ) -> Result<Self, UnexpectedResponse> {
+        Ok(Self {
             parameter_key: param
                 .parameter_key
-                .expect("Parameter without parameter_key"),
+                .expected(DESCRIBE_CHANGE_SET, "Parameter without parameter_key")?,
             parameter_value: param.parameter_value,
             use_previous_value: param.use_previous_value,
             resolved_value: param.resolved_value,
-        }
+        })
     }
 }
 
 /// The resource and the action that AWS CloudFormation will perform on it if you execute this
 /// change set.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ResourceChange {
     /// The action that AWS CloudFormation takes on the resource.
     pub action: Action,
@@ -254,43 +287,90 @@ pub struct ResourceChange {
 
     /// The type of AWS CloudFormation resource.
     pub resource_type: String,
+
+    /// The ID of the change set for a nested stack.
+    ///
+    /// This is only present for `AWS::CloudFormation::Stack` resources when the change set was
+    /// created with [`include_nested_stacks`](crate::ApplyStackInput::include_nested_stacks).
+    pub change_set_id: Option<String>,
+
+    /// The change set for a nested stack, describing the changes to the nested stack's resources.
+    ///
+    /// This is present whenever [`change_set_id`](Self::change_set_id) is, and may itself contain
+    /// nested change sets for deeper nested stacks.
+    pub nested_change_set: Option<Box<ChangeSet>>,
+
+    /// The resource's configuration before the change is executed.
+    ///
+    /// This is only present when the change set was described with
+    /// [`include_property_values`](crate::ApplyStackInput::include_property_values).
+    pub before_context: Option<serde_json::Value>,
+
+    /// The resource's configuration after the change is executed.
+    ///
+    /// This is only present when the change set was described with
+    /// [`include_property_values`](crate::ApplyStackInput::include_property_values).
+    pub after_context: Option<serde_json::Value>,
 }
 
 impl ResourceChange {
-    fn from_sdk(change: Change) -> Self {
-        assert!(
-            matches!(
-                change.r#type,
-                Some(aws_sdk_cloudformation::types::ChangeType::Resource)
-            ),
-            "Change with unexpected type {:?}",
-            change.r#type
-        );
+    fn from_sdk(change: Change) -> Result<Self, UnexpectedResponse> {
+        if !matches!(
+            change.r#type,
+            Some(aws_sdk_cloudformation::types::ChangeType::Resource)
+        ) {
+            return Err(UnexpectedResponse::new(
+                DESCRIBE_CHANGE_SET,
+                format!("Change with unexpected type {:?}", change.r#type),
+            ));
+        }
         let change = change
             .resource_change
-            .expect("Change without resource_change");
+            .expected(DESCRIBE_CHANGE_SET, "Change without resource_change")?;
         let resource_type = change
             .resource_type
-            .expect("ResourceChange without resource_type");
-        Self {
+            .expected(DESCRIBE_CHANGE_SET, "ResourceChange without resource_type")?;
+        Ok(Self {
             action: Action::from_sdk(
                 &resource_type,
-                &change.action.expect("ResourceChange without action"),
+                &change
+                    .action
+                    .expected(DESCRIBE_CHANGE_SET, "ResourceChange without action")?,
                 change.details,
                 change.replacement,
                 change.scope,
-            ),
-            logical_resource_id: change
-                .logical_resource_id
-                .expect("ResourceChange without logical_resource_id"),
+            )?,
+            logical_resource_id: change.logical_resource_id.expected(
+                DESCRIBE_CHANGE_SET,
+                "ResourceChange without logical_resource_id",
+            )?,
             physical_resource_id: change.physical_resource_id,
             resource_type,
-        }
+            change_set_id: change.change_set_id,
+            nested_change_set: None,
+            before_context: change
+                .before_context
+                .map(|context| serde_json::from_str(&context))
+                .transpose()
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "ResourceChange with invalid before_context",
+                )?,
+            after_context: change
+                .after_context
+                .map(|context| serde_json::from_str(&context))
+                .transpose()
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "ResourceChange with invalid after_context",
+                )?,
+        })
     }
 }
 
 /// The action that AWS CloudFormation takes on a resource.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
     /// Adds a new resource.
     Add,
@@ -306,6 +386,18 @@ pub enum Action {
 
     /// Exact action for the resource cannot be determined.
     Dynamic,
+
+    /// Brings the resource's configuration in line with its actual state, in a drift-aware change
+    /// set.
+    ///
+    /// CloudFormation may not report a replacement or scope for these changes, in which case the
+    /// replacement is [`False`](Replacement::False) and the scope is empty.
+    SyncWithActual(ModifyDetail),
+
+    /// An action that this version of the library doesn't recognise.
+    ///
+    /// This carries the raw action string. The change's details are not interpreted.
+    Unknown(String),
 }
 
 impl Action {
@@ -315,48 +407,64 @@ impl Action {
         details: Option<Vec<aws_sdk_cloudformation::types::ResourceChangeDetail>>,
         replacement: Option<aws_sdk_cloudformation::types::Replacement>,
         scope: Option<Vec<aws_sdk_cloudformation::types::ResourceAttribute>>,
-    ) -> Self {
+    ) -> Result<Self, UnexpectedResponse> {
         match action {
             ChangeAction::Add
             | ChangeAction::Remove
             | ChangeAction::Import
             | ChangeAction::Dynamic => {
-                assert!(
-                    matches!(details.as_deref(), None | Some([])),
-                    "ResourceChange with action {:?} and details",
-                    action
-                );
-                assert!(
-                    replacement.is_none(),
-                    "ResourceChange with action {:?} and replacement",
-                    action
-                );
-                assert!(
-                    scope.unwrap_or_default().is_empty(),
-                    "ResourceChange with action {:?} and scope",
-                    action
-                );
+                let unexpected = |field| {
+                    UnexpectedResponse::new(
+                        DESCRIBE_CHANGE_SET,
+                        format!("ResourceChange with action {action:?} and {field}"),
+                    )
+                };
+                if !matches!(details.as_deref(), None | Some([])) {
+                    return Err(unexpected("details"));
+                }
+                if replacement.is_some() {
+                    return Err(unexpected("replacement"));
+                }
+                if !scope.unwrap_or_default().is_empty() {
+                    return Err(unexpected("scope"));
+                }
                 match action {
-                    ChangeAction::Add => Self::Add,
-                    ChangeAction::Remove => Self::Remove,
-                    ChangeAction::Import => Self::Import,
-                    ChangeAction::Dynamic => Self::Dynamic,
+                    ChangeAction::Add => Ok(Self::Add),
+                    ChangeAction::Remove => Ok(Self::Remove),
+                    ChangeAction::Import => Ok(Self::Import),
+                    ChangeAction::Dynamic => Ok(Self::Dynamic),
                     _ => unreachable!(),
                 }
             }
-            ChangeAction::Modify => Self::Modify(ModifyDetail::from_sdk(
+            ChangeAction::Modify => Ok(Self::Modify(ModifyDetail::from_sdk(
                 resource_type,
-                details.expect("ResourceChange with action \"Modify\" without details"),
-                &replacement.expect("ResourceChange with action \"Modify\" without replacement"),
-                scope.expect("ResourceChange with action \"Modify\" without scope"),
-            )),
-            _ => panic!("ResourceChange with invalid action {:?}", action),
+                details.expected(
+                    DESCRIBE_CHANGE_SET,
+                    "ResourceChange with action \"Modify\" without details",
+                )?,
+                &replacement.expected(
+                    DESCRIBE_CHANGE_SET,
+                    "ResourceChange with action \"Modify\" without replacement",
+                )?,
+                scope.expected(
+                    DESCRIBE_CHANGE_SET,
+                    "ResourceChange with action \"Modify\" without scope",
+                )?,
+            )?)),
+            ChangeAction::SyncWithActual => Ok(Self::SyncWithActual(ModifyDetail::from_sdk(
+                resource_type,
+                details.unwrap_or_default(),
+                &replacement.unwrap_or(aws_sdk_cloudformation::types::Replacement::False),
+                scope.unwrap_or_default(),
+            )?)),
+            _ => Ok(Self::Unknown(action.as_str().to_string())),
         }
     }
 }
 
 /// Additional detail for resource modifications.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModifyDetail {
     /// A list of structures that describe the changes that AWS CloudFormation will make to the
     /// resource.
@@ -376,26 +484,26 @@ impl ModifyDetail {
         details: Vec<aws_sdk_cloudformation::types::ResourceChangeDetail>,
         replacement: &aws_sdk_cloudformation::types::Replacement,
         scope: Vec<aws_sdk_cloudformation::types::ResourceAttribute>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, UnexpectedResponse> {
+        Ok(Self {
             details: details
                 .into_iter()
                 .map(|detail| ResourceChangeDetail::from_sdk(resource_type, detail))
-                .collect(),
-            replacement: replacement
-                .as_str()
-                .parse()
-                .expect("ResourceChange with invalid replacement"),
+                .collect::<Result<_, _>>()?,
+            replacement: replacement.as_str().parse().expected(
+                DESCRIBE_CHANGE_SET,
+                "ResourceChange with invalid replacement",
+            )?,
             scope: scope
                 .into_iter()
                 .map(|scope| {
                     scope
                         .as_str()
                         .parse::<ModifyScope>()
-                        .expect("ResourceChange with invalid scope")
+                        .expected(DESCRIBE_CHANGE_SET, "ResourceChange with invalid scope")
                 })
-                .collect(),
-        }
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -438,6 +546,7 @@ mod modify_scope {
     /// Indicates which resource attribute is triggering this update.
     #[derive(Debug, enumset::EnumSetType, parse_display::Display, parse_display::FromStr)]
     #[enumset(no_ops)]
+    #[cfg_attr(feature = "serde", enumset(serialize_repr = "list"))]
     pub enum ModifyScope {
         /// A change to the resource's properties.
         Properties,
@@ -454,6 +563,9 @@ mod modify_scope {
         /// A change to the resource's deletion policy.
         DeletionPolicy,
 
+        /// A change to the resource's update replace policy.
+        UpdateReplacePolicy,
+
         /// A change to the resource's tags.
         Tags,
     }
@@ -462,8 +574,9 @@ pub use modify_scope::ModifyScope;
 
 /// A change that AWS CloudFormation will make to a resource.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ResourceChangeDetail {
-    /// The group to which the CausingEntity value belongs.
+    /// The group to which the `CausingEntity` value belongs.
     ///
     /// This will not be present if the change source cannot be described by CloudFormation's
     /// limited vocabulary, such as tags supplied when creating a change set.
@@ -477,37 +590,94 @@ pub struct ResourceChangeDetail {
     /// See [`Evaluation`] for more information.
     pub evaluation: Evaluation,
 
+    /// The type of change that will be made to the target, in a drift-aware change set.
+    ///
+    /// See [`AttributeChangeType`] for more information.
+    pub attribute_change_type: Option<AttributeChangeType>,
+
     /// A structure that describes the field that AWS CloudFormation will change and whether the
     /// resource will be recreated.
     pub target: ResourceTargetDefinition,
+
+    /// The path to the changed value within the resource, e.g. `/Properties/Tags/0/Value`.
+    ///
+    /// This is only present when the change set was described with
+    /// [`include_property_values`](crate::ApplyStackInput::include_property_values).
+    pub path: Option<String>,
+
+    /// The value before the change is executed.
+    ///
+    /// This is only present when the change set was described with
+    /// [`include_property_values`](crate::ApplyStackInput::include_property_values), and the
+    /// value existed before the change. CloudFormation reports values as strings, which are
+    /// interpreted as JSON where possible (e.g. for lists and objects), and are otherwise kept as
+    /// JSON strings.
+    pub before_value: Option<serde_json::Value>,
+
+    /// The value after the change is executed.
+    ///
+    /// This is only present when the change set was described with
+    /// [`include_property_values`](crate::ApplyStackInput::include_property_values), and the
+    /// value exists after the change. See [`before_value`](Self::before_value) for details of how
+    /// the value is interpreted.
+    pub after_value: Option<serde_json::Value>,
 }
 
 impl ResourceChangeDetail {
     fn from_sdk(
         resource_type: &str,
         details: aws_sdk_cloudformation::types::ResourceChangeDetail,
-    ) -> Self {
+    ) -> Result<Self, UnexpectedResponse> {
         let causing_entity = details.causing_entity;
-        Self {
+        let mut target = details
+            .target
+            .expected(DESCRIBE_CHANGE_SET, "ResourceChangeDetail without target")?;
+        let path = target.path.take();
+        let before_value = target.before_value.take().map(parse_property_value);
+        let after_value = target.after_value.take().map(parse_property_value);
+        let attribute_change_type = target
+            .attribute_change_type
+            .take()
+            .map(|attribute_change_type| attribute_change_type.as_str().parse())
+            .transpose()
+            .expected(
+                DESCRIBE_CHANGE_SET,
+                "ResourceTargetDefinition with invalid attribute_change_type",
+            )?;
+        Ok(Self {
             change_source: details
                 .change_source
-                .map(move |change_source| ChangeSource::from_sdk(&change_source, causing_entity)),
+                .map(move |change_source| ChangeSource::from_sdk(&change_source, causing_entity))
+                .transpose()?,
             evaluation: details
                 .evaluation
-                .expect("ResourceChangeDetail without evaluation")
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "ResourceChangeDetail without evaluation",
+                )?
                 .as_str()
                 .parse()
-                .expect("ResourceChangeDetail with invalid evaluation"),
-            target: ResourceTargetDefinition::from_sdk(
-                resource_type,
-                details.target.expect("ResourceChangeDetail without target"),
-            ),
-        }
+                .expected(
+                    DESCRIBE_CHANGE_SET,
+                    "ResourceChangeDetail with invalid evaluation",
+                )?,
+            attribute_change_type,
+            target: ResourceTargetDefinition::from_sdk(resource_type, target)?,
+            path,
+            before_value,
+            after_value,
+        })
     }
 }
 
+/// Interpret a property value from a change set as JSON, falling back to a JSON string.
+fn parse_property_value(value: String) -> serde_json::Value {
+    serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value))
+}
+
 /// The type of an entity that triggered a change.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ChangeSource {
     /// `Ref` intrinsic functions that refer to resources in the template, such as
     /// `{ "Ref" : "MyEC2InstanceResource" }`.
@@ -541,44 +711,60 @@ pub enum ChangeSource {
     /// Changes to a nested stack's template aren't visible to AWS CloudFormation until you run an
     /// update on the parent stack.
     Automatic,
+
+    /// Changes that aren't caused by a modification to the template, such as syncing a drifted
+    /// resource with its actual state in a drift-aware change set.
+    NoModification,
+
+    /// A change source that this version of the library doesn't recognise.
+    Unknown {
+        /// The raw change source.
+        change_source: String,
+
+        /// The identity of the entity that triggered the change, if any.
+        causing_entity: Option<String>,
+    },
 }
 
 impl ChangeSource {
     fn from_sdk(
         change_source: &aws_sdk_cloudformation::types::ChangeSource,
         causing_entity: Option<String>,
-    ) -> Self {
+    ) -> Result<Self, UnexpectedResponse> {
         match change_source {
             aws_sdk_cloudformation::types::ChangeSource::ResourceReference
             | aws_sdk_cloudformation::types::ChangeSource::ParameterReference
             | aws_sdk_cloudformation::types::ChangeSource::ResourceAttribute => {
-                let causing_entity = causing_entity.unwrap_or_else(|| {
-                    panic!(
-                        "ResourceChangeDetail with change_source {:?} without causing_entity",
-                        change_source
+                let causing_entity = causing_entity.ok_or_else(|| {
+                    UnexpectedResponse::new(
+                        DESCRIBE_CHANGE_SET,
+                        format!(
+                            "ResourceChangeDetail with change_source {change_source:?} without causing_entity"
+                        ),
                     )
-                });
+                })?;
                 match change_source {
                     aws_sdk_cloudformation::types::ChangeSource::ResourceReference => {
-                        Self::ResourceReference(causing_entity)
+                        Ok(Self::ResourceReference(causing_entity))
                     }
                     aws_sdk_cloudformation::types::ChangeSource::ParameterReference => {
-                        Self::ParameterReference(causing_entity)
+                        Ok(Self::ParameterReference(causing_entity))
                     }
                     aws_sdk_cloudformation::types::ChangeSource::ResourceAttribute => {
-                        Self::ResourceAttribute(causing_entity)
+                        Ok(Self::ResourceAttribute(causing_entity))
                     }
                     _ => unreachable!(),
                 }
             }
             aws_sdk_cloudformation::types::ChangeSource::DirectModification => {
-                Self::DirectModification
+                Ok(Self::DirectModification)
             }
-            aws_sdk_cloudformation::types::ChangeSource::Automatic => Self::Automatic,
-            _ => panic!(
-                "ResourceChangeDetail with invalid change_source {:?}",
-                change_source
-            ),
+            aws_sdk_cloudformation::types::ChangeSource::Automatic => Ok(Self::Automatic),
+            aws_sdk_cloudformation::types::ChangeSource::NoModification => Ok(Self::NoModification),
+            _ => Ok(Self::Unknown {
+                change_source: change_source.as_str().to_string(),
+                causing_entity,
+            }),
         }
     }
 }
@@ -602,9 +788,32 @@ pub enum Evaluation {
     Dynamic,
 }
 
+/// The type of change that will be made to a resource's attribute, in a drift-aware change set.
+#[derive(Clone, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+pub enum AttributeChangeType {
+    /// The attribute will be added.
+    Add,
+
+    /// The attribute will be removed.
+    Remove,
+
+    /// The attribute will be modified.
+    Modify,
+
+    /// The attribute will be set to its actual value, i.e. its drift will be kept.
+    SyncWithActual,
+
+    /// An attribute change type that this version of the library doesn't recognise.
+    ///
+    /// This carries the raw attribute change type string.
+    #[display("{0}")]
+    Unknown(String),
+}
+
 /// The field that AWS CloudFormation will change, such as the name of a resource's property, and
 /// whether the resource will be recreated.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ResourceTargetDefinition {
     /// A change to the resource's properties.
     Properties {
@@ -627,63 +836,92 @@ pub enum ResourceTargetDefinition {
     /// A change to the resource's deletion policy.
     DeletionPolicy,
 
+    /// A change to the resource's update replace policy.
+    UpdateReplacePolicy,
+
     /// A change to the resource's tags.
     Tags,
+
+    /// A change to an attribute that this version of the library doesn't recognise.
+    Unknown {
+        /// The raw attribute name.
+        attribute: String,
+
+        /// The name of the changed field within the attribute, if any.
+        name: Option<String>,
+    },
 }
 
 impl ResourceTargetDefinition {
     fn from_sdk(
         resource_type: &str,
         target: aws_sdk_cloudformation::types::ResourceTargetDefinition,
-    ) -> Self {
-        let attribute = target
-            .attribute
-            .expect("ResourceTargetDefinition without attribute");
+    ) -> Result<Self, UnexpectedResponse> {
+        let attribute = target.attribute.expected(
+            DESCRIBE_CHANGE_SET,
+            "ResourceTargetDefinition without attribute",
+        )?;
         match attribute {
-            aws_sdk_cloudformation::types::ResourceAttribute::Properties => Self::Properties {
+            aws_sdk_cloudformation::types::ResourceAttribute::Properties => Ok(Self::Properties {
                 name: target.name,
                 requires_recreation: target
                     .requires_recreation
-                    .expect(concat!(
-                        "ResourceTargetDefinition with attribute \"Properties\" without ",
-                        "requires_recreation"
-                    ))
+                    .expected(
+                        DESCRIBE_CHANGE_SET,
+                        concat!(
+                            "ResourceTargetDefinition with attribute \"Properties\" without ",
+                            "requires_recreation"
+                        ),
+                    )?
                     .as_str()
                     .parse()
-                    .expect("ResourceTargetDefinition with invalid requires_recreation"),
-            },
+                    .expected(
+                        DESCRIBE_CHANGE_SET,
+                        "ResourceTargetDefinition with invalid requires_recreation",
+                    )?,
+            }),
             aws_sdk_cloudformation::types::ResourceAttribute::Metadata
             | aws_sdk_cloudformation::types::ResourceAttribute::CreationPolicy
             | aws_sdk_cloudformation::types::ResourceAttribute::UpdatePolicy
             | aws_sdk_cloudformation::types::ResourceAttribute::DeletionPolicy
+            | aws_sdk_cloudformation::types::ResourceAttribute::UpdateReplacePolicy
             | aws_sdk_cloudformation::types::ResourceAttribute::Tags => {
-                assert!(
-                    target.name.is_none(),
-                    "ResourceTargetDefinition with attribute {:?} with name",
-                    attribute
-                );
-                assert!(
-                    // We assume that changes to these attributes would never require recreation.
-                    // NOTE: CloudFormation may report tag changes on AWS::SecretsManager::Secret
-                    // resources as conditionally requiring recreation. We assume this is a bug in
-                    // CloudFormation and ignore it.
-                    matches!(
-                        target.requires_recreation,
-                        None | Some(aws_sdk_cloudformation::types::RequiresRecreation::Never)
-                    ) || resource_type == "AWS::SecretsManager::Secret",
-                    "ResourceTargetDefinition with attribute {:?} with requires_recreation",
-                    attribute
-                );
+                if target.name.is_some() {
+                    return Err(UnexpectedResponse::new(
+                        DESCRIBE_CHANGE_SET,
+                        format!("ResourceTargetDefinition with attribute {attribute:?} with name"),
+                    ));
+                }
+                // We assume that changes to these attributes would never require recreation.
+                // NOTE: CloudFormation may report tag changes on AWS::SecretsManager::Secret
+                // resources as conditionally requiring recreation. We assume this is a bug in
+                // CloudFormation and ignore it.
+                if !matches!(
+                    target.requires_recreation,
+                    None | Some(aws_sdk_cloudformation::types::RequiresRecreation::Never)
+                ) && resource_type != "AWS::SecretsManager::Secret"
+                {
+                    return Err(UnexpectedResponse::new(
+                        DESCRIBE_CHANGE_SET,
+                        format!(
+                            "ResourceTargetDefinition with attribute {attribute:?} with requires_recreation"
+                        ),
+                    ));
+                }
                 match attribute.as_str() {
-                    "Metadata" => Self::Metadata,
-                    "CreationPolicy" => Self::CreationPolicy,
-                    "UpdatePolicy" => Self::UpdatePolicy,
-                    "DeletionPolicy" => Self::DeletionPolicy,
-                    "Tags" => Self::Tags,
+                    "Metadata" => Ok(Self::Metadata),
+                    "CreationPolicy" => Ok(Self::CreationPolicy),
+                    "UpdatePolicy" => Ok(Self::UpdatePolicy),
+                    "DeletionPolicy" => Ok(Self::DeletionPolicy),
+                    "UpdateReplacePolicy" => Ok(Self::UpdateReplacePolicy),
+                    "Tags" => Ok(Self::Tags),
                     _ => unreachable!(),
                 }
             }
-            _ => panic!("ResourceTargetDefinition with invalid attribute"),
+            _ => Ok(Self::Unknown {
+                attribute: attribute.as_str().to_string(),
+                name: target.name,
+            }),
         }
     }
 }
@@ -704,6 +942,191 @@ pub enum RequiresRecreation {
     Always,
 }
 
+/// A policy that refuses to execute destructive change sets.
+///
+/// Resources can be protected by type (e.g. `AWS::RDS::DBInstance`) or by logical ID. Change sets
+/// that would remove or replace a protected resource violate the policy.
+///
+/// The policy errs on the side of caution: changes that *might* replace a protected resource (a
+/// [`Conditional`](Replacement::Conditional) replacement), and changes whose effect can't be
+/// determined ([`Dynamic`](Action::Dynamic) or [`Unknown`](Action::Unknown) actions), also violate
+/// the policy.
+///
+/// Changes to the resources of nested stacks are evaluated against the same policy, if the change
+/// set includes them (see [`include_nested_stacks`](crate::ApplyStackInput::include_nested_stacks)).
+///
+/// A policy can be attached to an `apply_stack` operation with
+/// [`ApplyStackInput::set_change_set_policy`](crate::ApplyStackInput::set_change_set_policy).
+///
+/// ```
+/// use cloudformatious::change_set::ChangeSetPolicy;
+///
+/// let policy = ChangeSetPolicy::new()
+///     .set_protected_resource_types(["AWS::RDS::DBInstance", "AWS::DynamoDB::Table"])
+///     .set_protected_logical_resource_ids(["Bucket"]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::module_name_repetitions)]
+pub struct ChangeSetPolicy {
+    /// Resource types that must not be removed or replaced, e.g. `AWS::DynamoDB::Table`.
+    pub protected_resource_types: Vec<String>,
+
+    /// Logical IDs of resources that must not be removed or replaced.
+    pub protected_logical_resource_ids: Vec<String>,
+}
+
+impl ChangeSetPolicy {
+    /// Construct a policy that protects no resources.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value for `protected_resource_types`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_protected_resource_types<I, S>(mut self, protected_resource_types: I) -> Self
+    where
+        I: Into<Vec<S>>,
+        S: Into<String>,
+    {
+        self.protected_resource_types = protected_resource_types
+            .into()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        self
+    }
+
+    /// Set the value for `protected_logical_resource_ids`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_protected_logical_resource_ids<I, S>(
+        mut self,
+        protected_logical_resource_ids: I,
+    ) -> Self
+    where
+        I: Into<Vec<S>>,
+        S: Into<String>,
+    {
+        self.protected_logical_resource_ids = protected_logical_resource_ids
+            .into()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        self
+    }
+
+    /// Evaluate the policy against a change set.
+    ///
+    /// Returns the changes that violate the policy, which will be empty if the change set is
+    /// allowed.
+    #[must_use]
+    pub fn evaluate(&self, change_set: &ChangeSet) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        self.evaluate_changes(&change_set.changes, &mut violations);
+        violations
+    }
+
+    fn evaluate_changes(&self, changes: &[ResourceChange], violations: &mut Vec<PolicyViolation>) {
+        for change in changes {
+            let kind = match &change.action {
+                Action::Remove => Some(PolicyViolationKind::Removed),
+                Action::Modify(ModifyDetail {
+                    replacement: Replacement::True,
+                    ..
+                })
+                | Action::SyncWithActual(ModifyDetail {
+                    replacement: Replacement::True,
+                    ..
+                }) => Some(PolicyViolationKind::Replaced),
+                Action::Modify(ModifyDetail {
+                    replacement: Replacement::Conditional,
+                    ..
+                })
+                | Action::SyncWithActual(ModifyDetail {
+                    replacement: Replacement::Conditional,
+                    ..
+                }) => Some(PolicyViolationKind::ConditionallyReplaced),
+                Action::Dynamic | Action::Unknown(_) => Some(PolicyViolationKind::Undetermined),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                if self.is_protected(change) {
+                    violations.push(PolicyViolation {
+                        logical_resource_id: change.logical_resource_id.clone(),
+                        physical_resource_id: change.physical_resource_id.clone(),
+                        resource_type: change.resource_type.clone(),
+                        kind,
+                    });
+                }
+            }
+
+            if let Some(nested_change_set) = &change.nested_change_set {
+                self.evaluate_changes(&nested_change_set.changes, violations);
+            }
+        }
+    }
+
+    fn is_protected(&self, change: &ResourceChange) -> bool {
+        self.protected_resource_types
+            .contains(&change.resource_type)
+            || self
+                .protected_logical_resource_ids
+                .contains(&change.logical_resource_id)
+    }
+}
+
+/// A change that violates a [`ChangeSetPolicy`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PolicyViolation {
+    /// The logical ID of the protected resource.
+    pub logical_resource_id: String,
+
+    /// The physical ID of the protected resource.
+    pub physical_resource_id: Option<String>,
+
+    /// The type of the protected resource.
+    pub resource_type: String,
+
+    /// What the change would do to the protected resource.
+    pub kind: PolicyViolationKind,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) would be {}",
+            self.logical_resource_id, self.resource_type, self.kind
+        )
+    }
+}
+
+/// What a change that violates a [`ChangeSetPolicy`] would do to a protected resource.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[display(style = "lowercase")]
+pub enum PolicyViolationKind {
+    /// The resource would be removed.
+    Removed,
+
+    /// The resource would be replaced.
+    Replaced,
+
+    /// The resource might be replaced, depending on values that are only known during the update.
+    #[display("conditionally replaced")]
+    ConditionallyReplaced,
+
+    /// The change's effect on the resource can't be determined, either because CloudFormation
+    /// can't determine it or because this version of the library doesn't recognise the action.
+    #[display("changed in an undetermined way")]
+    Undetermined,
+}
+
 pub(crate) struct ChangeSetWithType {
     pub(crate) change_set: ChangeSet,
     pub(crate) change_set_type: ChangeSetType,
@@ -713,8 +1136,9 @@ pub(crate) enum CreateChangeSetError {
     CreateApi(SdkError<aws_sdk_cloudformation::operation::create_change_set::CreateChangeSetError>),
     PollApi(SdkError<DescribeChangeSetError>),
     Blocked { status: BlockedStackStatus },
-    NoChanges(ChangeSetWithType),
-    Failed(ChangeSetWithType),
+    NoChanges(ChangeSet),
+    Failed(ChangeSet),
+    UnexpectedResponse(UnexpectedResponse),
 }
 
 impl From<SdkError<aws_sdk_cloudformation::operation::create_change_set::CreateChangeSetError>>
@@ -737,10 +1161,44 @@ impl From<SdkError<DescribeChangeSetError>> for CreateChangeSetError {
     }
 }
 
+impl From<UnexpectedResponse> for CreateChangeSetError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse(error)
+    }
+}
+
+impl From<DescribeError> for CreateChangeSetError {
+    fn from(error: DescribeError) -> Self {
+        match error {
+            DescribeError::Api(error) => Self::PollApi(*error),
+            DescribeError::UnexpectedResponse(error) => Self::UnexpectedResponse(error),
+        }
+    }
+}
+
+pub(crate) enum DescribeError {
+    Api(Box<SdkError<DescribeChangeSetError>>),
+    UnexpectedResponse(UnexpectedResponse),
+}
+
+impl From<SdkError<DescribeChangeSetError>> for DescribeError {
+    fn from(error: SdkError<DescribeChangeSetError>) -> Self {
+        Self::Api(Box::new(error))
+    }
+}
+
+impl From<UnexpectedResponse> for DescribeError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse(error)
+    }
+}
+
 pub(crate) async fn create_change_set(
     client: &aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
     mut change_set_type: ChangeSetType,
     input: CreateChangeSetFluentBuilder,
+    include_property_values: bool,
 ) -> Result<ChangeSetWithType, CreateChangeSetError> {
     let change_set = input
         .clone()
@@ -763,54 +1221,150 @@ pub(crate) async fn create_change_set(
             }
         })
         .await?;
-    let change_set_id = change_set.id.expect("CreateChangeSetOutput without id");
+    let change_set_id = change_set
+        .id
+        .expected("CreateChangeSet", "CreateChangeSetOutput without id")?;
+
+    let change_set =
+        wait_for_change_set(client, polling, change_set_id, include_property_values).await?;
+    Ok(ChangeSetWithType {
+        change_set,
+        change_set_type,
+    })
+}
 
+/// Poll a change set until it has finished creating.
+///
+/// This is used when creating change sets, but also when executing a change set by ID, since we
+/// can't assume the change set was ready when the ID was handed to us.
+///
+/// `include_property_values` requests the before and after values of changed properties.
+pub(crate) async fn wait_for_change_set(
+    client: &aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
+    change_set_id: String,
+    include_property_values: bool,
+) -> Result<ChangeSet, CreateChangeSetError> {
     let mut interval = interval_at(
-        Instant::now() + POLL_INTERVAL_CHANGE_SET,
-        POLL_INTERVAL_CHANGE_SET,
+        Instant::now() + polling.change_set_interval,
+        polling.change_set_interval,
     );
     loop {
         interval.tick().await;
 
-        let change_set = client
-            .describe_change_set()
-            .change_set_name(change_set_id.clone())
-            .send()
-            .await?;
-        let change_set = ChangeSet::from_sdk(change_set);
+        let change_set =
+            describe_change_set(client, polling, &change_set_id, include_property_values).await?;
         match change_set.status {
-            ChangeSetStatus::CreatePending | ChangeSetStatus::CreateInProgress => continue,
+            ChangeSetStatus::CreatePending | ChangeSetStatus::CreateInProgress => {}
             ChangeSetStatus::CreateComplete => {
-                return Ok(ChangeSetWithType {
-                    change_set,
-                    change_set_type,
-                })
+                let mut change_set = change_set;
+                describe_nested_change_sets(
+                    client,
+                    polling,
+                    &mut change_set.changes,
+                    include_property_values,
+                )
+                .await?;
+                return Ok(change_set);
             }
             ChangeSetStatus::Failed if is_no_changes(change_set.status_reason.as_deref()) => {
-                return Err(CreateChangeSetError::NoChanges(ChangeSetWithType {
-                    change_set,
-                    change_set_type,
-                }))
-            }
-            ChangeSetStatus::Failed => {
-                return Err(CreateChangeSetError::Failed(ChangeSetWithType {
-                    change_set,
-                    change_set_type,
-                }))
+                return Err(CreateChangeSetError::NoChanges(change_set))
             }
+            ChangeSetStatus::Failed => return Err(CreateChangeSetError::Failed(change_set)),
+            ChangeSetStatus::Unknown(_) if !change_set.status.is_settled() => {}
             _ => {
-                panic!(
-                    "change set {} had unexpected status: {}",
-                    change_set.change_set_id, change_set.status
-                );
+                return Err(CreateChangeSetError::UnexpectedResponse(
+                    UnexpectedResponse::new(
+                        DESCRIBE_CHANGE_SET,
+                        format!(
+                            "change set {} had unexpected status: {}",
+                            change_set.change_set_id, change_set.status
+                        ),
+                    ),
+                ));
             }
         }
     }
 }
 
+/// Describe a change set, including all pages of its changes.
+///
+/// `DescribeChangeSet` returns at most 100 changes per page, so large change sets would otherwise
+/// be silently truncated.
+pub(crate) async fn describe_change_set(
+    client: &aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
+    change_set_id: &str,
+    include_property_values: bool,
+) -> Result<ChangeSet, DescribeError> {
+    let mut output: Option<DescribeChangeSetOutput> = None;
+    let mut next_token = None;
+    loop {
+        let page = polling
+            .retry_throttled(polling.change_set_interval, || {
+                client
+                    .describe_change_set()
+                    .change_set_name(change_set_id)
+                    .include_property_values(include_property_values)
+                    .set_next_token(next_token.clone())
+                    .send()
+            })
+            .await?;
+        next_token.clone_from(&page.next_token);
+
+        output = Some(match output {
+            None => page,
+            Some(mut output) => {
+                output
+                    .changes
+                    .get_or_insert_with(Vec::new)
+                    .extend(page.changes.unwrap_or_default());
+                output
+            }
+        });
+        if next_token.is_none() {
+            break;
+        }
+    }
+    Ok(ChangeSet::from_sdk(
+        output.expect("at least one page is described"),
+    )?)
+}
+
+/// Describe the nested change sets of the given changes, recursively.
+///
+/// Nested change sets are created along with their root change set, so they will have settled by
+/// the time the root change set has.
+pub(crate) fn describe_nested_change_sets<'a>(
+    client: &'a aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
+    changes: &'a mut [ResourceChange],
+    include_property_values: bool,
+) -> Pin<Box<dyn Future<Output = Result<(), DescribeError>> + Send + 'a>> {
+    Box::pin(async move {
+        for change in changes {
+            let Some(change_set_id) = &change.change_set_id else {
+                continue;
+            };
+            let mut nested_change_set =
+                describe_change_set(client, polling, change_set_id, include_property_values)
+                    .await?;
+            describe_nested_change_sets(
+                client,
+                polling,
+                &mut nested_change_set.changes,
+                include_property_values,
+            )
+            .await?;
+            change.nested_change_set = Some(Box::new(nested_change_set));
+        }
+        Ok(())
+    })
+}
+
 pub(crate) enum ExecuteChangeSetError {
     ExecuteApi(
-        SdkError<aws_sdk_cloudformation::operation::execute_change_set::ExecuteChangeSetError>,
+        Box<SdkError<aws_sdk_cloudformation::operation::execute_change_set::ExecuteChangeSetError>>,
     ),
     Blocked {
         status: BlockedStackStatus,
@@ -825,18 +1379,19 @@ impl From<SdkError<aws_sdk_cloudformation::operation::execute_change_set::Execut
             aws_sdk_cloudformation::operation::execute_change_set::ExecuteChangeSetError,
         >,
     ) -> Self {
-        Self::ExecuteApi(error)
+        Self::ExecuteApi(Box::new(error))
     }
 }
 
 pub(crate) async fn execute_change_set(
     client: &aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
     stack_id: String,
     change_set_id: String,
     change_set_type: ChangeSetType,
     disable_rollback: bool,
 ) -> Result<
-    StackOperation<'_, impl Fn(StackStatus) -> StackOperationStatus + Unpin>,
+    StackOperation<'_, impl Fn(&StackStatus) -> StackOperationStatus + Unpin>,
     ExecuteChangeSetError,
 > {
     let started_at = Utc::now();
@@ -850,16 +1405,18 @@ pub(crate) async fn execute_change_set(
             if let Some(status) = is_execute_blocked(&error) {
                 return ExecuteChangeSetError::Blocked { status };
             }
-            ExecuteChangeSetError::ExecuteApi(error)
+            ExecuteChangeSetError::ExecuteApi(Box::new(error))
         })?;
 
     Ok(StackOperation::new(
         client,
+        polling,
         stack_id,
         started_at,
         match change_set_type {
             ChangeSetType::Create => check_create_progress,
             ChangeSetType::Update => check_update_progress,
+            ChangeSetType::Import => check_import_progress,
         },
     ))
 }
@@ -875,34 +1432,32 @@ fn is_already_exists(
 fn is_create_blocked(
     error: &SdkError<aws_sdk_cloudformation::operation::create_change_set::CreateChangeSetError>,
 ) -> Option<BlockedStackStatus> {
-    lazy_static::lazy_static! {
-        static ref BLOCKED: regex::Regex = regex::Regex::new(r"(?i)^Stack:[^ ]* is in (?P<status>[_A-Z]+) state and can not be updated").unwrap();
-    }
+    static BLOCKED: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)^Stack:[^ ]* is in (?P<status>[_A-Z]+) state and can not be updated")
+            .unwrap()
+    });
 
-    is_blocked(&BLOCKED, error.message().unwrap())
+    is_blocked(&BLOCKED, error.message()?)
 }
 
 fn is_execute_blocked(
     error: &SdkError<aws_sdk_cloudformation::operation::execute_change_set::ExecuteChangeSetError>,
 ) -> Option<BlockedStackStatus> {
-    lazy_static::lazy_static! {
-        static ref BLOCKED: regex::Regex = regex::Regex::new(r"(?i)^This stack is currently in a non-terminal \[(?P<status>[_A-Z]+)\] state").unwrap();
-    }
+    static BLOCKED: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)^This stack is currently in a non-terminal \[(?P<status>[_A-Z]+)\] state")
+            .unwrap()
+    });
 
-    is_blocked(&BLOCKED, error.message().unwrap())
+    is_blocked(&BLOCKED, error.message()?)
 }
 
 fn is_blocked(pattern: &Regex, message: &str) -> Option<BlockedStackStatus> {
     let detail = pattern.captures(message)?;
 
-    let status: StackStatus = detail
-        .name("status")
-        .unwrap()
-        .as_str()
-        .parse()
-        .expect("captured invalid status");
-    let status = BlockedStackStatus::try_from(status).expect("captured non-blocked status");
-    Some(status)
+    // If the captured status isn't one we recognise as blocking, treat the error as an ordinary API
+    // error rather than guessing.
+    let status: StackStatus = detail.name("status")?.as_str().parse().ok()?;
+    BlockedStackStatus::try_from(status).ok()
 }
 
 fn is_no_changes(status_reason: Option<&str>) -> bool {
@@ -911,7 +1466,7 @@ fn is_no_changes(status_reason: Option<&str>) -> bool {
         || status_reason.contains("No updates are to be performed.")
 }
 
-fn check_create_progress(stack_status: StackStatus) -> StackOperationStatus {
+fn check_create_progress(stack_status: &StackStatus) -> StackOperationStatus {
     match stack_status {
         StackStatus::CreateInProgress | StackStatus::RollbackInProgress => {
             StackOperationStatus::InProgress
@@ -920,11 +1475,12 @@ fn check_create_progress(stack_status: StackStatus) -> StackOperationStatus {
         StackStatus::CreateFailed | StackStatus::RollbackFailed | StackStatus::RollbackComplete => {
             StackOperationStatus::Failed
         }
+        StackStatus::Unknown(_) => StackOperationStatus::unknown(stack_status),
         _ => StackOperationStatus::Unexpected,
     }
 }
 
-fn check_update_progress(stack_status: StackStatus) -> StackOperationStatus {
+fn check_update_progress(stack_status: &StackStatus) -> StackOperationStatus {
     match stack_status {
         StackStatus::UpdateInProgress
         | StackStatus::UpdateCompleteCleanupInProgress
@@ -934,6 +1490,329 @@ fn check_update_progress(stack_status: StackStatus) -> StackOperationStatus {
         StackStatus::UpdateFailed
         | StackStatus::UpdateRollbackFailed
         | StackStatus::UpdateRollbackComplete => StackOperationStatus::Failed,
+        StackStatus::Unknown(_) => StackOperationStatus::unknown(stack_status),
+        _ => StackOperationStatus::Unexpected,
+    }
+}
+
+fn check_import_progress(stack_status: &StackStatus) -> StackOperationStatus {
+    match stack_status {
+        StackStatus::ImportInProgress | StackStatus::ImportRollbackInProgress => {
+            StackOperationStatus::InProgress
+        }
+        StackStatus::ImportComplete => StackOperationStatus::Complete,
+        StackStatus::ImportRollbackFailed | StackStatus::ImportRollbackComplete => {
+            StackOperationStatus::Failed
+        }
+        StackStatus::Unknown(_) => StackOperationStatus::unknown(stack_status),
         _ => StackOperationStatus::Unexpected,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use enumset::EnumSet;
+
+    use crate::ChangeSetStatus;
+
+    use super::{
+        Action, ChangeSet, ChangeSetPolicy, ExecutionStatus, ModifyDetail, PolicyViolation,
+        PolicyViolationKind, Replacement, ResourceChange,
+    };
+
+    pub(super) fn change_set(changes: Vec<ResourceChange>) -> ChangeSet {
+        ChangeSet {
+            capabilities: Vec::new(),
+            change_set_id: "arn:aws:cloudformation:eu-west-1:123456789012:changeSet/cs/1"
+                .to_string(),
+            change_set_name: "cs".to_string(),
+            changes,
+            creation_time: Utc::now(),
+            description: None,
+            execution_status: ExecutionStatus::Available,
+            notification_arns: Vec::new(),
+            parameters: Vec::new(),
+            stack_id: "arn:aws:cloudformation:eu-west-1:123456789012:stack/stack/1".to_string(),
+            stack_name: "stack".to_string(),
+            status: ChangeSetStatus::CreateComplete,
+            status_reason: None,
+            tags: Vec::new(),
+        }
+    }
+
+    pub(super) fn change(
+        logical_resource_id: &str,
+        resource_type: &str,
+        action: Action,
+    ) -> ResourceChange {
+        ResourceChange {
+            action,
+            logical_resource_id: logical_resource_id.to_string(),
+            physical_resource_id: Some(format!("{logical_resource_id}-physical")),
+            resource_type: resource_type.to_string(),
+            change_set_id: None,
+            nested_change_set: None,
+            before_context: None,
+            after_context: None,
+        }
+    }
+
+    pub(super) fn modify(replacement: Replacement) -> Action {
+        Action::Modify(ModifyDetail {
+            details: Vec::new(),
+            replacement,
+            scope: EnumSet::empty(),
+        })
+    }
+
+    #[test]
+    fn policy_violations() {
+        let policy = ChangeSetPolicy::new()
+            .set_protected_resource_types(["AWS::DynamoDB::Table"])
+            .set_protected_logical_resource_ids(["Bucket"]);
+
+        let mut nested = change(
+            "Nested",
+            "AWS::CloudFormation::Stack",
+            modify(Replacement::False),
+        );
+        nested.nested_change_set = Some(Box::new(change_set(vec![change(
+            "NestedTable",
+            "AWS::DynamoDB::Table",
+            modify(Replacement::True),
+        )])));
+        let change_set = change_set(vec![
+            change("Table", "AWS::DynamoDB::Table", Action::Remove),
+            change("Bucket", "AWS::S3::Bucket", modify(Replacement::True)),
+            change(
+                "OtherTable",
+                "AWS::DynamoDB::Table",
+                modify(Replacement::Conditional),
+            ),
+            change("OtherBucket", "AWS::S3::Bucket", Action::Remove),
+            change("DynamicTable", "AWS::DynamoDB::Table", Action::Dynamic),
+            change(
+                "UnknownTable",
+                "AWS::DynamoDB::Table",
+                Action::Unknown("Frobnicate".to_string()),
+            ),
+            change("UnknownQueue", "AWS::SQS::Queue", Action::Dynamic),
+            change("NewTable", "AWS::DynamoDB::Table", Action::Add),
+            nested,
+        ]);
+
+        let violation = |logical_resource_id: &str, resource_type: &str, kind| PolicyViolation {
+            logical_resource_id: logical_resource_id.to_string(),
+            physical_resource_id: Some(format!("{logical_resource_id}-physical")),
+            resource_type: resource_type.to_string(),
+            kind,
+        };
+        assert_eq!(
+            policy.evaluate(&change_set),
+            vec![
+                violation(
+                    "Table",
+                    "AWS::DynamoDB::Table",
+                    PolicyViolationKind::Removed
+                ),
+                violation("Bucket", "AWS::S3::Bucket", PolicyViolationKind::Replaced),
+                violation(
+                    "OtherTable",
+                    "AWS::DynamoDB::Table",
+                    PolicyViolationKind::ConditionallyReplaced
+                ),
+                violation(
+                    "DynamicTable",
+                    "AWS::DynamoDB::Table",
+                    PolicyViolationKind::Undetermined
+                ),
+                violation(
+                    "UnknownTable",
+                    "AWS::DynamoDB::Table",
+                    PolicyViolationKind::Undetermined
+                ),
+                violation(
+                    "NestedTable",
+                    "AWS::DynamoDB::Table",
+                    PolicyViolationKind::Replaced
+                ),
+            ]
+        );
+        assert_eq!(
+            violation(
+                "Table",
+                "AWS::DynamoDB::Table",
+                PolicyViolationKind::Removed
+            )
+            .to_string(),
+            "Table (AWS::DynamoDB::Table) would be removed"
+        );
+        assert_eq!(
+            violation(
+                "OtherTable",
+                "AWS::DynamoDB::Table",
+                PolicyViolationKind::ConditionallyReplaced
+            )
+            .to_string(),
+            "OtherTable (AWS::DynamoDB::Table) would be conditionally replaced"
+        );
+        assert_eq!(
+            "changed in an undetermined way".parse(),
+            Ok(PolicyViolationKind::Undetermined)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut change_set = change_set(vec![
+            change("Table", "AWS::DynamoDB::Table", Action::Remove),
+            change("Bucket", "AWS::S3::Bucket", modify(Replacement::True)),
+        ]);
+        if let Action::Modify(modify) = &mut change_set.changes[1].action {
+            modify.scope = EnumSet::only(super::ModifyScope::Properties);
+        }
+
+        let json = serde_json::to_value(&change_set).unwrap();
+        assert_eq!(json["status"], "CREATE_COMPLETE");
+        assert_eq!(json["changes"][0]["action"], "Remove");
+        assert_eq!(
+            json["changes"][1]["action"]["Modify"]["scope"],
+            serde_json::json!(["Properties"])
+        );
+        assert_eq!(
+            serde_json::from_value::<ChangeSet>(json).unwrap(),
+            change_set
+        );
+    }
+
+    #[test]
+    fn drift_aware_change_from_sdk() {
+        use aws_sdk_cloudformation::types::{
+            AttributeChangeType as SdkAttributeChangeType, Change, ChangeAction,
+            ChangeSource as SdkChangeSource, ChangeType, EvaluationType,
+            ResourceAttribute as SdkResourceAttribute, ResourceChange as SdkResourceChange,
+            ResourceChangeDetail as SdkResourceChangeDetail,
+            ResourceTargetDefinition as SdkResourceTargetDefinition,
+        };
+
+        use super::{AttributeChangeType, ChangeSource, Evaluation, ResourceTargetDefinition};
+
+        let change = Change::builder()
+            .r#type(ChangeType::Resource)
+            .resource_change(
+                SdkResourceChange::builder()
+                    .action(ChangeAction::SyncWithActual)
+                    .logical_resource_id("Bucket")
+                    .resource_type("AWS::S3::Bucket")
+                    .scope(SdkResourceAttribute::UpdateReplacePolicy)
+                    .details(
+                        SdkResourceChangeDetail::builder()
+                            .change_source(SdkChangeSource::NoModification)
+                            .evaluation(EvaluationType::Static)
+                            .target(
+                                SdkResourceTargetDefinition::builder()
+                                    .attribute(SdkResourceAttribute::UpdateReplacePolicy)
+                                    .attribute_change_type(SdkAttributeChangeType::SyncWithActual)
+                                    .build(),
+                            )
+                            .build(),
+                    )
+                    .details(
+                        SdkResourceChangeDetail::builder()
+                            .change_source(SdkChangeSource::from("SomeNewSource"))
+                            .causing_entity("Entity")
+                            .evaluation(EvaluationType::Static)
+                            .target(
+                                SdkResourceTargetDefinition::builder()
+                                    .attribute(SdkResourceAttribute::from("SomeNewAttribute"))
+                                    .name("Field")
+                                    .attribute_change_type(SdkAttributeChangeType::from(
+                                        "SomeNewChangeType",
+                                    ))
+                                    .build(),
+                            )
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+
+        let change = ResourceChange::from_sdk(change).unwrap();
+        let Action::SyncWithActual(modify) = change.action else {
+            panic!("unexpected action {:?}", change.action);
+        };
+        assert_eq!(modify.replacement, Replacement::False);
+        assert_eq!(
+            modify.scope,
+            EnumSet::only(super::ModifyScope::UpdateReplacePolicy)
+        );
+        assert_eq!(
+            modify.details[0].change_source,
+            Some(ChangeSource::NoModification)
+        );
+        assert_eq!(modify.details[0].evaluation, Evaluation::Static);
+        assert_eq!(
+            modify.details[0].target,
+            ResourceTargetDefinition::UpdateReplacePolicy
+        );
+        assert_eq!(
+            modify.details[0].attribute_change_type,
+            Some(AttributeChangeType::SyncWithActual)
+        );
+        assert_eq!(
+            modify.details[1].change_source,
+            Some(ChangeSource::Unknown {
+                change_source: "SomeNewSource".to_string(),
+                causing_entity: Some("Entity".to_string()),
+            })
+        );
+        assert_eq!(
+            modify.details[1].target,
+            ResourceTargetDefinition::Unknown {
+                attribute: "SomeNewAttribute".to_string(),
+                name: Some("Field".to_string()),
+            }
+        );
+        assert_eq!(
+            modify.details[1].attribute_change_type,
+            Some(AttributeChangeType::Unknown(
+                "SomeNewChangeType".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn unknown_action_from_sdk() {
+        use aws_sdk_cloudformation::types::{
+            Change, ChangeAction, ChangeType, ResourceChange as SdkResourceChange,
+        };
+
+        let change = Change::builder()
+            .r#type(ChangeType::Resource)
+            .resource_change(
+                SdkResourceChange::builder()
+                    .action(ChangeAction::from("SomeNewAction"))
+                    .logical_resource_id("Bucket")
+                    .resource_type("AWS::S3::Bucket")
+                    .build(),
+            )
+            .build();
+
+        assert_eq!(
+            ResourceChange::from_sdk(change).unwrap().action,
+            Action::Unknown("SomeNewAction".to_string())
+        );
+    }
+
+    #[test]
+    fn empty_policy_allows_everything() {
+        let change_set = change_set(vec![change(
+            "Table",
+            "AWS::DynamoDB::Table",
+            Action::Remove,
+        )]);
+        assert_eq!(ChangeSetPolicy::new().evaluate(&change_set), Vec::new());
+    }
+}
@@ -0,0 +1,423 @@
+//! Human-readable rendering of change sets.
+//!
+//! [`Render`] produces a textual summary of a [`ChangeSet`] in the style of a `terraform plan`,
+//! suitable for showing to a human before executing the change set:
+//!
+//! ```text
+//! Change set my-change-set for stack my-stack:
+//!
+//!   + Queue (AWS::SQS::Queue)
+//!
+//!   ~ Topic (AWS::SNS::Topic)
+//!     ~ Properties.DisplayName (caused by parameter DisplayName)
+//!     ~ Tags
+//!
+//! -/+ Subnet (AWS::EC2::Subnet) [replace]
+//!     ~ Properties.CidrBlock [requires recreation] (caused by parameter CidrBlock)
+//!
+//!   - Bucket (AWS::S3::Bucket)
+//!
+//! Plan: 1 to add, 2 to modify (1 to replace), 1 to remove, 0 to import.
+//! ```
+//!
+//! Changes are grouped by action. Nested change sets (see
+//! [`include_nested_stacks`](crate::ApplyStackInput::include_nested_stacks)) are rendered beneath
+//! their `AWS::CloudFormation::Stack` resource, and are included in the counts. Property values
+//! are included when the change set was described with
+//! [`include_property_values`](crate::ApplyStackInput::include_property_values).
+
+use std::fmt;
+
+use super::{
+    Action, ChangeSet, ChangeSource, Evaluation, Replacement, RequiresRecreation, ResourceChange,
+    ResourceChangeDetail, ResourceTargetDefinition,
+};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const MAGENTA: &str = "\x1b[35m";
+const CYAN: &str = "\x1b[36m";
+
+/// The number of spaces each level of nested change set is indented by.
+const INDENT: usize = 4;
+
+/// A human-readable rendering of a [`ChangeSet`].
+///
+/// This implements [`Display`](fmt::Display), so it can be printed or converted to a `String`.
+/// Output is plain text by default, and can include ANSI colours with
+/// [`set_color`](Self::set_color).
+///
+/// ```no_run
+/// use cloudformatious::{change_set::render::Render, ApplyStackInput, TemplateSource};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = aws_config::load_from_env().await;
+/// let client = cloudformatious::Client::new(&config);
+/// let input = ApplyStackInput::new("my-stack", TemplateSource::inline("{}"));
+/// let change_set = client.create_change_set(input).await?;
+/// eprintln!("{}", Render::new(&change_set).set_color(true));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Render<'a> {
+    change_set: &'a ChangeSet,
+    color: bool,
+}
+
+impl<'a> Render<'a> {
+    /// Construct a plain text rendering of the given `change_set`.
+    #[must_use]
+    pub fn new(change_set: &'a ChangeSet) -> Self {
+        Self {
+            change_set,
+            color: false,
+        }
+    }
+
+    /// Set whether or not to include ANSI colours in the output.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn paint(&self, color: &'static str) -> Paint {
+        if self.color {
+            Paint(color, RESET)
+        } else {
+            Paint("", "")
+        }
+    }
+
+    fn fmt_changes(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        changes: &[ResourceChange],
+        depth: usize,
+    ) -> fmt::Result {
+        let mut changes: Vec<_> = changes.iter().collect();
+        changes.sort_by_key(|change| group(&change.action));
+
+        for change in changes {
+            writeln!(f)?;
+            self.fmt_change(f, change, depth)?;
+
+            if let Some(nested_change_set) = &change.nested_change_set {
+                self.fmt_changes(f, &nested_change_set.changes, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_change(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        change: &ResourceChange,
+        depth: usize,
+    ) -> fmt::Result {
+        let (marker, color) = marker(&change.action);
+        let Paint(color, reset) = self.paint(color);
+        let Paint(bold, bold_reset) = self.paint(BOLD);
+        write!(
+            f,
+            "{:indent$}{color}{marker:>3}{reset} {bold}{}{bold_reset} ({})",
+            "",
+            change.logical_resource_id,
+            change.resource_type,
+            indent = depth * INDENT,
+        )?;
+        match &change.action {
+            Action::Modify(modify) | Action::SyncWithActual(modify) => {
+                if let Action::SyncWithActual(_) = change.action {
+                    write!(f, " {color}[sync with actual]{reset}")?;
+                }
+                match modify.replacement {
+                    Replacement::True => write!(f, " {color}[replace]{reset}")?,
+                    Replacement::Conditional => write!(f, " {color}[may replace]{reset}")?,
+                    Replacement::False => {}
+                }
+                writeln!(f)?;
+                for detail in &modify.details {
+                    self.fmt_detail(f, detail, depth)?;
+                }
+            }
+            Action::Import => writeln!(f, " {color}[import]{reset}")?,
+            Action::Dynamic => writeln!(f, " {color}[dynamic]{reset}")?,
+            Action::Unknown(action) => writeln!(f, " {color}[{action}]{reset}")?,
+            Action::Add | Action::Remove => writeln!(f)?,
+        }
+        Ok(())
+    }
+
+    fn fmt_detail(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        detail: &ResourceChangeDetail,
+        depth: usize,
+    ) -> fmt::Result {
+        let Paint(yellow, reset) = self.paint(YELLOW);
+        write!(
+            f,
+            "{:indent$}{yellow}~{reset} ",
+            "",
+            indent = depth * INDENT + INDENT
+        )?;
+        match &detail.target {
+            ResourceTargetDefinition::Properties {
+                name,
+                requires_recreation,
+            } => {
+                write!(f, "Properties")?;
+                if let Some(name) = name {
+                    write!(f, ".{name}")?;
+                }
+                let Paint(red, reset) = self.paint(RED);
+                match requires_recreation {
+                    RequiresRecreation::Never => {}
+                    RequiresRecreation::Conditionally => {
+                        write!(f, " {red}[may require recreation]{reset}")?;
+                    }
+                    RequiresRecreation::Always => {
+                        write!(f, " {red}[requires recreation]{reset}")?;
+                    }
+                }
+            }
+            ResourceTargetDefinition::Metadata => write!(f, "Metadata")?,
+            ResourceTargetDefinition::CreationPolicy => write!(f, "CreationPolicy")?,
+            ResourceTargetDefinition::UpdatePolicy => write!(f, "UpdatePolicy")?,
+            ResourceTargetDefinition::DeletionPolicy => write!(f, "DeletionPolicy")?,
+            ResourceTargetDefinition::UpdateReplacePolicy => write!(f, "UpdateReplacePolicy")?,
+            ResourceTargetDefinition::Tags => write!(f, "Tags")?,
+            ResourceTargetDefinition::Unknown { attribute, name } => {
+                write!(f, "{attribute}")?;
+                if let Some(name) = name {
+                    write!(f, ".{name}")?;
+                }
+            }
+        }
+
+        match &detail.change_source {
+            Some(ChangeSource::ResourceReference(resource)) => {
+                write!(f, " (caused by reference to {resource})")?;
+            }
+            Some(ChangeSource::ParameterReference(parameter)) => {
+                write!(f, " (caused by parameter {parameter})")?;
+            }
+            Some(ChangeSource::ResourceAttribute(attribute)) => {
+                write!(f, " (caused by attribute {attribute})")?;
+            }
+            Some(ChangeSource::Automatic) => write!(f, " (automatic)")?,
+            Some(ChangeSource::NoModification) => write!(f, " (no modification)")?,
+            Some(ChangeSource::Unknown {
+                change_source,
+                causing_entity,
+            }) => {
+                write!(f, " (caused by {change_source}")?;
+                if let Some(causing_entity) = causing_entity {
+                    write!(f, " {causing_entity}")?;
+                }
+                write!(f, ")")?;
+            }
+            Some(ChangeSource::DirectModification) | None => {}
+        }
+        if let Some(attribute_change_type) = &detail.attribute_change_type {
+            write!(f, " [{attribute_change_type}]")?;
+        }
+        if detail.evaluation == Evaluation::Dynamic {
+            write!(f, " (evaluated during execution)")?;
+        }
+
+        if detail.before_value.is_some() || detail.after_value.is_some() {
+            let none = serde_json::Value::Null;
+            write!(
+                f,
+                ": {} => {}",
+                detail.before_value.as_ref().unwrap_or(&none),
+                detail.after_value.as_ref().unwrap_or(&none),
+            )?;
+        }
+
+        writeln!(f)
+    }
+}
+
+impl fmt::Display for Render<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Change set {} for stack {}:",
+            self.change_set.change_set_name, self.change_set.stack_name
+        )?;
+
+        if self.change_set.changes.is_empty() {
+            writeln!(f)?;
+            return write!(f, "No changes.");
+        }
+
+        self.fmt_changes(f, &self.change_set.changes, 0)?;
+
+        let mut counts = Counts::default();
+        counts.count(&self.change_set.changes);
+        writeln!(f)?;
+        write!(
+            f,
+            "Plan: {} to add, {} to modify ({} to replace), {} to remove, {} to import.",
+            counts.add, counts.modify, counts.replace, counts.remove, counts.import,
+        )
+    }
+}
+
+/// Colour codes, which are empty when rendering plain text.
+struct Paint(&'static str, &'static str);
+
+#[derive(Default)]
+struct Counts {
+    add: usize,
+    modify: usize,
+    replace: usize,
+    remove: usize,
+    import: usize,
+}
+
+impl Counts {
+    fn count(&mut self, changes: &[ResourceChange]) {
+        for change in changes {
+            match &change.action {
+                Action::Add => self.add += 1,
+                Action::Modify(modify) | Action::SyncWithActual(modify) => {
+                    self.modify += 1;
+                    if modify.replacement == Replacement::True {
+                        self.replace += 1;
+                    }
+                }
+                Action::Remove => self.remove += 1,
+                Action::Import => self.import += 1,
+                Action::Dynamic | Action::Unknown(_) => {}
+            }
+
+            if let Some(nested_change_set) = &change.nested_change_set {
+                self.count(&nested_change_set.changes);
+            }
+        }
+    }
+}
+
+/// The order in which actions are rendered.
+fn group(action: &Action) -> u8 {
+    match action {
+        Action::Add => 0,
+        Action::Import => 1,
+        Action::Modify(_) | Action::SyncWithActual(_) => 2,
+        Action::Dynamic | Action::Unknown(_) => 3,
+        Action::Remove => 4,
+    }
+}
+
+fn marker(action: &Action) -> (&'static str, &'static str) {
+    match action {
+        Action::Add => ("+", GREEN),
+        Action::Import => ("<=", CYAN),
+        Action::Modify(modify) | Action::SyncWithActual(modify) => match modify.replacement {
+            Replacement::True => ("-/+", RED),
+            Replacement::Conditional | Replacement::False => ("~", YELLOW),
+        },
+        Action::Dynamic | Action::Unknown(_) => ("?", MAGENTA),
+        Action::Remove => ("-", RED),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change_set::{
+        tests::{change, change_set, modify},
+        Action, ChangeSource, Evaluation, ModifyDetail, Replacement, RequiresRecreation,
+        ResourceChangeDetail, ResourceTargetDefinition,
+    };
+
+    use super::Render;
+
+    #[test]
+    fn render_plain() {
+        let mut subnet = change("Subnet", "AWS::EC2::Subnet", modify(Replacement::True));
+        if let Action::Modify(ModifyDetail { details, .. }) = &mut subnet.action {
+            details.push(ResourceChangeDetail {
+                change_source: Some(ChangeSource::ParameterReference("CidrBlock".to_string())),
+                evaluation: Evaluation::Static,
+                attribute_change_type: None,
+                target: ResourceTargetDefinition::Properties {
+                    name: Some("CidrBlock".to_string()),
+                    requires_recreation: RequiresRecreation::Always,
+                },
+                path: Some("/Properties/CidrBlock".to_string()),
+                before_value: Some("10.0.0.0/28".into()),
+                after_value: Some("10.0.0.16/28".into()),
+            });
+            details.push(ResourceChangeDetail {
+                change_source: Some(ChangeSource::DirectModification),
+                evaluation: Evaluation::Static,
+                attribute_change_type: None,
+                target: ResourceTargetDefinition::Tags,
+                path: None,
+                before_value: None,
+                after_value: None,
+            });
+        }
+        let mut nested = change(
+            "Nested",
+            "AWS::CloudFormation::Stack",
+            modify(Replacement::False),
+        );
+        nested.nested_change_set = Some(Box::new(change_set(vec![change(
+            "Queue",
+            "AWS::SQS::Queue",
+            Action::Add,
+        )])));
+        let change_set = change_set(vec![
+            change("Bucket", "AWS::S3::Bucket", Action::Remove),
+            subnet,
+            change("Topic", "AWS::SNS::Topic", Action::Add),
+            nested,
+        ]);
+
+        assert_eq!(
+            Render::new(&change_set).to_string(),
+            r#"Change set cs for stack stack:
+
+  + Topic (AWS::SNS::Topic)
+
+-/+ Subnet (AWS::EC2::Subnet) [replace]
+    ~ Properties.CidrBlock [requires recreation] (caused by parameter CidrBlock): "10.0.0.0/28" => "10.0.0.16/28"
+    ~ Tags
+
+  ~ Nested (AWS::CloudFormation::Stack)
+
+      + Queue (AWS::SQS::Queue)
+
+  - Bucket (AWS::S3::Bucket)
+
+Plan: 2 to add, 2 to modify (1 to replace), 1 to remove, 0 to import."#
+        );
+    }
+
+    #[test]
+    fn render_no_changes() {
+        assert_eq!(
+            Render::new(&change_set(Vec::new())).to_string(),
+            "Change set cs for stack stack:\n\nNo changes."
+        );
+    }
+
+    #[test]
+    fn render_color() {
+        let change_set = change_set(vec![change("Topic", "AWS::SNS::Topic", Action::Add)]);
+        let rendered = Render::new(&change_set).set_color(true).to_string();
+        assert!(rendered.contains("\x1b[32m  +\x1b[0m \x1b[1mTopic\x1b[0m (AWS::SNS::Topic)"));
+    }
+}
@@ -0,0 +1,353 @@
+use std::{fmt, future::Future, pin::Pin, task};
+
+use async_stream::try_stream;
+use aws_sdk_cloudformation::{
+    error::SdkError,
+    operation::{
+        continue_update_rollback::builders::ContinueUpdateRollbackFluentBuilder,
+        describe_stacks::DescribeStacksError,
+    },
+};
+use chrono::Utc;
+use futures_util::{Stream, TryStreamExt};
+
+use crate::{
+    stack::{
+        describe_stack_id, StackEventsError, StackOperation, StackOperationError,
+        StackOperationStatus,
+    },
+    unexpected_response::UnexpectedResponse,
+    PollingConfig, StackEvent, StackFailure, StackStatus, StackWarning,
+};
+
+/// The input for the `continue_update_rollback` operation.
+///
+/// You can create a continue update rollback input via the [`new`](Self::new) associated
+/// function. Setters are also available to make constructing sparse inputs more ergonomic.
+///
+/// ```no_run
+/// use cloudformatious::ContinueUpdateRollbackInput;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = aws_config::load_from_env().await;
+/// let client = cloudformatious::Client::new(&config);
+/// let input = ContinueUpdateRollbackInput::new("my-stack")
+///     .set_client_request_token("hello")
+///     .set_resources_to_skip(["MyResource"])
+///     .set_role_arn("arn:foo");
+/// client.continue_update_rollback(input).await?;
+/// // ...
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::module_name_repetitions)]
+pub struct ContinueUpdateRollbackInput {
+    /// A unique identifier for this `ContinueUpdateRollback` request. Specify this token if you
+    /// plan to retry requests so that AWS CloudFormation knows that you're not attempting to
+    /// continue the rollback of a stack with the same name. You might retry
+    /// `ContinueUpdateRollback` requests to ensure that AWS CloudFormation successfully received
+    /// them.
+    pub client_request_token: Option<String>,
+
+    /// The logical IDs of the resources that AWS CloudFormation skips during the continue update
+    /// rollback operation.
+    ///
+    /// You can specify only resources that are in the `UPDATE_FAILED` state because a rollback
+    /// failed. You can't specify resources that are in the `UPDATE_FAILED` state for other
+    /// reasons, for example, because an update was cancelled.
+    ///
+    /// CloudFormation sets the status of the specified resources to `UPDATE_COMPLETE` and
+    /// continues to roll back the stack. After the rollback is complete, the state of the skipped
+    /// resources will be inconsistent with the state of the resources in the stack template.
+    ///
+    /// To skip resources that are part of nested stacks, use the format
+    /// `NestedStackName.ResourceLogicalID`.
+    ///
+    /// [`StackFailure::resources_to_skip`] can be used to determine the resources whose rollback
+    /// failed.
+    pub resources_to_skip: Option<Vec<String>>,
+
+    /// The Amazon Resource Name (ARN) of an AWS Identity and Access Management (IAM) role that AWS
+    /// CloudFormation assumes to roll back the stack. AWS CloudFormation uses the role's
+    /// credentials to make calls on your behalf.
+    ///
+    /// If you don't specify a value, AWS CloudFormation uses the role that was previously
+    /// associated with the stack. If no role is available, AWS CloudFormation uses a temporary
+    /// session that is generated from your user credentials.
+    pub role_arn: Option<String>,
+
+    /// The name or the unique stack ID that is associated with the stack.
+    pub stack_name: String,
+}
+
+impl ContinueUpdateRollbackInput {
+    /// Construct an input for the given `stack_name`.
+    pub fn new(stack_name: impl Into<String>) -> Self {
+        Self {
+            stack_name: stack_name.into(),
+
+            client_request_token: None,
+            resources_to_skip: None,
+            role_arn: None,
+        }
+    }
+
+    /// Set the value for `client_request_token`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_client_request_token(mut self, client_request_token: impl Into<String>) -> Self {
+        self.client_request_token = Some(client_request_token.into());
+        self
+    }
+
+    /// Set the value for `resources_to_skip`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_resources_to_skip<I, S>(mut self, resources_to_skip: I) -> Self
+    where
+        I: Into<Vec<S>>,
+        S: Into<String>,
+    {
+        self.resources_to_skip = Some(
+            resources_to_skip
+                .into()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        );
+        self
+    }
+
+    /// Set the value for `role_arn`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_role_arn(mut self, role_arn: impl Into<String>) -> Self {
+        self.role_arn = Some(role_arn.into());
+        self
+    }
+
+    fn configure(
+        self,
+        input: ContinueUpdateRollbackFluentBuilder,
+    ) -> ContinueUpdateRollbackFluentBuilder {
+        input
+            .set_client_request_token(self.client_request_token)
+            .set_resources_to_skip(self.resources_to_skip)
+            .set_role_arn(self.role_arn)
+            .stack_name(self.stack_name)
+    }
+}
+
+/// Errors emitted by a `continue_update_rollback` operation.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ContinueUpdateRollbackError {
+    /// A CloudFormation API error occurred.
+    ///
+    /// This is likely to be due to the stack not being in the `UPDATE_ROLLBACK_FAILED` state, or
+    /// missing CloudFormation permissions. The inner error should have a descriptive message.
+    ///
+    /// **Note:** the inner error will always be some variant of [`SdkError`], but since they are
+    /// generic over the type of service errors we either need a variant per API used, or `Box`. If
+    /// you do need to programmatically match a particular API error you can use [`Box::downcast`].
+    CloudFormationApi(Box<dyn std::error::Error + Send + Sync>),
+
+    /// The rollback failed again.
+    ///
+    /// [`StackFailure::resources_to_skip`] can be used to determine the resources to skip when
+    /// trying again.
+    Failure(StackFailure),
+
+    /// The rollback succeeded with warnings.
+    Warning(StackWarning),
+
+    /// CloudFormation returned a response we didn't expect.
+    ///
+    /// This could be due to a missing field, or a value (such as a status) that we don't
+    /// recognise. It likely indicates a change in CloudFormation's behaviour that this library
+    /// hasn't caught up with.
+    UnexpectedResponse {
+        /// The CloudFormation API that returned the response, e.g. `DescribeStackEvents`.
+        api: &'static str,
+
+        /// A description of what was unexpected about the response.
+        detail: String,
+    },
+}
+
+impl ContinueUpdateRollbackError {
+    fn from_sdk_error<E: std::error::Error + Send + Sync + 'static>(error: SdkError<E>) -> Self {
+        Self::CloudFormationApi(error.into())
+    }
+}
+
+impl From<SdkError<DescribeStacksError>> for ContinueUpdateRollbackError {
+    fn from(error: SdkError<DescribeStacksError>) -> Self {
+        Self::from_sdk_error(error)
+    }
+}
+
+impl From<UnexpectedResponse> for ContinueUpdateRollbackError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse {
+            api: error.api,
+            detail: error.detail,
+        }
+    }
+}
+
+impl From<StackEventsError> for ContinueUpdateRollbackError {
+    fn from(error: StackEventsError) -> Self {
+        match error {
+            StackEventsError::Api(error) => Self::from_sdk_error(*error),
+            StackEventsError::UnexpectedResponse(error) => error.into(),
+        }
+    }
+}
+
+impl fmt::Display for ContinueUpdateRollbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CloudFormationApi(error) => {
+                write!(f, "CloudFormation API error: {error:#?}")
+            }
+            Self::Failure(failure) => write!(f, "{failure}"),
+            Self::Warning(warning) => write!(f, "{warning}"),
+            Self::UnexpectedResponse { api, detail } => {
+                write!(f, "unexpected response from {api}: {detail}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContinueUpdateRollbackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CloudFormationApi(error) => Some(error.as_ref()),
+            Self::Failure { .. } | Self::Warning { .. } | Self::UnexpectedResponse { .. } => None,
+        }
+    }
+}
+
+/// An ongoing `continue_update_rollback` operation.
+///
+/// This implements `Future`, which will simply wait for the stack to finish rolling back. If you
+/// want to observe progress, see [`ContinueUpdateRollback::events`].
+pub struct ContinueUpdateRollback {
+    event_stream:
+        Pin<Box<dyn Stream<Item = Result<StackEvent, ContinueUpdateRollbackError>> + Send>>,
+    output: Option<Result<(), ContinueUpdateRollbackError>>,
+}
+
+impl ContinueUpdateRollback {
+    pub(crate) fn new(
+        client: aws_sdk_cloudformation::Client,
+        polling: PollingConfig,
+        input: ContinueUpdateRollbackInput,
+    ) -> Self {
+        let event_stream = try_stream! {
+            let stack_id =
+                describe_stack_id::<ContinueUpdateRollbackError>(&client, &input.stack_name).await?;
+
+            let started_at = Utc::now();
+            input
+                .configure(client.continue_update_rollback())
+                .send()
+                .await
+                .map_err(ContinueUpdateRollbackError::from_sdk_error)?;
+
+            let mut operation =
+                StackOperation::new(&client, polling, stack_id, started_at, check_operation_status);
+            while let Some(event) = operation.try_next().await? {
+                yield event;
+            }
+
+            match operation.verify() {
+                Ok(()) => {}
+                Err(StackOperationError::Failure(failure)) => {
+                    Err(ContinueUpdateRollbackError::Failure(failure))?;
+                    unreachable!()
+                }
+                Err(StackOperationError::Warning(warning)) => {
+                    Err(ContinueUpdateRollbackError::Warning(warning))?;
+                    unreachable!()
+                }
+            }
+        };
+        Self {
+            event_stream: Box::pin(event_stream),
+            output: None,
+        }
+    }
+
+    /// Get a `Stream` of `StackEvent`s.
+    pub fn events(&mut self) -> ContinueUpdateRollbackEvents<'_> {
+        ContinueUpdateRollbackEvents(self)
+    }
+
+    fn poll_next_internal(&mut self, ctx: &mut task::Context) -> task::Poll<Option<StackEvent>> {
+        match self.event_stream.as_mut().poll_next(ctx) {
+            task::Poll::Pending => task::Poll::Pending,
+            task::Poll::Ready(None) => {
+                self.output.get_or_insert(Ok(()));
+                task::Poll::Ready(None)
+            }
+            task::Poll::Ready(Some(Ok(event))) => task::Poll::Ready(Some(event)),
+            task::Poll::Ready(Some(Err(error))) => {
+                self.output.replace(Err(error));
+                task::Poll::Ready(None)
+            }
+        }
+    }
+}
+
+impl Future for ContinueUpdateRollback {
+    type Output = Result<(), ContinueUpdateRollbackError>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context) -> task::Poll<Self::Output> {
+        loop {
+            match self.poll_next_internal(ctx) {
+                task::Poll::Pending => return task::Poll::Pending,
+                task::Poll::Ready(None) => {
+                    return task::Poll::Ready(
+                        self.output
+                            .take()
+                            .expect("end of stream without err or output"),
+                    )
+                }
+                task::Poll::Ready(Some(_)) => {}
+            }
+        }
+    }
+}
+
+/// Return value of [`ContinueUpdateRollback::events`].
+#[allow(clippy::module_name_repetitions)]
+pub struct ContinueUpdateRollbackEvents<'rollback>(&'rollback mut ContinueUpdateRollback);
+
+impl Stream for ContinueUpdateRollbackEvents<'_> {
+    type Item = StackEvent;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut task::Context,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.0.poll_next_internal(ctx)
+    }
+}
+
+fn check_operation_status(stack_status: &StackStatus) -> StackOperationStatus {
+    match stack_status {
+        StackStatus::UpdateRollbackInProgress
+        | StackStatus::UpdateRollbackCompleteCleanupInProgress => StackOperationStatus::InProgress,
+        StackStatus::UpdateRollbackComplete => StackOperationStatus::Complete,
+        StackStatus::UpdateRollbackFailed => StackOperationStatus::Failed,
+        StackStatus::Unknown(_) => StackOperationStatus::unknown(stack_status),
+        _ => StackOperationStatus::Unexpected,
+    }
+}
@@ -11,8 +11,10 @@ use chrono::Utc;
 use futures_util::{Stream, TryStreamExt};
 
 use crate::{
-    stack::{StackOperation, StackOperationError, StackOperationStatus},
-    StackEvent, StackFailure, StackStatus, StackWarning,
+    apply_stack::DESCRIBE_STACKS,
+    stack::{StackEventsError, StackOperation, StackOperationError, StackOperationStatus},
+    unexpected_response::{Expected, UnexpectedResponse},
+    PollingConfig, StackEvent, StackFailure, StackStatus, StackWarning,
 };
 
 /// The input for the `delete_stack` operation.
@@ -36,6 +38,7 @@ use crate::{
 /// # Ok(())
 /// # }
 /// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::module_name_repetitions)]
 pub struct DeleteStackInput {
     /// A unique identifier for this `DeleteStack` request. Specify this token if you plan to retry
@@ -147,21 +150,52 @@ pub enum DeleteStackError {
     /// **Note:** the inner error will always be some variant of [`SdkError`], but since they are
     /// generic over the type of service errors we either need a variant per API used, or `Box`. If
     /// you do need to programmatically match a particular API error you can use [`Box::downcast`].
-    CloudFormationApi(Box<dyn std::error::Error>),
+    CloudFormationApi(Box<dyn std::error::Error + Send + Sync>),
 
     /// The delete stack operation failed.
     Failure(StackFailure),
 
     /// The delete stack operation succeeded with warnings.
     Warning(StackWarning),
+
+    /// CloudFormation returned a response we didn't expect.
+    ///
+    /// This could be due to a missing field, or a value (such as a status) that we don't
+    /// recognise. It likely indicates a change in CloudFormation's behaviour that this library
+    /// hasn't caught up with.
+    UnexpectedResponse {
+        /// The CloudFormation API that returned the response, e.g. `DescribeStackEvents`.
+        api: &'static str,
+
+        /// A description of what was unexpected about the response.
+        detail: String,
+    },
 }
 
 impl DeleteStackError {
-    fn from_sdk_error<E: std::error::Error + 'static>(error: SdkError<E>) -> Self {
+    fn from_sdk_error<E: std::error::Error + Send + Sync + 'static>(error: SdkError<E>) -> Self {
         Self::CloudFormationApi(error.into())
     }
 }
 
+impl From<UnexpectedResponse> for DeleteStackError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse {
+            api: error.api,
+            detail: error.detail,
+        }
+    }
+}
+
+impl From<StackEventsError> for DeleteStackError {
+    fn from(error: StackEventsError) -> Self {
+        match error {
+            StackEventsError::Api(error) => Self::from_sdk_error(*error),
+            StackEventsError::UnexpectedResponse(error) => error.into(),
+        }
+    }
+}
+
 impl fmt::Display for DeleteStackError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -170,6 +204,9 @@ impl fmt::Display for DeleteStackError {
             }
             Self::Failure(failure) => write!(f, "{failure}"),
             Self::Warning(warning) => write!(f, "{warning}"),
+            Self::UnexpectedResponse { api, detail } => {
+                write!(f, "unexpected response from {api}: {detail}")
+            }
         }
     }
 }
@@ -178,7 +215,7 @@ impl std::error::Error for DeleteStackError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::CloudFormationApi(error) => Some(error.as_ref()),
-            Self::Failure { .. } | Self::Warning { .. } => None,
+            Self::Failure { .. } | Self::Warning { .. } | Self::UnexpectedResponse { .. } => None,
         }
     }
 }
@@ -187,18 +224,19 @@ impl std::error::Error for DeleteStackError {
 ///
 /// This implements `Future`, which will simply wait for the operation to conclude. If you want to
 /// observe progress, see [`DeleteStack::events`].
-pub struct DeleteStack<'client> {
-    event_stream: Pin<Box<dyn Stream<Item = Result<StackEvent, DeleteStackError>> + 'client>>,
+pub struct DeleteStack {
+    event_stream: Pin<Box<dyn Stream<Item = Result<StackEvent, DeleteStackError>> + Send>>,
     output: Option<Result<(), DeleteStackError>>,
 }
 
-impl<'client> DeleteStack<'client> {
+impl DeleteStack {
     pub(crate) fn new(
-        client: &'client aws_sdk_cloudformation::Client,
+        client: aws_sdk_cloudformation::Client,
+        polling: PollingConfig,
         input: DeleteStackInput,
     ) -> Self {
         let event_stream = try_stream! {
-            let Some(stack_id) = (describe_stack_id(client, input.stack_name.clone()).await?) else {
+            let Some(stack_id) = (describe_stack_id(&client, input.stack_name.clone()).await?) else {
                 return;
             };
 
@@ -208,12 +246,8 @@ impl<'client> DeleteStack<'client> {
                 .map_err(DeleteStackError::from_sdk_error)?;
 
             let mut operation =
-                StackOperation::new(client, stack_id, started_at, check_operation_status);
-            while let Some(event) = operation
-                .try_next()
-                .await
-                .map_err(DeleteStackError::from_sdk_error)?
-            {
+                StackOperation::new(&client, polling, stack_id, started_at, check_operation_status);
+            while let Some(event) = operation.try_next().await? {
                 yield event;
             }
 
@@ -227,7 +261,7 @@ impl<'client> DeleteStack<'client> {
                     Err(DeleteStackError::Warning(warning))?;
                     unreachable!()
                 }
-            };
+            }
         };
         Self {
             event_stream: Box::pin(event_stream),
@@ -236,7 +270,7 @@ impl<'client> DeleteStack<'client> {
     }
 
     /// Get a `Stream` of `StackEvent`s.
-    pub fn events(&mut self) -> DeleteStackEvents<'client, '_> {
+    pub fn events(&mut self) -> DeleteStackEvents<'_> {
         DeleteStackEvents(self)
     }
 
@@ -256,7 +290,7 @@ impl<'client> DeleteStack<'client> {
     }
 }
 
-impl Future for DeleteStack<'_> {
+impl Future for DeleteStack {
     type Output = Result<(), DeleteStackError>;
 
     fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context) -> task::Poll<Self::Output> {
@@ -270,7 +304,7 @@ impl Future for DeleteStack<'_> {
                             .expect("end of stream without err or output"),
                     )
                 }
-                task::Poll::Ready(Some(_)) => continue,
+                task::Poll::Ready(Some(_)) => {}
             }
         }
     }
@@ -278,9 +312,9 @@ impl Future for DeleteStack<'_> {
 
 /// Return value of [`DeleteStack::events`].
 #[allow(clippy::module_name_repetitions)]
-pub struct DeleteStackEvents<'client, 'delete>(&'delete mut DeleteStack<'client>);
+pub struct DeleteStackEvents<'delete>(&'delete mut DeleteStack);
 
-impl Stream for DeleteStackEvents<'_, '_> {
+impl Stream for DeleteStackEvents<'_> {
     type Item = StackEvent;
 
     fn poll_next(
@@ -303,22 +337,26 @@ async fn describe_stack_id(
 
     let stack = output
         .stacks
-        .expect("DescribeStacksOutput without stacks")
-        .pop()
-        .expect("DescribeStacksOutput empty stacks");
+        .and_then(|mut stacks| stacks.pop())
+        .expected(DESCRIBE_STACKS, "DescribeStacksOutput without stacks")?;
 
     if stack.stack_status == Some(aws_sdk_cloudformation::types::StackStatus::DeleteComplete) {
         Ok(None)
     } else {
-        Ok(Some(stack.stack_id.expect("Stack without stack_id")))
+        Ok(Some(
+            stack
+                .stack_id
+                .expected(DESCRIBE_STACKS, "Stack without stack_id")?,
+        ))
     }
 }
 
-fn check_operation_status(stack_status: StackStatus) -> StackOperationStatus {
+fn check_operation_status(stack_status: &StackStatus) -> StackOperationStatus {
     match stack_status {
         StackStatus::DeleteInProgress => StackOperationStatus::InProgress,
         StackStatus::DeleteComplete => StackOperationStatus::Complete,
         StackStatus::DeleteFailed => StackOperationStatus::Failed,
+        StackStatus::Unknown(_) => StackOperationStatus::unknown(stack_status),
         _ => StackOperationStatus::Unexpected,
     }
 }
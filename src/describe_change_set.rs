@@ -0,0 +1,153 @@
+use std::fmt;
+
+use crate::{
+    change_set::{describe_change_set, describe_nested_change_sets, ChangeSet, DescribeError},
+    unexpected_response::UnexpectedResponse,
+    ChangeSetStatus, PollingConfig,
+};
+
+/// The input for the `describe_change_set` operation.
+///
+/// You can create a describe change set input via the [`new`](Self::new) associated function.
+/// Setters are also available to make constructing sparse inputs more ergonomic.
+///
+/// ```no_run
+/// use cloudformatious::DescribeChangeSetInput;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = aws_config::load_from_env().await;
+/// let client = cloudformatious::Client::new(&config);
+/// let input = DescribeChangeSetInput::new("arn:aws:cloudformation:...")
+///     .set_include_property_values(true);
+/// let change_set = client.describe_change_set(input).await?;
+/// // ...
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::module_name_repetitions)]
+pub struct DescribeChangeSetInput {
+    /// The ARN of the change set to describe.
+    ///
+    /// This is the [`change_set_id`](crate::change_set::ChangeSet::change_set_id) of a change set
+    /// returned by [`Client::create_change_set`](crate::Client::create_change_set).
+    pub change_set_id: String,
+
+    /// Whether or not to include the before and after values of changed properties.
+    ///
+    /// See [`ApplyStackInput::include_property_values`](crate::ApplyStackInput::include_property_values)
+    /// for more information.
+    pub include_property_values: bool,
+}
+
+impl DescribeChangeSetInput {
+    /// Construct an input for the given `change_set_id`.
+    pub fn new(change_set_id: impl Into<String>) -> Self {
+        Self {
+            change_set_id: change_set_id.into(),
+
+            include_property_values: false,
+        }
+    }
+
+    /// Set the value for `include_property_values`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_include_property_values(mut self, include_property_values: bool) -> Self {
+        self.include_property_values = include_property_values;
+        self
+    }
+}
+
+/// Errors emitted by a `describe_change_set` operation.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum DescribeChangeSetError {
+    /// A CloudFormation API error occurred.
+    ///
+    /// This is likely to be due to the change set not existing, or missing CloudFormation
+    /// permissions. The inner error should have a descriptive message.
+    ///
+    /// **Note:** the inner error will always be some variant of [`SdkError`], but since they are
+    /// generic over the type of service errors we either need a variant per API used, or `Box`. If
+    /// you do need to programmatically match a particular API error you can use [`Box::downcast`].
+    ///
+    /// [`SdkError`]: aws_sdk_cloudformation::error::SdkError
+    CloudFormationApi(Box<dyn std::error::Error + Send + Sync>),
+
+    /// CloudFormation returned a response we didn't expect.
+    ///
+    /// This could be due to a missing field, or a value (such as a status) that we don't
+    /// recognise. It likely indicates a change in CloudFormation's behaviour that this library
+    /// hasn't caught up with.
+    UnexpectedResponse {
+        /// The CloudFormation API that returned the response, e.g. `DescribeChangeSet`.
+        api: &'static str,
+
+        /// A description of what was unexpected about the response.
+        detail: String,
+    },
+}
+
+impl From<DescribeError> for DescribeChangeSetError {
+    fn from(error: DescribeError) -> Self {
+        match error {
+            DescribeError::Api(error) => Self::CloudFormationApi(error),
+            DescribeError::UnexpectedResponse(UnexpectedResponse { api, detail }) => {
+                Self::UnexpectedResponse { api, detail }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DescribeChangeSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CloudFormationApi(error) => {
+                write!(f, "CloudFormation API error: {error:#?}")
+            }
+            Self::UnexpectedResponse { api, detail } => {
+                write!(f, "unexpected response from {api}: {detail}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DescribeChangeSetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CloudFormationApi(error) => Some(error.as_ref()),
+            Self::UnexpectedResponse { .. } => None,
+        }
+    }
+}
+
+pub(crate) async fn describe_change_set_internal(
+    client: &aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
+    input: DescribeChangeSetInput,
+) -> Result<ChangeSet, DescribeChangeSetError> {
+    let mut change_set = describe_change_set(
+        client,
+        polling,
+        &input.change_set_id,
+        input.include_property_values,
+    )
+    .await?;
+
+    // Nested change sets may still be being created until the root change set has settled.
+    if change_set.status == ChangeSetStatus::CreateComplete {
+        describe_nested_change_sets(
+            client,
+            polling,
+            &mut change_set.changes,
+            input.include_property_values,
+        )
+        .await?;
+    }
+
+    Ok(change_set)
+}
@@ -0,0 +1,585 @@
+//! Types for detecting and describing stack drift.
+//!
+//! A stack has drifted when its resources have been changed outside of CloudFormation, such that
+//! their actual configuration differs from the configuration in the stack's template. See
+//! [`Client::detect_stack_drift`](crate::Client::detect_stack_drift).
+
+use std::{fmt, future::Future, pin::Pin, task};
+
+use aws_sdk_cloudformation::error::SdkError;
+use aws_smithy_types_convert::date_time::DateTimeExt;
+use chrono::{DateTime, Utc};
+use futures_util::TryFutureExt;
+use tokio::time::{interval_at, Instant};
+
+use crate::{
+    unexpected_response::{Expected, UnexpectedResponse},
+    PollingConfig,
+};
+
+const DETECT_STACK_DRIFT: &str = "DetectStackDrift";
+const DESCRIBE_STACK_DRIFT_DETECTION_STATUS: &str = "DescribeStackDriftDetectionStatus";
+const DESCRIBE_STACK_RESOURCE_DRIFTS: &str = "DescribeStackResourceDrifts";
+
+/// The result of detecting drift on a stack.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::module_name_repetitions)]
+pub struct StackDrift {
+    /// The ID of the stack.
+    pub stack_id: String,
+
+    /// Whether or not the stack has drifted from its template.
+    pub stack_drift_status: StackDriftStatus,
+
+    /// The reason the drift detection failed, if it did.
+    ///
+    /// Drift detection fails if it couldn't be completed for at least one resource, in which case
+    /// the drift of the remaining resources is still reported.
+    pub detection_status_reason: Option<String>,
+
+    /// The time at which drift detection was initiated.
+    pub timestamp: DateTime<Utc>,
+
+    /// The drift of each resource in the stack that supports drift detection.
+    pub resource_drifts: Vec<ResourceDrift>,
+}
+
+impl StackDrift {
+    /// The resources that have been modified or deleted outside of CloudFormation.
+    pub fn drifted_resources(&self) -> impl Iterator<Item = &ResourceDrift> {
+        self.resource_drifts
+            .iter()
+            .filter(|resource_drift| resource_drift.stack_resource_drift_status.is_drifted())
+    }
+}
+
+/// Whether or not a stack has drifted from its template.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[display(style = "SNAKE_CASE")]
+#[allow(clippy::module_name_repetitions)]
+pub enum StackDriftStatus {
+    /// One or more resources in the stack differ from their expected template configurations.
+    Drifted,
+
+    /// The stack's resources match their expected template configurations.
+    InSync,
+
+    /// CloudFormation hasn't checked if the stack differs from its expected template
+    /// configuration.
+    NotChecked,
+
+    /// CloudFormation couldn't determine whether the stack has drifted.
+    Unknown,
+}
+
+/// Describes whether a resource has drifted from its template configuration, and how.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResourceDrift {
+    /// The logical name of the resource specified in the template.
+    pub logical_resource_id: String,
+
+    /// The name or unique identifier that corresponds to a physical instance ID of the resource.
+    pub physical_resource_id: Option<String>,
+
+    /// The type of the resource.
+    pub resource_type: String,
+
+    /// The ID of the stack the resource belongs to.
+    pub stack_id: String,
+
+    /// Whether or not the resource has drifted from its template configuration.
+    pub stack_resource_drift_status: ResourceDriftStatus,
+
+    /// The resource's expected properties, as defined in the stack template and any values
+    /// specified as template parameters.
+    ///
+    /// This is not present for resources whose drift status is `DELETED`.
+    pub expected_properties: Option<serde_json::Value>,
+
+    /// The resource's actual properties.
+    ///
+    /// This is not present for resources whose drift status is `DELETED`.
+    pub actual_properties: Option<serde_json::Value>,
+
+    /// The properties of the resource that differ from their expected values.
+    ///
+    /// This is empty unless the resource's drift status is `MODIFIED`.
+    pub property_differences: Vec<PropertyDifference>,
+
+    /// The reason drift couldn't be detected for the resource, if it couldn't.
+    pub drift_status_reason: Option<String>,
+
+    /// The time at which CloudFormation performed drift detection on the resource.
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ResourceDrift {
+    fn from_sdk(
+        resource_drift: aws_sdk_cloudformation::types::StackResourceDrift,
+    ) -> Result<Self, UnexpectedResponse> {
+        let parse_properties = |properties: Option<String>, detail: &str| {
+            properties
+                .map(|properties| serde_json::from_str(&properties))
+                .transpose()
+                .expected(DESCRIBE_STACK_RESOURCE_DRIFTS, detail)
+        };
+        Ok(Self {
+            logical_resource_id: resource_drift.logical_resource_id.expected(
+                DESCRIBE_STACK_RESOURCE_DRIFTS,
+                "StackResourceDrift without logical_resource_id",
+            )?,
+            physical_resource_id: resource_drift.physical_resource_id,
+            resource_type: resource_drift.resource_type.expected(
+                DESCRIBE_STACK_RESOURCE_DRIFTS,
+                "StackResourceDrift without resource_type",
+            )?,
+            stack_id: resource_drift.stack_id.expected(
+                DESCRIBE_STACK_RESOURCE_DRIFTS,
+                "StackResourceDrift without stack_id",
+            )?,
+            stack_resource_drift_status: resource_drift
+                .stack_resource_drift_status
+                .expected(
+                    DESCRIBE_STACK_RESOURCE_DRIFTS,
+                    "StackResourceDrift without stack_resource_drift_status",
+                )?
+                .as_str()
+                .parse()
+                .expected(
+                    DESCRIBE_STACK_RESOURCE_DRIFTS,
+                    "StackResourceDrift with invalid stack_resource_drift_status",
+                )?,
+            expected_properties: parse_properties(
+                resource_drift.expected_properties,
+                "StackResourceDrift with invalid expected_properties",
+            )?,
+            actual_properties: parse_properties(
+                resource_drift.actual_properties,
+                "StackResourceDrift with invalid actual_properties",
+            )?,
+            property_differences: resource_drift
+                .property_differences
+                .unwrap_or_default()
+                .into_iter()
+                .map(PropertyDifference::from_sdk)
+                .collect::<Result<_, _>>()?,
+            drift_status_reason: resource_drift.drift_status_reason,
+            timestamp: resource_drift
+                .timestamp
+                .expected(
+                    DESCRIBE_STACK_RESOURCE_DRIFTS,
+                    "StackResourceDrift without timestamp",
+                )?
+                .to_chrono_utc()
+                .expected(
+                    DESCRIBE_STACK_RESOURCE_DRIFTS,
+                    "StackResourceDrift with invalid timestamp",
+                )?,
+        })
+    }
+}
+
+/// Whether or not a resource has drifted from its template configuration.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[display(style = "SNAKE_CASE")]
+pub enum ResourceDriftStatus {
+    /// The resource differs from its expected template configuration because the resource has
+    /// been deleted.
+    Deleted,
+
+    /// One or more resource properties differ from their expected values (as defined in the stack
+    /// template and any values specified as template parameters).
+    Modified,
+
+    /// The resource's actual configuration matches its expected template configuration.
+    InSync,
+
+    /// CloudFormation doesn't currently return this value.
+    NotChecked,
+
+    /// CloudFormation couldn't determine whether the resource has drifted.
+    Unknown,
+
+    /// The resource type doesn't support drift detection.
+    Unsupported,
+}
+
+impl ResourceDriftStatus {
+    /// Whether or not the resource has drifted (i.e. it has been modified or deleted).
+    #[must_use]
+    pub fn is_drifted(self) -> bool {
+        matches!(self, Self::Deleted | Self::Modified)
+    }
+}
+
+/// A resource property whose actual value differs from its expected value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropertyDifference {
+    /// The fully-qualified path to the resource property, e.g. `/Properties/Tags/0/Value`.
+    pub property_path: String,
+
+    /// The expected value of the property, as defined in the stack template and any values
+    /// specified as template parameters.
+    pub expected_value: String,
+
+    /// The actual value of the property.
+    pub actual_value: String,
+
+    /// How the actual value differs from the expected value.
+    pub difference_type: DifferenceType,
+}
+
+impl PropertyDifference {
+    fn from_sdk(
+        difference: aws_sdk_cloudformation::types::PropertyDifference,
+    ) -> Result<Self, UnexpectedResponse> {
+        Ok(Self {
+            property_path: difference.property_path.expected(
+                DESCRIBE_STACK_RESOURCE_DRIFTS,
+                "PropertyDifference without property_path",
+            )?,
+            expected_value: difference.expected_value.expected(
+                DESCRIBE_STACK_RESOURCE_DRIFTS,
+                "PropertyDifference without expected_value",
+            )?,
+            actual_value: difference.actual_value.expected(
+                DESCRIBE_STACK_RESOURCE_DRIFTS,
+                "PropertyDifference without actual_value",
+            )?,
+            difference_type: difference
+                .difference_type
+                .expected(
+                    DESCRIBE_STACK_RESOURCE_DRIFTS,
+                    "PropertyDifference without difference_type",
+                )?
+                .as_str()
+                .parse()
+                .expected(
+                    DESCRIBE_STACK_RESOURCE_DRIFTS,
+                    "PropertyDifference with invalid difference_type",
+                )?,
+        })
+    }
+}
+
+/// How a property's actual value differs from its expected value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[display(style = "SNAKE_CASE")]
+pub enum DifferenceType {
+    /// A value has been added to a resource property that's an array or list data type.
+    Add,
+
+    /// The property has been removed from the current resource configuration.
+    Remove,
+
+    /// The current property value differs from its expected value (as defined in the stack
+    /// template and any values specified as template parameters).
+    NotEqual,
+}
+
+/// Errors emitted by a `detect_stack_drift` operation.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum DetectStackDriftError {
+    /// A CloudFormation API error occurred.
+    ///
+    /// This is likely to be due to the stack not existing or missing CloudFormation permissions.
+    /// The inner error should have a descriptive message.
+    ///
+    /// **Note:** the inner error will always be some variant of [`SdkError`], but since they are
+    /// generic over the type of service errors we either need a variant per API used, or `Box`. If
+    /// you do need to programmatically match a particular API error you can use [`Box::downcast`].
+    CloudFormationApi(Box<dyn std::error::Error + Send + Sync>),
+
+    /// Drift detection failed for at least one resource.
+    ///
+    /// The error includes the drift of the resources for which detection succeeded, and the
+    /// reason for the failure in [`detection_status_reason`](StackDrift::detection_status_reason).
+    DetectionFailed(StackDrift),
+
+    /// CloudFormation returned a response we didn't expect.
+    ///
+    /// This could be due to a missing field, or a value (such as a status) that we don't
+    /// recognise. It likely indicates a change in CloudFormation's behaviour that this library
+    /// hasn't caught up with.
+    UnexpectedResponse {
+        /// The CloudFormation API that returned the response, e.g. `DescribeStackEvents`.
+        api: &'static str,
+
+        /// A description of what was unexpected about the response.
+        detail: String,
+    },
+}
+
+impl DetectStackDriftError {
+    fn from_sdk_error<E: std::error::Error + Send + Sync + 'static>(error: SdkError<E>) -> Self {
+        Self::CloudFormationApi(error.into())
+    }
+}
+
+impl From<UnexpectedResponse> for DetectStackDriftError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse {
+            api: error.api,
+            detail: error.detail,
+        }
+    }
+}
+
+impl fmt::Display for DetectStackDriftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CloudFormationApi(error) => {
+                write!(f, "CloudFormation API error: {error:#?}")
+            }
+            Self::DetectionFailed(drift) => {
+                write!(
+                    f,
+                    "Drift detection failed for {}: {}",
+                    drift.stack_id,
+                    drift
+                        .detection_status_reason
+                        .as_deref()
+                        .unwrap_or("no reason reported")
+                )
+            }
+            Self::UnexpectedResponse { api, detail } => {
+                write!(f, "unexpected response from {api}: {detail}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DetectStackDriftError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CloudFormationApi(error) => Some(error.as_ref()),
+            Self::DetectionFailed(_) | Self::UnexpectedResponse { .. } => None,
+        }
+    }
+}
+
+/// An ongoing `detect_stack_drift` operation.
+///
+/// This implements `Future`, which will wait for drift detection to finish and resolve to the
+/// [`StackDrift`].
+#[allow(clippy::module_name_repetitions)]
+pub struct DetectStackDrift {
+    future: Pin<Box<dyn Future<Output = Result<StackDrift, DetectStackDriftError>> + Send>>,
+}
+
+impl DetectStackDrift {
+    pub(crate) fn new(
+        client: aws_sdk_cloudformation::Client,
+        polling: PollingConfig,
+        stack_name: String,
+    ) -> Self {
+        Self {
+            future: Box::pin(async move { detect_stack_drift(&client, polling, stack_name).await }),
+        }
+    }
+}
+
+impl Future for DetectStackDrift {
+    type Output = Result<StackDrift, DetectStackDriftError>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context) -> task::Poll<Self::Output> {
+        self.future.as_mut().poll(ctx)
+    }
+}
+
+async fn detect_stack_drift(
+    client: &aws_sdk_cloudformation::Client,
+    polling: PollingConfig,
+    stack_name: String,
+) -> Result<StackDrift, DetectStackDriftError> {
+    let stack_drift_detection_id = polling
+        .retry_throttled(polling.drift_detection_interval, || {
+            client.detect_stack_drift().stack_name(&stack_name).send()
+        })
+        .map_err(DetectStackDriftError::from_sdk_error)
+        .await?
+        .stack_drift_detection_id
+        .expected(
+            DETECT_STACK_DRIFT,
+            "DetectStackDriftOutput without stack_drift_detection_id",
+        )?;
+
+    let mut interval = interval_at(
+        Instant::now() + polling.drift_detection_interval,
+        polling.drift_detection_interval,
+    );
+    let (status, failed) = loop {
+        interval.tick().await;
+
+        let status = polling
+            .retry_throttled(polling.drift_detection_interval, || {
+                client
+                    .describe_stack_drift_detection_status()
+                    .stack_drift_detection_id(&stack_drift_detection_id)
+                    .send()
+            })
+            .map_err(DetectStackDriftError::from_sdk_error)
+            .await?;
+        match status.detection_status.clone().expected(
+            DESCRIBE_STACK_DRIFT_DETECTION_STATUS,
+            "DescribeStackDriftDetectionStatusOutput without detection_status",
+        )? {
+            aws_sdk_cloudformation::types::StackDriftDetectionStatus::DetectionInProgress => {}
+            aws_sdk_cloudformation::types::StackDriftDetectionStatus::DetectionComplete => {
+                break (status, false);
+            }
+            aws_sdk_cloudformation::types::StackDriftDetectionStatus::DetectionFailed => {
+                break (status, true);
+            }
+            detection_status => {
+                return Err(UnexpectedResponse::new(
+                    DESCRIBE_STACK_DRIFT_DETECTION_STATUS,
+                    format!("drift detection had unexpected status: {detection_status}"),
+                )
+                .into());
+            }
+        }
+    };
+    let stack_id = status.stack_id.expected(
+        DESCRIBE_STACK_DRIFT_DETECTION_STATUS,
+        "DescribeStackDriftDetectionStatusOutput without stack_id",
+    )?;
+    let drift = StackDrift {
+        stack_drift_status: status
+            .stack_drift_status
+            .expected(
+                DESCRIBE_STACK_DRIFT_DETECTION_STATUS,
+                "DescribeStackDriftDetectionStatusOutput without stack_drift_status",
+            )?
+            .as_str()
+            .parse()
+            .expected(
+                DESCRIBE_STACK_DRIFT_DETECTION_STATUS,
+                "DescribeStackDriftDetectionStatusOutput with invalid stack_drift_status",
+            )?,
+        detection_status_reason: status.detection_status_reason,
+        timestamp: status
+            .timestamp
+            .expected(
+                DESCRIBE_STACK_DRIFT_DETECTION_STATUS,
+                "DescribeStackDriftDetectionStatusOutput without timestamp",
+            )?
+            .to_chrono_utc()
+            .expected(
+                DESCRIBE_STACK_DRIFT_DETECTION_STATUS,
+                "DescribeStackDriftDetectionStatusOutput with invalid timestamp",
+            )?,
+        resource_drifts: describe_resource_drifts(client, &polling, &stack_id).await?,
+        stack_id,
+    };
+
+    if failed {
+        Err(DetectStackDriftError::DetectionFailed(drift))
+    } else {
+        Ok(drift)
+    }
+}
+
+/// Describe the drift of all the resources in a stack, following `next_token`.
+async fn describe_resource_drifts(
+    client: &aws_sdk_cloudformation::Client,
+    polling: &PollingConfig,
+    stack_id: &str,
+) -> Result<Vec<ResourceDrift>, DetectStackDriftError> {
+    let mut resource_drifts = Vec::new();
+    let mut next_token = None;
+    loop {
+        let output = polling
+            .retry_throttled(polling.drift_detection_interval, || {
+                client
+                    .describe_stack_resource_drifts()
+                    .stack_name(stack_id)
+                    .set_next_token(next_token.clone())
+                    .send()
+            })
+            .map_err(DetectStackDriftError::from_sdk_error)
+            .await?;
+        for resource_drift in output.stack_resource_drifts.expected(
+            DESCRIBE_STACK_RESOURCE_DRIFTS,
+            "DescribeStackResourceDriftsOutput without stack_resource_drifts",
+        )? {
+            resource_drifts.push(ResourceDrift::from_sdk(resource_drift)?);
+        }
+
+        next_token = output.next_token;
+        if next_token.is_none() {
+            return Ok(resource_drifts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_cloudformation::{
+        primitives::DateTime,
+        types::{DifferenceType, PropertyDifference, StackResourceDrift, StackResourceDriftStatus},
+    };
+
+    use super::ResourceDrift;
+
+    #[test]
+    fn resource_drift_from_sdk() {
+        let resource_drift = StackResourceDrift::builder()
+            .logical_resource_id("Queue")
+            .physical_resource_id("https://sqs.eu-west-1.amazonaws.com/123456789012/queue")
+            .resource_type("AWS::SQS::Queue")
+            .stack_id("arn:stack")
+            .stack_resource_drift_status(StackResourceDriftStatus::Modified)
+            .expected_properties(r#"{"VisibilityTimeout":30}"#)
+            .actual_properties(r#"{"VisibilityTimeout":60}"#)
+            .property_differences(
+                PropertyDifference::builder()
+                    .property_path("/VisibilityTimeout")
+                    .expected_value("30")
+                    .actual_value("60")
+                    .difference_type(DifferenceType::NotEqual)
+                    .build(),
+            )
+            .timestamp(DateTime::from_secs(0))
+            .build();
+
+        let resource_drift = ResourceDrift::from_sdk(resource_drift).unwrap();
+        assert!(resource_drift.stack_resource_drift_status.is_drifted());
+        assert_eq!(
+            resource_drift.expected_properties,
+            Some(serde_json::json!({ "VisibilityTimeout": 30 }))
+        );
+        assert_eq!(
+            resource_drift.actual_properties,
+            Some(serde_json::json!({ "VisibilityTimeout": 60 }))
+        );
+        assert_eq!(resource_drift.property_differences.len(), 1);
+        assert_eq!(
+            resource_drift.property_differences[0].difference_type,
+            super::DifferenceType::NotEqual
+        );
+    }
+
+    #[test]
+    fn resource_drift_from_sdk_invalid_properties() {
+        let resource_drift = StackResourceDrift::builder()
+            .logical_resource_id("Queue")
+            .resource_type("AWS::SQS::Queue")
+            .stack_id("arn:stack")
+            .stack_resource_drift_status(StackResourceDriftStatus::Modified)
+            .expected_properties("oh no")
+            .timestamp(DateTime::from_secs(0))
+            .build();
+
+        let error = ResourceDrift::from_sdk(resource_drift).unwrap_err();
+        assert_eq!(error.api, "DescribeStackResourceDrifts");
+        assert_eq!(
+            error.detail,
+            "StackResourceDrift with invalid expected_properties"
+        );
+    }
+}
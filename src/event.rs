@@ -4,13 +4,19 @@
 use aws_smithy_types_convert::date_time::DateTimeExt;
 use chrono::{DateTime, Utc};
 
-use crate::{status_reason::StatusReason, ResourceStatus, StackStatus, Status};
+use crate::{
+    stack::DESCRIBE_STACK_EVENTS,
+    status_reason::StatusReason,
+    unexpected_response::{Expected, UnexpectedResponse},
+    ResourceStatus, StackStatus, Status,
+};
 
 /// A stack event from the `DescribeStackEvents` API.
 ///
 /// Stack events are represented as an enum because the API reports both events for the stack and
 /// events for the resources in the stack, but these can have different sets of statuses.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StackEvent {
     /// An event for the stack itself.
     Stack {
@@ -148,53 +154,67 @@ impl StackEvent {
     pub(crate) fn from_sdk(
         stack_alias: Option<String>,
         event: aws_sdk_cloudformation::types::StackEvent,
-    ) -> Self {
+    ) -> Result<Self, UnexpectedResponse> {
         let is_stack = event.physical_resource_id.as_deref() == event.stack_id.as_deref();
         let resource_status = event
             .resource_status
-            .expect("StackEvent without resource_status");
+            .expected(DESCRIBE_STACK_EVENTS, "StackEvent without resource_status")?;
         let details = StackEventDetails {
             client_request_token: event.client_request_token,
-            event_id: event.event_id.expect("StackEvent without event_id"),
-            logical_resource_id: event
-                .logical_resource_id
-                .expect("StackEvent without logical_resource_id"),
+            event_id: event
+                .event_id
+                .expected(DESCRIBE_STACK_EVENTS, "StackEvent without event_id")?,
+            logical_resource_id: event.logical_resource_id.expected(
+                DESCRIBE_STACK_EVENTS,
+                "StackEvent without logical_resource_id",
+            )?,
             physical_resource_id: event.physical_resource_id,
             resource_status_reason: event.resource_status_reason,
             resource_type: event
                 .resource_type
-                .expect("StackEvent without resource_type"),
-            stack_id: event.stack_id.expect("StackEvent without stack_id"),
-            stack_name: event.stack_name.expect("StackEvent without stack_name"),
+                .expected(DESCRIBE_STACK_EVENTS, "StackEvent without resource_type")?,
+            stack_id: event
+                .stack_id
+                .expected(DESCRIBE_STACK_EVENTS, "StackEvent without stack_id")?,
+            stack_name: event
+                .stack_name
+                .expected(DESCRIBE_STACK_EVENTS, "StackEvent without stack_name")?,
             stack_alias,
             timestamp: event
                 .timestamp
-                .expect("StackEvent without timestamp")
+                .expected(DESCRIBE_STACK_EVENTS, "StackEvent without timestamp")?
                 .to_chrono_utc()
-                .expect("invalid timestamp"),
+                .expected(DESCRIBE_STACK_EVENTS, "StackEvent with invalid timestamp")?,
+        };
+        let invalid_status = || {
+            UnexpectedResponse::new(
+                DESCRIBE_STACK_EVENTS,
+                format!("StackEvent with invalid resource_status {resource_status}"),
+            )
         };
         if is_stack {
-            Self::Stack {
+            Ok(Self::Stack {
                 resource_status: resource_status
                     .as_str()
                     .parse()
-                    .expect("invalid stack status"),
+                    .map_err(|_| invalid_status())?,
                 details,
-            }
+            })
         } else {
-            Self::Resource {
+            Ok(Self::Resource {
                 resource_status: resource_status
                     .as_str()
                     .parse()
-                    .expect("invalid resource status"),
+                    .map_err(|_| invalid_status())?,
                 details,
-            }
+            })
         }
     }
 }
 
 /// Event details from the `DescribeStackEvents` API that are common for stack and resource events.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StackEventDetails {
     /// The token passed to the operation that generated this event.
     ///
@@ -286,7 +306,7 @@ impl StackEventDetails {
 
     /// Get the success/failure message associated with the resource.
     #[must_use]
-    pub fn resource_status_reason(&self) -> StatusReason {
+    pub fn resource_status_reason(&self) -> StatusReason<'_> {
         StatusReason::new(self.resource_status_reason.as_deref())
     }
 
@@ -320,3 +340,26 @@ impl StackEventDetails {
         &self.timestamp
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_cloudformation::primitives::DateTime;
+
+    use super::StackEvent;
+
+    #[test]
+    fn from_sdk_missing_field() {
+        let event = aws_sdk_cloudformation::types::StackEvent::builder()
+            .event_id("event-1")
+            .logical_resource_id("MyBucket")
+            .resource_type("AWS::S3::Bucket")
+            .stack_id("arn:stack")
+            .stack_name("my-stack")
+            .timestamp(DateTime::from_secs(0))
+            .build();
+
+        let error = StackEvent::from_sdk(None, event).unwrap_err();
+        assert_eq!(error.api, "DescribeStackEvents");
+        assert_eq!(error.detail, "StackEvent without resource_status");
+    }
+}
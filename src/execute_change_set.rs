@@ -0,0 +1,225 @@
+use std::{future::Future, pin::Pin, task};
+
+use async_stream::try_stream;
+use futures_util::{Stream, TryFutureExt};
+
+use crate::{
+    apply_stack::{
+        describe_output, execute_change_set_internal, map_create_change_set_error, ApplyStackEvent,
+        DESCRIBE_STACKS,
+    },
+    change_set::{wait_for_change_set, Action, ChangeSet, ChangeSetType},
+    unexpected_response::Expected,
+    ApplyStackError, ApplyStackOutput, PollingConfig, StackEvent,
+};
+
+/// The input for the `execute_change_set` operation.
+///
+/// You can create an execute change set input via the [`new`](Self::new) associated function.
+/// Setters are also available to make constructing sparse inputs more ergonomic.
+///
+/// ```no_run
+/// use cloudformatious::{ApplyStackInput, ExecuteChangeSetInput, TemplateSource};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = aws_config::load_from_env().await;
+/// let client = cloudformatious::Client::new(&config);
+///
+/// // In one process...
+/// let input = ApplyStackInput::new("my-stack", TemplateSource::inline("{}"));
+/// let change_set = client.create_change_set(input).await?;
+///
+/// // ...and later, potentially in another.
+/// let input = ExecuteChangeSetInput::new(change_set.change_set_id).set_disable_rollback(true);
+/// let output = client.execute_change_set(input).await?;
+/// // ...
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::module_name_repetitions)]
+pub struct ExecuteChangeSetInput {
+    /// The ARN of the change set to execute.
+    ///
+    /// This is the [`change_set_id`](crate::change_set::ChangeSet::change_set_id) of a change set
+    /// returned by [`Client::create_change_set`](crate::Client::create_change_set).
+    pub change_set_id: String,
+
+    /// Whether or not to disable rolling back in the event of a failure.
+    ///
+    /// When rollback is disabled, resources that were created/updated before the failing operation
+    /// are preserved and the stack settles with a `*_FAILED` status. This may be helpful when
+    /// debugging failing stack operations.
+    pub disable_rollback: bool,
+}
+
+impl ExecuteChangeSetInput {
+    /// Construct an input for the given `change_set_id`.
+    pub fn new(change_set_id: impl Into<String>) -> Self {
+        Self {
+            change_set_id: change_set_id.into(),
+
+            disable_rollback: false,
+        }
+    }
+
+    /// Set the value for `disable_rollback`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_disable_rollback(mut self, disable_rollback: bool) -> Self {
+        self.disable_rollback = disable_rollback;
+        self
+    }
+}
+
+/// An ongoing `execute_change_set` operation.
+///
+/// This implements `Future`, which will simply wait for the operation to conclude. If you want to
+/// observe progress, see [`ExecuteChangeSet::events`].
+///
+/// The operation behaves like the second half of an [`ApplyStack`](crate::ApplyStack) operation.
+/// In particular, executing a change set that contained no changes is not an error, and will
+/// simply resolve to the current state of the stack.
+pub struct ExecuteChangeSet {
+    event_stream: Pin<Box<dyn Stream<Item = Result<ApplyStackEvent, ApplyStackError>> + Send>>,
+    output: Option<Result<ApplyStackOutput, ApplyStackError>>,
+}
+
+impl ExecuteChangeSet {
+    pub(crate) fn new(
+        client: aws_sdk_cloudformation::Client,
+        polling: PollingConfig,
+        input: ExecuteChangeSetInput,
+    ) -> Self {
+        let event_stream = try_stream! {
+            let change_set = match wait_for_change_set(&client, polling, input.change_set_id, false).await {
+                Ok(change_set) => change_set,
+                Err(error) => {
+                    let change_set = map_create_change_set_error(error, None)?;
+                    let output = describe_output(
+                        &client,
+                        change_set.stack_id,
+                        change_set.change_set_id,
+                    )
+                    .await?;
+                    yield ApplyStackEvent::Output(output);
+                    return;
+                }
+            };
+
+            let change_set_type = describe_change_set_type(&client, &change_set).await?;
+
+            for await event in execute_change_set_internal(
+                &client,
+                polling,
+                change_set.stack_id,
+                change_set.change_set_id,
+                change_set_type,
+                input.disable_rollback,
+            ) {
+                yield event?;
+            }
+        };
+        Self {
+            event_stream: Box::pin(event_stream),
+            output: None,
+        }
+    }
+
+    /// Get a `Stream` of `StackEvent`s.
+    pub fn events(&mut self) -> ExecuteChangeSetEvents<'_> {
+        ExecuteChangeSetEvents(self)
+    }
+
+    fn poll_next_internal(&mut self, ctx: &mut task::Context) -> task::Poll<Option<StackEvent>> {
+        loop {
+            match self.event_stream.as_mut().poll_next(ctx) {
+                task::Poll::Pending => return task::Poll::Pending,
+                task::Poll::Ready(None) => return task::Poll::Ready(None),
+                task::Poll::Ready(Some(Ok(ApplyStackEvent::ChangeSet(_)))) => {}
+                task::Poll::Ready(Some(Ok(ApplyStackEvent::Event(event)))) => {
+                    return task::Poll::Ready(Some(event))
+                }
+                task::Poll::Ready(Some(Ok(ApplyStackEvent::Output(output)))) => {
+                    self.output.replace(Ok(output));
+                }
+                task::Poll::Ready(Some(Err(error))) => {
+                    self.output.replace(Err(error));
+                }
+            }
+        }
+    }
+}
+
+impl Future for ExecuteChangeSet {
+    type Output = Result<ApplyStackOutput, ApplyStackError>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context) -> task::Poll<Self::Output> {
+        loop {
+            match self.poll_next_internal(ctx) {
+                task::Poll::Pending => return task::Poll::Pending,
+                task::Poll::Ready(None) => {
+                    return task::Poll::Ready(
+                        self.output
+                            .take()
+                            .expect("end of stream without err or output"),
+                    )
+                }
+                task::Poll::Ready(Some(_)) => {}
+            }
+        }
+    }
+}
+
+/// Return value of [`ExecuteChangeSet::events`].
+#[allow(clippy::module_name_repetitions)]
+pub struct ExecuteChangeSetEvents<'execute>(&'execute mut ExecuteChangeSet);
+
+impl Stream for ExecuteChangeSetEvents<'_> {
+    type Item = StackEvent;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut task::Context,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.0.poll_next_internal(ctx)
+    }
+}
+
+/// Determine the type of a change set from its changes and the status of its stack.
+///
+/// Import change sets can only contain imports, so those are recognisable from their changes.
+/// Otherwise, `CreateChangeSet` with a `CREATE` change set type leaves the new stack in
+/// `REVIEW_IN_PROGRESS` until it's executed, so any other status means the change set will update
+/// the stack.
+async fn describe_change_set_type(
+    client: &aws_sdk_cloudformation::Client,
+    change_set: &ChangeSet,
+) -> Result<ChangeSetType, ApplyStackError> {
+    if change_set
+        .changes
+        .iter()
+        .any(|change| matches!(change.action, Action::Import))
+    {
+        return Ok(ChangeSetType::Import);
+    }
+
+    let stack = client
+        .describe_stacks()
+        .stack_name(&change_set.stack_id)
+        .send()
+        .map_err(ApplyStackError::from_sdk_error)
+        .await?
+        .stacks
+        .and_then(|mut stacks| stacks.pop())
+        .expected(DESCRIBE_STACKS, "DescribeStacksOutput without stacks")?;
+
+    if stack.stack_status == Some(aws_sdk_cloudformation::types::StackStatus::ReviewInProgress) {
+        Ok(ChangeSetType::Create)
+    } else {
+        Ok(ChangeSetType::Update)
+    }
+}
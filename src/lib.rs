@@ -1,32 +1,87 @@
 #![warn(clippy::pedantic)]
 
 mod apply_stack;
+mod cancel_update_stack;
+mod continue_update_rollback;
 mod delete_stack;
+mod describe_change_set;
 mod event;
+mod execute_change_set;
+#[cfg(feature = "serde")]
+mod outputs;
+mod polling;
+#[cfg(feature = "serde")]
+mod serde_str;
 mod stack;
 mod status;
 mod tag;
+#[cfg(feature = "s3-staging")]
+mod template_bucket;
+mod unexpected_response;
+mod watch_stack;
 
 pub mod change_set;
+pub mod drift;
 pub mod status_reason;
+pub mod template;
 
 use aws_config::SdkConfig;
 
+use crate::apply_stack::S3Client;
+
 pub use apply_stack::{
-    ApplyStack, ApplyStackChangeSet, ApplyStackError, ApplyStackEvents, ApplyStackInput,
-    ApplyStackOutput, Capability, Parameter, StackOutput, TemplateSource,
+    ApplyStack, ApplyStackCancelHandle, ApplyStackChangeSet, ApplyStackError, ApplyStackEvents,
+    ApplyStackInput, ApplyStackOutput, Capability, Parameter, ResourceToImport, StackOutput,
+    StackParameter, TemplateSource,
+};
+pub use cancel_update_stack::{
+    CancelUpdateStack, CancelUpdateStackError, CancelUpdateStackEvents, CancelUpdateStackInput,
+};
+pub use continue_update_rollback::{
+    ContinueUpdateRollback, ContinueUpdateRollbackError, ContinueUpdateRollbackEvents,
+    ContinueUpdateRollbackInput,
 };
 pub use delete_stack::{DeleteStack, DeleteStackError, DeleteStackEvents, DeleteStackInput};
+pub use describe_change_set::{DescribeChangeSetError, DescribeChangeSetInput};
+pub use drift::{DetectStackDrift, DetectStackDriftError};
 pub use event::{StackEvent, StackEventDetails};
+pub use execute_change_set::{ExecuteChangeSet, ExecuteChangeSetEvents, ExecuteChangeSetInput};
+#[cfg(feature = "serde")]
+pub use outputs::{InvalidOutput, OutputsError};
+pub use polling::PollingConfig;
 pub use stack::{StackFailure, StackWarning};
 pub use status::{
     BlockedStackStatus, ChangeSetStatus, ResourceStatus, StackStatus, Status, StatusSentiment,
 };
 pub use tag::Tag;
+#[cfg(feature = "s3-staging")]
+pub use template_bucket::TemplateBucket;
+pub use watch_stack::{WatchStack, WatchStackError, WatchStackEvents};
 
 /// A client for performing cloudformatious operations.
+///
+/// The client is cheap to clone, and the operations it starts own a clone of the client, so they
+/// can be spawned onto a runtime or stored independently of the client that started them.
+///
+/// ```no_run
+/// use cloudformatious::{ApplyStackInput, TemplateSource};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let config = aws_config::load_from_env().await;
+/// let client = cloudformatious::Client::new(&config);
+///
+/// let input = ApplyStackInput::new("my-stack", TemplateSource::inline("{}"));
+/// let output = tokio::spawn(client.apply_stack(input)).await??;
+/// // ...
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
 pub struct Client {
     inner: aws_sdk_cloudformation::Client,
+    s3: S3Client,
+    polling: PollingConfig,
 }
 
 impl Client {
@@ -35,9 +90,35 @@ impl Client {
     pub fn new(config: &SdkConfig) -> Self {
         Self {
             inner: aws_sdk_cloudformation::Client::new(config),
+            s3: S3Client::new(config),
+            polling: PollingConfig::default(),
         }
     }
 
+    /// Set the [`PollingConfig`] used by operations started from this client.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_polling_config(mut self, polling: PollingConfig) -> Self {
+        self.polling = polling;
+        self
+    }
+
+    /// Set the S3 client used to stage templates in an
+    /// [`ApplyStackInput::template_bucket`].
+    ///
+    /// By default, an S3 client is constructed from the same configuration as the CloudFormation
+    /// client. This can be used to configure S3 separately, e.g. to use a different region or
+    /// endpoint.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[cfg(feature = "s3-staging")]
+    #[must_use]
+    pub fn set_s3_client(mut self, s3: aws_sdk_s3::Client) -> Self {
+        self.s3 = S3Client(s3);
+        self
+    }
+
     /// Apply a CloudFormation stack to an AWS environment.
     ///
     /// This is an idempotent operation that will create the indicated stack if it doesn't exist, or
@@ -51,7 +132,99 @@ impl Client {
     /// operation. See [`ApplyStack`] for more details.
     #[must_use]
     pub fn apply_stack(&self, input: ApplyStackInput) -> ApplyStack {
-        ApplyStack::new(&self.inner, input)
+        ApplyStack::new(self.inner.clone(), self.s3.clone(), self.polling, input)
+    }
+
+    /// Create a change set for applying a CloudFormation stack to an AWS environment.
+    ///
+    /// This is the first half of [`apply_stack`](Self::apply_stack), allowing changes to be
+    /// reviewed (e.g. by a human) before they're executed with
+    /// [`execute_change_set`](Self::execute_change_set). The returned change set is settled, and
+    /// only its [`change_set_id`](change_set::ChangeSet::change_set_id) is needed to execute it, so
+    /// planning and executing can happen in different processes.
+    ///
+    /// As with `apply_stack`, it is not an error for there to be no changes. In that case the
+    /// change set will have a [`Failed`](ChangeSetStatus::Failed) status and an
+    /// [`Unavailable`](change_set::ExecutionStatus::Unavailable) execution status, and executing it
+    /// will simply return the current state of the stack.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as [`ApplyStack::change_set`] are possible, most notably
+    /// [`ApplyStackError::Blocked`] if the stack is not in an updatable state and
+    /// [`ApplyStackError::CreateChangeSetFailed`] if the change set could not be created.
+    pub async fn create_change_set(
+        &self,
+        input: ApplyStackInput,
+    ) -> Result<change_set::ChangeSet, ApplyStackError> {
+        match apply_stack::create_change_set_internal(&self.inner, &self.s3, self.polling, input)
+            .await?
+        {
+            Ok(change_set::ChangeSetWithType { change_set, .. }) | Err(change_set) => {
+                Ok(change_set)
+            }
+        }
+    }
+
+    /// Describe an existing change set.
+    ///
+    /// Unlike [`create_change_set`](Self::create_change_set), this does not wait for the change
+    /// set to settle, so the change set may still be being created. All of the change set's
+    /// changes are described, along with any nested change sets once the change set has been
+    /// created.
+    ///
+    /// # Errors
+    ///
+    /// Any CloudFormation API errors are returned, e.g. if the change set does not exist. See
+    /// [`DescribeChangeSetError`] for more details.
+    pub async fn describe_change_set(
+        &self,
+        input: DescribeChangeSetInput,
+    ) -> Result<change_set::ChangeSet, DescribeChangeSetError> {
+        describe_change_set::describe_change_set_internal(&self.inner, self.polling, input).await
+    }
+
+    /// Execute a change set created by [`create_change_set`](Self::create_change_set).
+    ///
+    /// The returned `Future` can be used to simply wait for the operation to complete. You can also
+    /// use [`ExecuteChangeSet::events`] to get a `Stream` of the stack events that occur during the
+    /// operation. The outcome is the same as for [`apply_stack`](Self::apply_stack). See
+    /// [`ExecuteChangeSet`] for more details.
+    #[must_use]
+    pub fn execute_change_set(&self, input: ExecuteChangeSetInput) -> ExecuteChangeSet {
+        ExecuteChangeSet::new(self.inner.clone(), self.polling, input)
+    }
+
+    /// Cancel an in-progress update of a CloudFormation stack.
+    ///
+    /// The stack will be rolled back to its previous state. This is useful to cancel an update
+    /// that was started by another process (see [`ApplyStack::cancel`] to cancel an update started
+    /// by [`apply_stack`](Self::apply_stack)).
+    ///
+    /// The returned `Future` can be used to simply wait for the rollback to complete. You can also
+    /// use [`CancelUpdateStack::events`] to get a `Stream` of the stack events that occur during the
+    /// rollback. See [`CancelUpdateStack`] for more details.
+    #[must_use]
+    pub fn cancel_update_stack(&self, input: CancelUpdateStackInput) -> CancelUpdateStack {
+        CancelUpdateStack::new(self.inner.clone(), self.polling, input)
+    }
+
+    /// Continue rolling back a stack in the `UPDATE_ROLLBACK_FAILED` state.
+    ///
+    /// A stack whose update rollback failed is [blocked](BlockedStackStatus::UpdateRollbackFailed)
+    /// until the rollback is continued. Depending on the cause of the failure, it may be possible
+    /// to fix the error and simply try again, or it may be necessary to skip the resources that
+    /// failed to roll back (see [`StackFailure::resources_to_skip`]).
+    ///
+    /// The returned `Future` can be used to simply wait for the rollback to complete. You can also
+    /// use [`ContinueUpdateRollback::events`] to get a `Stream` of the stack events that occur
+    /// during the rollback. See [`ContinueUpdateRollback`] for more details.
+    #[must_use]
+    pub fn continue_update_rollback(
+        &self,
+        input: ContinueUpdateRollbackInput,
+    ) -> ContinueUpdateRollback {
+        ContinueUpdateRollback::new(self.inner.clone(), self.polling, input)
     }
 
     /// Delete a CloudFormation stack from an AWS environment.
@@ -64,7 +237,48 @@ impl Client {
     /// struct for more details.
     #[must_use]
     pub fn delete_stack(&self, input: DeleteStackInput) -> DeleteStack {
-        DeleteStack::new(&self.inner, input)
+        DeleteStack::new(self.inner.clone(), self.polling, input)
+    }
+
+    /// Detect drift between a CloudFormation stack's resources and its template.
+    ///
+    /// This initiates drift detection for the stack, waits for it to finish, and then describes
+    /// the drift of each of the stack's resources. See [`drift::StackDrift`] for details of the
+    /// result.
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = aws_config::load_from_env().await;
+    /// let client = cloudformatious::Client::new(&config);
+    ///
+    /// let drift = client.detect_stack_drift("my-stack").await?;
+    /// for resource_drift in drift.drifted_resources() {
+    ///     eprintln!(
+    ///         "{} has drifted: {}",
+    ///         resource_drift.logical_resource_id, resource_drift.stack_resource_drift_status,
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn detect_stack_drift(&self, stack_name: impl Into<String>) -> DetectStackDrift {
+        DetectStackDrift::new(self.inner.clone(), self.polling, stack_name.into())
+    }
+
+    /// Watch a CloudFormation stack operation that's already in progress.
+    ///
+    /// This is useful to resume following an operation after a restart, or to follow an operation
+    /// that was started by another process. Events are replayed from the start of the stack's
+    /// current operation, or its most recent operation if it has already settled.
+    ///
+    /// The returned `Future` can be used to simply wait for the operation to complete. You can also
+    /// use [`WatchStack::events`] to get a `Stream` of the stack events that occur during the
+    /// operation. See [`WatchStack`] for more details.
+    #[must_use]
+    pub fn watch_stack(&self, stack_name: impl Into<String>) -> WatchStack {
+        WatchStack::new(self.inner.clone(), self.polling, stack_name.into())
     }
 }
 
@@ -79,3 +293,41 @@ mod test_readme {
 
     external_doc_test!(include_str!("../README.md"));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ApplyStack, ApplyStackError, CancelUpdateStack, CancelUpdateStackError, Client,
+        ContinueUpdateRollback, ContinueUpdateRollbackError, DeleteStack, DeleteStackError,
+        DescribeChangeSetError, DetectStackDrift, DetectStackDriftError, ExecuteChangeSet,
+        WatchStack, WatchStackError,
+    };
+
+    fn assert_send_static<T: Send + 'static>() {}
+
+    fn assert_send_sync_static<T: Send + Sync + 'static>() {}
+
+    #[test]
+    fn operations_are_send_static() {
+        assert_send_sync_static::<Client>();
+        assert_send_static::<ApplyStack>();
+        assert_send_static::<CancelUpdateStack>();
+        assert_send_static::<ContinueUpdateRollback>();
+        assert_send_static::<DeleteStack>();
+        assert_send_static::<DetectStackDrift>();
+        assert_send_static::<ExecuteChangeSet>();
+        assert_send_static::<WatchStack>();
+    }
+
+    #[test]
+    fn errors_are_send_sync() {
+        assert_send_sync_static::<ApplyStackError>();
+        assert_send_sync_static::<CancelUpdateStackError>();
+        assert_send_sync_static::<ContinueUpdateRollbackError>();
+        assert_send_sync_static::<DeleteStackError>();
+        assert_send_sync_static::<DescribeChangeSetError>();
+        assert_send_sync_static::<DetectStackDriftError>();
+        assert_send_sync_static::<WatchStackError>();
+        assert_send_sync_static::<crate::template::ParseError>();
+    }
+}
@@ -0,0 +1,526 @@
+//! Deserialization of stack outputs into user-defined types.
+
+use std::{collections::HashSet, fmt};
+
+use serde::{
+    de::{self, value::StrDeserializer, DeserializeOwned, IntoDeserializer},
+    forward_to_deserialize_any,
+};
+
+use crate::ApplyStackOutput;
+
+impl ApplyStackOutput {
+    /// Deserialize the stack's outputs into `T`.
+    ///
+    /// Output keys are mapped to the fields of `T` (so `#[serde(rename = "...")]` can be used to
+    /// map output keys that aren't valid field names). Output values are always strings, so they
+    /// are parsed into the field types where necessary: numbers and `bool`s are parsed with
+    /// `FromStr`, unit enum variants are matched by name, and sequences (e.g. `Vec<String>`) are
+    /// split on commas, matching the lists produced by `Fn::Join`. Outputs that aren't fields of
+    /// `T` are ignored, and `Option` fields are `None` if the output is missing.
+    ///
+    /// This requires the `serde` feature.
+    ///
+    /// ```
+    /// # fn example(output: cloudformatious::ApplyStackOutput) -> Result<(), Box<dyn std::error::Error>> {
+    /// #[derive(serde::Deserialize)]
+    /// #[serde(rename_all = "PascalCase")]
+    /// struct Outputs {
+    ///     bucket_name: String,
+    ///     port: u16,
+    ///     subnet_ids: Vec<String>,
+    ///     description: Option<String>,
+    /// }
+    ///
+    /// let outputs: Outputs = output.outputs_as()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An [`OutputsError`] is returned if the outputs can't be deserialized into `T`. This lists
+    /// every output that couldn't be parsed, along with the missing outputs. Note that fields
+    /// with `#[serde(default)]` may be listed as missing, if they follow a required field that is
+    /// missing.
+    pub fn outputs_as<T: DeserializeOwned>(&self) -> Result<T, OutputsError> {
+        let mut skip = HashSet::new();
+        let mut absent = Vec::new();
+        let mut error = OutputsError {
+            missing: Vec::new(),
+            invalid: Vec::new(),
+            other: None,
+        };
+
+        // Deserialization stops at the first error, so we try again (skipping the problematic
+        // output) until we've found every problem.
+        loop {
+            let deserializer = OutputsDeserializer {
+                output: self,
+                skip: &skip,
+            };
+            match T::deserialize(deserializer) {
+                Ok(outputs) if error.invalid.is_empty() => return Ok(outputs),
+                Ok(_) => return Err(error),
+                Err(Error::Absent(key)) => {
+                    skip.insert(key.clone());
+                    absent.push(key);
+                }
+                Err(Error::Invalid(invalid)) => {
+                    skip.insert(invalid.key.clone());
+                    error.invalid.push(invalid);
+                }
+                Err(Error::Missing(key)) => {
+                    // Fields are checked in order, so any absent fields before the first missing
+                    // one must have defaults. Those after it may be required or have defaults,
+                    // but we can't tell which.
+                    if let Some(index) = absent.iter().position(|absent| *absent == key) {
+                        error.missing = absent.split_off(index);
+                    } else if !error.invalid.iter().any(|invalid| invalid.key == key) {
+                        error.missing.push(key);
+                    }
+                    return Err(error);
+                }
+                Err(Error::Custom(message)) => {
+                    error.other = Some(message);
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+/// Errors from [`ApplyStackOutput::outputs_as`].
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct OutputsError {
+    /// The keys of required outputs that the stack does not have.
+    pub missing: Vec<String>,
+
+    /// Outputs whose values could not be parsed.
+    pub invalid: Vec<InvalidOutput>,
+
+    /// Any other error, e.g. if the target type is not a struct or map.
+    pub other: Option<String>,
+}
+
+impl fmt::Display for OutputsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid stack outputs")?;
+        let mut separator = ": ";
+        if !self.missing.is_empty() {
+            write!(f, "{separator}missing {}", self.missing.join(", "))?;
+            separator = "; ";
+        }
+        for invalid in &self.invalid {
+            write!(f, "{separator}{invalid}")?;
+            separator = "; ";
+        }
+        if let Some(other) = &self.other {
+            write!(f, "{separator}{other}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for OutputsError {}
+
+/// A stack output whose value could not be parsed.
+#[derive(Debug)]
+pub struct InvalidOutput {
+    /// The key of the output.
+    pub key: String,
+
+    /// The value of the output.
+    pub value: String,
+
+    /// A description of why the value could not be parsed.
+    pub message: String,
+}
+
+impl fmt::Display for InvalidOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid {} ({:?}): {}",
+            self.key, self.value, self.message
+        )
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    /// A placeholder for an absent output was deserialized as something other than an `Option`.
+    Absent(String),
+    Missing(String),
+    Invalid(InvalidOutput),
+    Custom(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absent(key) | Self::Missing(key) => write!(f, "missing {key}"),
+            Self::Invalid(invalid) => write!(f, "{invalid}"),
+            Self::Custom(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Self::Custom(message.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::Missing(field.to_string())
+    }
+}
+
+/// Deserializes the outputs as a map, or as a struct with placeholders for missing fields.
+struct OutputsDeserializer<'a> {
+    output: &'a ApplyStackOutput,
+    skip: &'a HashSet<String>,
+}
+
+impl<'a> OutputsDeserializer<'a> {
+    fn entries(&self) -> Vec<(&'a str, Value<'a>)> {
+        let skip = self.skip;
+        self.output
+            .outputs
+            .iter()
+            .filter(|output| !skip.contains(&output.key))
+            .map(|output| (output.key.as_str(), Value::Output(&output.value)))
+            .collect()
+    }
+}
+
+impl<'de> de::Deserializer<'de> for OutputsDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(Entries {
+            entries: self.entries().into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        // Missing fields are given placeholders, so that we can find all the missing outputs
+        // rather than only the first.
+        let placeholders = fields
+            .iter()
+            .filter(|field| {
+                !self.skip.contains(**field)
+                    && !self
+                        .output
+                        .outputs
+                        .iter()
+                        .any(|output| output.key == **field)
+            })
+            .map(|field| (*field, Value::Missing(field)));
+        let mut entries = self.entries();
+        entries.extend(placeholders);
+        visitor.visit_map(Entries {
+            entries: entries.into_iter(),
+            value: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+struct Entries<'a, I> {
+    entries: I,
+    value: Option<(&'a str, Value<'a>)>,
+}
+
+impl<'de, 'a, I> de::MapAccess<'de> for Entries<'a, I>
+where
+    I: Iterator<Item = (&'a str, Value<'a>)>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some((key, value));
+        let key: StrDeserializer<Error> = key.into_deserializer();
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (key, value) = self.value.take().expect("value requested before key");
+        match value {
+            Value::Output(value) => seed.deserialize(OutputValue(value)).map_err(|error| {
+                Error::Invalid(InvalidOutput {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    message: error.to_string(),
+                })
+            }),
+            Value::Missing(_) => seed.deserialize(value),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Value<'a> {
+    /// The value of an output.
+    Output(&'a str),
+
+    /// A placeholder for a missing output, which is only valid for `Option` fields.
+    Missing(&'a str),
+}
+
+impl<'de> de::Deserializer<'de> for Value<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Self::Output(_) => unreachable!("outputs are deserialized with OutputValue"),
+            Self::Missing(key) => Err(Error::Absent(key.to_string())),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_none()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+/// Deserializes an output value, parsing it as necessary.
+struct OutputValue<'a>(&'a str);
+
+macro_rules! deserialize_parse {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(self.0.parse().map_err(de::Error::custom)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for OutputValue<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str(self.0)
+    }
+
+    deserialize_parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items = if self.0.is_empty() {
+            Vec::new()
+        } else {
+            self.0.split(',').map(OutputValue).collect()
+        };
+        visitor.visit_seq(de::value::SeqDeserializer::new(items.into_iter()))
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let value: StrDeserializer<Error> = self.0.into_deserializer();
+        visitor.visit_enum(value)
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, Error> for OutputValue<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::{ApplyStackOutput, StackOutput, StackStatus};
+
+    fn output(outputs: &[(&str, &str)]) -> ApplyStackOutput {
+        ApplyStackOutput {
+            change_set_id: "change-set".to_string(),
+            creation_time: Utc::now(),
+            description: None,
+            last_updated_time: None,
+            outputs: outputs
+                .iter()
+                .map(|(key, value)| StackOutput {
+                    description: None,
+                    export_name: None,
+                    key: (*key).to_string(),
+                    value: (*value).to_string(),
+                })
+                .collect(),
+            parameters: Vec::new(),
+            stack_id: "stack".to_string(),
+            stack_name: "stack".to_string(),
+            stack_status: StackStatus::CreateComplete,
+            tags: Vec::new(),
+        }
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Mode {
+        Active,
+        Passive,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Outputs {
+        bucket_name: String,
+        port: u16,
+        enabled: bool,
+        subnet_ids: Vec<String>,
+        mode: Mode,
+        description: Option<String>,
+        #[serde(rename = "Optional.Port")]
+        optional_port: Option<u16>,
+    }
+
+    #[test]
+    fn outputs_as() {
+        let output = output(&[
+            ("BucketName", "my-bucket"),
+            ("Port", "8080"),
+            ("Enabled", "true"),
+            ("SubnetIds", "subnet-1,subnet-2"),
+            ("Mode", "Active"),
+            ("Optional.Port", "443"),
+            ("Unrelated", "ignored"),
+        ]);
+        assert_eq!(
+            output.outputs_as::<Outputs>().unwrap(),
+            Outputs {
+                bucket_name: "my-bucket".to_string(),
+                port: 8080,
+                enabled: true,
+                subnet_ids: vec!["subnet-1".to_string(), "subnet-2".to_string()],
+                mode: Mode::Active,
+                description: None,
+                optional_port: Some(443),
+            }
+        );
+        assert_eq!(output.outputs_map()["Port"], "8080");
+    }
+
+    #[test]
+    fn outputs_as_errors() {
+        let output = output(&[
+            ("Port", "eighty"),
+            ("SubnetIds", ""),
+            ("Mode", "Unknown"),
+            ("Optional.Port", "-1"),
+        ]);
+        let error = output.outputs_as::<Outputs>().unwrap_err();
+        assert_eq!(error.missing, vec!["BucketName", "Enabled"]);
+        assert_eq!(
+            error
+                .invalid
+                .iter()
+                .map(|invalid| invalid.key.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Port", "Mode", "Optional.Port"]
+        );
+        assert!(error.other.is_none());
+        assert_eq!(
+            error.to_string(),
+            concat!(
+                "invalid stack outputs: missing BucketName, Enabled; ",
+                r#"invalid Port ("eighty"): invalid digit found in string; "#,
+                r#"invalid Mode ("Unknown"): unknown variant `Unknown`, expected `Active` or `Passive`; "#,
+                r#"invalid Optional.Port ("-1"): invalid digit found in string"#,
+            )
+        );
+    }
+
+    #[test]
+    fn outputs_as_defaults() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Outputs {
+            #[serde(default)]
+            port: u16,
+            name: String,
+        }
+
+        let output = output(&[("name", "web")]);
+        assert_eq!(
+            output.outputs_as::<Outputs>().unwrap(),
+            Outputs {
+                port: 0,
+                name: "web".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn outputs_as_map() {
+        let output = output(&[("A", "1"), ("B", "2")]);
+        let outputs: std::collections::BTreeMap<String, u8> = output.outputs_as().unwrap();
+        assert_eq!(
+            outputs.into_iter().collect::<Vec<_>>(),
+            vec![("A".to_string(), 1), ("B".to_string(), 2),]
+        );
+    }
+}
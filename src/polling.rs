@@ -0,0 +1,245 @@
+//! Configuration for how operations poll CloudFormation for progress.
+
+use std::{future::Future, time::Duration};
+
+use aws_sdk_cloudformation::error::{ProvideErrorMetadata, SdkError};
+
+/// Error codes that indicate a request was throttled.
+const THROTTLING_ERROR_CODES: &[&str] = &[
+    "Throttling",
+    "ThrottlingException",
+    "TooManyRequestsException",
+    "RequestLimitExceeded",
+];
+
+/// Configuration for how operations poll CloudFormation for progress.
+///
+/// Long-running operations poll `DescribeStackEvents`, `DescribeChangeSet`, and
+/// `DescribeStackDriftDetectionStatus` to follow their progress. When many operations run
+/// concurrently in the same account these APIs can be throttled, in which case the request is
+/// retried after a jittered exponential backoff rather than failing the operation.
+///
+/// The default configuration polls stack events and drift detection every 5 seconds and change sets
+/// every second, and retries throttled requests up to 8 times with a backoff of at most 1 minute.
+///
+/// The intervals and `max_backoff` must be non-zero, since polling can't be done without waiting
+/// between requests. The setters panic if given a zero duration.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use cloudformatious::PollingConfig;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let config = aws_config::load_from_env().await;
+/// let client = cloudformatious::Client::new(&config).set_polling_config(
+///     PollingConfig::default()
+///         .set_stack_events_interval(Duration::from_secs(10))
+///         .set_max_retries(20),
+/// );
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct PollingConfig {
+    /// How often to poll for new stack events.
+    pub stack_events_interval: Duration,
+
+    /// How often to poll the status of a change set while it's being created.
+    pub change_set_interval: Duration,
+
+    /// How often to poll the status of stack drift detection.
+    pub drift_detection_interval: Duration,
+
+    /// The maximum delay between retries of a throttled request.
+    ///
+    /// The first retry waits for roughly the relevant polling interval, with the delay doubling for
+    /// each subsequent retry until it reaches this maximum. A random jitter of up to half the delay
+    /// is subtracted from each delay, so that concurrent operations don't retry in lock-step.
+    pub max_backoff: Duration,
+
+    /// The maximum number of consecutive retries of a throttled request.
+    ///
+    /// If a request is still throttled after this many retries, the throttling error is returned
+    /// from the operation.
+    pub max_retries: u32,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        Self {
+            stack_events_interval: Duration::from_secs(5),
+            change_set_interval: Duration::from_secs(1),
+            drift_detection_interval: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(60),
+            max_retries: 8,
+        }
+    }
+}
+
+impl PollingConfig {
+    /// Set the value for `stack_events_interval`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stack_events_interval` is zero.
+    #[must_use]
+    pub fn set_stack_events_interval(mut self, stack_events_interval: Duration) -> Self {
+        assert!(
+            !stack_events_interval.is_zero(),
+            "`stack_events_interval` must be non-zero"
+        );
+        self.stack_events_interval = stack_events_interval;
+        self
+    }
+
+    /// Set the value for `change_set_interval`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `change_set_interval` is zero.
+    #[must_use]
+    pub fn set_change_set_interval(mut self, change_set_interval: Duration) -> Self {
+        assert!(
+            !change_set_interval.is_zero(),
+            "`change_set_interval` must be non-zero"
+        );
+        self.change_set_interval = change_set_interval;
+        self
+    }
+
+    /// Set the value for `drift_detection_interval`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `drift_detection_interval` is zero.
+    #[must_use]
+    pub fn set_drift_detection_interval(mut self, drift_detection_interval: Duration) -> Self {
+        assert!(
+            !drift_detection_interval.is_zero(),
+            "`drift_detection_interval` must be non-zero"
+        );
+        self.drift_detection_interval = drift_detection_interval;
+        self
+    }
+
+    /// Set the value for `max_backoff`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_backoff` is zero.
+    #[must_use]
+    pub fn set_max_backoff(mut self, max_backoff: Duration) -> Self {
+        assert!(!max_backoff.is_zero(), "`max_backoff` must be non-zero");
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set the value for `max_retries`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Send a request, retrying with backoff if it's throttled.
+    ///
+    /// `interval` is the polling interval of the caller, which is used as the initial backoff.
+    pub(crate) async fn retry_throttled<T, E, F, Fut>(
+        &self,
+        interval: Duration,
+        mut send: F,
+    ) -> Result<T, SdkError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, SdkError<E>>>,
+        E: ProvideErrorMetadata,
+    {
+        let mut retries = 0;
+        loop {
+            match send().await {
+                Err(error) if is_throttling(&error) && retries < self.max_retries => {
+                    tokio::time::sleep(self.backoff(interval, retries)).await;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// The delay before the given retry (starting from 0).
+    fn backoff(&self, interval: Duration, retry: u32) -> Duration {
+        let delay = interval
+            .checked_mul(2_u32.saturating_pow(retry))
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff));
+        let jitter = delay.mul_f64(fastrand::f64() / 2.0);
+        delay.saturating_sub(jitter)
+    }
+}
+
+fn is_throttling<E: ProvideErrorMetadata>(error: &SdkError<E>) -> bool {
+    error
+        .code()
+        .is_some_and(|code| THROTTLING_ERROR_CODES.contains(&code))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::PollingConfig;
+
+    #[test]
+    #[should_panic(expected = "`stack_events_interval` must be non-zero")]
+    fn zero_stack_events_interval() {
+        let _ = PollingConfig::default().set_stack_events_interval(Duration::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "`change_set_interval` must be non-zero")]
+    fn zero_change_set_interval() {
+        let _ = PollingConfig::default().set_change_set_interval(Duration::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "`drift_detection_interval` must be non-zero")]
+    fn zero_drift_detection_interval() {
+        let _ = PollingConfig::default().set_drift_detection_interval(Duration::ZERO);
+    }
+
+    #[test]
+    #[should_panic(expected = "`max_backoff` must be non-zero")]
+    fn zero_max_backoff() {
+        let _ = PollingConfig::default().set_max_backoff(Duration::ZERO);
+    }
+
+    #[test]
+    fn backoff() {
+        let config = PollingConfig::default().set_max_backoff(Duration::from_secs(30));
+        let interval = Duration::from_secs(5);
+
+        for (retry, max) in [(0, 5), (1, 10), (2, 20), (3, 30), (4, 30), (100, 30)] {
+            let max = Duration::from_secs(max);
+            let delay = config.backoff(interval, retry);
+            assert!(delay <= max, "retry {}: {:?} > {:?}", retry, delay, max);
+            assert!(
+                delay >= max / 2,
+                "retry {}: {:?} < {:?}",
+                retry,
+                delay,
+                max / 2
+            );
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! `serde` implementations for enums that are represented as strings.
+//!
+//! These enums correspond to CloudFormation's own enumerations, so they are serialized using the
+//! same strings as the CloudFormation API (e.g. `CREATE_COMPLETE`), via their `Display` and
+//! `FromStr` implementations. This also means that `Unknown` variants round-trip.
+
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+fn serialize<T: fmt::Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    value
+        .parse()
+        .map_err(|error| de::Error::custom(format_args!("invalid value {value:?}: {error}")))
+}
+
+macro_rules! serde_str {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize(self, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+serde_str!(
+    crate::BlockedStackStatus,
+    crate::Capability,
+    crate::ChangeSetStatus,
+    crate::ResourceStatus,
+    crate::StackStatus,
+    crate::change_set::AttributeChangeType,
+    crate::change_set::Evaluation,
+    crate::change_set::ExecutionStatus,
+    crate::change_set::ModifyScope,
+    crate::change_set::PolicyViolationKind,
+    crate::change_set::Replacement,
+    crate::change_set::RequiresRecreation,
+    crate::drift::DifferenceType,
+    crate::drift::ResourceDriftStatus,
+    crate::drift::StackDriftStatus,
+);
+
+#[cfg(test)]
+mod tests {
+    use enumset::EnumSet;
+
+    use crate::{
+        change_set::{ModifyScope, Replacement},
+        Capability, StackStatus,
+    };
+
+    #[test]
+    fn statuses_use_cloudformation_strings() {
+        assert_eq!(
+            serde_json::to_string(&StackStatus::UpdateRollbackComplete).unwrap(),
+            r#""UPDATE_ROLLBACK_COMPLETE""#
+        );
+        assert_eq!(
+            serde_json::from_str::<StackStatus>(r#""UPDATE_ROLLBACK_COMPLETE""#).unwrap(),
+            StackStatus::UpdateRollbackComplete
+        );
+        assert_eq!(
+            serde_json::to_string(&Capability::NamedIam).unwrap(),
+            r#""CAPABILITY_NAMED_IAM""#
+        );
+        assert_eq!(
+            serde_json::to_string(&Replacement::Conditional).unwrap(),
+            r#""Conditional""#
+        );
+    }
+
+    #[test]
+    fn unknown_statuses_round_trip() {
+        let status: StackStatus = serde_json::from_str(r#""SOME_NEW_STATUS""#).unwrap();
+        assert_eq!(status, StackStatus::Unknown("SOME_NEW_STATUS".to_string()));
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#""SOME_NEW_STATUS""#
+        );
+    }
+
+    #[test]
+    fn modify_scopes_are_lists() {
+        let scope = EnumSet::only(ModifyScope::Properties) | EnumSet::only(ModifyScope::Tags);
+        let json = serde_json::to_string(&scope).unwrap();
+        assert_eq!(json, r#"["Properties","Tags"]"#);
+        assert_eq!(
+            serde_json::from_str::<EnumSet<ModifyScope>>(&json).unwrap(),
+            scope
+        );
+    }
+}
@@ -1,18 +1,29 @@
-use std::{collections::BTreeMap, fmt, iter, pin::Pin, task, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, iter,
+    pin::Pin,
+    task,
+};
 
 use async_stream::try_stream;
 use aws_sdk_cloudformation::{
-    error::SdkError, operation::describe_stack_events::DescribeStackEventsError,
+    error::SdkError,
+    operation::{
+        describe_stack_events::DescribeStackEventsError, describe_stacks::DescribeStacksError,
+    },
 };
 use aws_smithy_types_convert::date_time::DateTimeExt;
 use chrono::{DateTime, Utc};
 use futures_util::{stream, Stream, TryStreamExt};
 
 use crate::{
-    status_reason::StatusReason, ResourceStatus, StackEvent, StackEventDetails, StackStatus, Status,
+    apply_stack::DESCRIBE_STACKS,
+    status_reason::StatusReason,
+    unexpected_response::{Expected, UnexpectedResponse},
+    PollingConfig, ResourceStatus, StackEvent, StackEventDetails, StackStatus, Status,
 };
 
-const POLL_INTERVAL_STACK_EVENT: Duration = Duration::from_secs(5);
+pub(crate) const DESCRIBE_STACK_EVENTS: &str = "DescribeStackEvents";
 
 /// Describes a failed stack operation.
 ///
@@ -21,6 +32,7 @@ const POLL_INTERVAL_STACK_EVENT: Duration = Duration::from_secs(5);
 /// in the stack). [`stack_status_reason`](Self::stack_status_reason) and
 /// [`StackEventDetails::resource_status_reason`] may be useful for this purpose.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::module_name_repetitions)]
 pub struct StackFailure {
     /// The ID of the stack.
@@ -43,14 +55,65 @@ pub struct StackFailure {
     /// matching [`StackEvent`] variants (when it would be a logical error for them to be
     /// anything other than the `Resource` variant).
     pub resource_events: Vec<(ResourceStatus, StackEventDetails)>,
+
+    /// When the stack started rolling back an update, if it did.
+    ///
+    /// This is used by [`resources_to_skip`](Self::resources_to_skip) to tell failures during the
+    /// rollback apart from the failures that caused it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rollback_started_at: Option<DateTime<Utc>>,
 }
 
 impl StackFailure {
     /// The *first* reason the stack moved into a failing state.
     #[must_use]
-    pub fn stack_status_reason(&self) -> StatusReason {
+    pub fn stack_status_reason(&self) -> StatusReason<'_> {
         StatusReason::new(Some(&self.stack_status_reason))
     }
+
+    /// The resources that failed to roll back, in the format expected by
+    /// [`ContinueUpdateRollbackInput::resources_to_skip`].
+    ///
+    /// This is empty unless the stack settled in `UPDATE_ROLLBACK_FAILED`. Otherwise, it contains
+    /// the logical IDs of resources that were left in `UPDATE_FAILED` while rolling back. Resources
+    /// in nested stacks are identified as `NestedStackName.ResourceLogicalID`. Nested stack
+    /// resources themselves are never included, since CloudFormation won't skip them (the failed
+    /// resources within them are included instead).
+    ///
+    /// Skipping resources leaves them inconsistent with the stack's template, so the result
+    /// should be reviewed before it's used.
+    ///
+    /// [`ContinueUpdateRollbackInput::resources_to_skip`]: crate::ContinueUpdateRollbackInput::resources_to_skip
+    #[must_use]
+    pub fn resources_to_skip(&self) -> Vec<String> {
+        let Some(rollback_started_at) = self.rollback_started_at else {
+            return Vec::new();
+        };
+        if self.stack_status != StackStatus::UpdateRollbackFailed {
+            return Vec::new();
+        }
+
+        let mut resources_to_skip = Vec::<String>::new();
+        for (resource_status, details) in &self.resource_events {
+            if *resource_status != ResourceStatus::UpdateFailed
+                || details.timestamp < rollback_started_at
+                || details.resource_type == "AWS::CloudFormation::Stack"
+            {
+                continue;
+            }
+            let resource_id = match details.stack_alias.as_deref() {
+                Some(stack_alias) => {
+                    let nested_stack_name = stack_alias.rsplit('/').next().unwrap_or(stack_alias);
+                    format!("{nested_stack_name}.{}", details.logical_resource_id)
+                }
+                None => details.logical_resource_id.clone(),
+            };
+            if !resources_to_skip.contains(&resource_id) {
+                resources_to_skip.push(resource_id);
+            }
+        }
+        resources_to_skip
+    }
 }
 
 impl fmt::Display for StackFailure {
@@ -89,6 +152,7 @@ impl fmt::Display for StackFailure {
 /// letting this pass silently, or relying on carefully interrogating `StackEvent`s, the
 /// operation returns an error.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::module_name_repetitions)]
 pub struct StackWarning {
     /// The ID of the stack.
@@ -131,6 +195,47 @@ pub(crate) enum StackOperationError {
     Warning(StackWarning),
 }
 
+/// Errors that can occur while following the events of a stack operation.
+pub(crate) enum StackEventsError {
+    Api(Box<SdkError<DescribeStackEventsError>>),
+    UnexpectedResponse(UnexpectedResponse),
+}
+
+impl From<SdkError<DescribeStackEventsError>> for StackEventsError {
+    fn from(error: SdkError<DescribeStackEventsError>) -> Self {
+        Self::Api(Box::new(error))
+    }
+}
+
+impl From<UnexpectedResponse> for StackEventsError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse(error)
+    }
+}
+
+/// Get the ID of the stack with the given name.
+///
+/// Operations follow the stack by ID, since a stack's name can be reused once it's deleted.
+pub(crate) async fn describe_stack_id<E>(
+    client: &aws_sdk_cloudformation::Client,
+    stack_name: &str,
+) -> Result<String, E>
+where
+    E: From<SdkError<DescribeStacksError>> + From<UnexpectedResponse>,
+{
+    let stack = client
+        .describe_stacks()
+        .stack_name(stack_name)
+        .send()
+        .await?
+        .stacks
+        .and_then(|mut stacks| stacks.pop())
+        .expected(DESCRIBE_STACKS, "DescribeStacksOutput without stacks")?;
+    Ok(stack
+        .stack_id
+        .expected(DESCRIBE_STACKS, "Stack without stack_id")?)
+}
+
 pub(crate) enum StackOperationStatus {
     InProgress,
     Complete,
@@ -138,73 +243,88 @@ pub(crate) enum StackOperationStatus {
     Unexpected,
 }
 
+impl StackOperationStatus {
+    /// A conservative operation status for a stack status we don't recognise.
+    ///
+    /// Unsettled statuses are assumed to be in progress, and settled statuses are only assumed to
+    /// have failed if they look negative. We never assume an unknown status is complete.
+    pub(crate) fn unknown(stack_status: &StackStatus) -> Self {
+        if !stack_status.is_settled() {
+            Self::InProgress
+        } else if stack_status.sentiment().is_negative() {
+            Self::Failed
+        } else {
+            Self::Unexpected
+        }
+    }
+}
+
 pub(crate) struct StackOperation<'client, F> {
     stack_id: String,
     check_progress: F,
-    events: Pin<
-        Box<dyn Stream<Item = Result<StackEvent, SdkError<DescribeStackEventsError>>> + 'client>,
-    >,
+    events: Pin<Box<dyn Stream<Item = Result<StackEvent, StackEventsError>> + Send + 'client>>,
     stack_error_status: Option<StackStatus>,
     stack_error_status_reason: Option<String>,
     resource_error_events: Vec<(ResourceStatus, StackEventDetails)>,
+    rollback_started_at: Option<DateTime<Utc>>,
 }
 
 impl<'client, F> StackOperation<'client, F>
 where
-    F: Fn(StackStatus) -> StackOperationStatus + Unpin,
+    F: Fn(&StackStatus) -> StackOperationStatus + Unpin,
 {
     pub(crate) fn new(
         client: &'client aws_sdk_cloudformation::Client,
+        polling: PollingConfig,
         stack_id: String,
         started_at: DateTime<Utc>,
         check_progress: F,
     ) -> Self {
         let root_stack_id = stack_id.clone();
         let events = try_stream! {
-            let mut interval = tokio::time::interval(POLL_INTERVAL_STACK_EVENT);
+            let mut interval = tokio::time::interval(polling.stack_events_interval);
             let mut since = started_at;
+            let mut seen_at_since = HashSet::<String>::new();
             let mut nested_stacks = BTreeMap::<String, String>::new();
 
             loop {
                 interval.tick().await;
 
                 let stack_ids = iter::once(root_stack_id.clone()).chain(nested_stacks.keys().cloned());
-                let stack_events = stack_ids.into_iter().map(|stack_id| stream::once(Box::pin(async {
-                    let stack_events: Vec<_> = client
-                        .describe_stack_events()
-                        .stack_name(stack_id)
-                        .send()
-                        .await?
-                        .stack_events
-                        .expect("DescribeStackEventsOutput without stack_events")
-                        .into_iter()
-                        .take_while(|event| {
-                            event
-                                .timestamp
-                                .expect("StackEvent without timestamp")
-                                .to_chrono_utc()
-                                .expect("invalid timestamp")
-                                > since
-                        })
-                        .map(|event| {
-                            let stack_alias = event.stack_id().and_then(|stack_id| nested_stacks.get(stack_id)).cloned();
-                            StackEvent::from_sdk(stack_alias, event)
-                        })
-                        .filter(|event| {
-                            match event {
-                                StackEvent::Stack { details, .. } => details.stack_id() == root_stack_id,
-                                StackEvent::Resource{ .. } => true,
-                            }
-                        })
-                        .collect();
-                    Ok::<_, SdkError<DescribeStackEventsError>>(stack_events)
-                })));
+                let stack_events = stack_ids.into_iter().map(|stack_id| {
+                    stream::once(Box::pin(describe_stack_events_since(client, &polling, stack_id, since, &seen_at_since)))
+                });
                 let stack_events: Vec<_> = stream::select_all(stack_events).try_collect().await?;
-                let stack_events: Vec<_> = stack_events.into_iter().flatten().collect();
+                let stack_events = stack_events
+                    .into_iter()
+                    .flatten()
+                    .map(|event| {
+                        let stack_alias = event.stack_id().and_then(|stack_id| nested_stacks.get(stack_id)).cloned();
+                        StackEvent::from_sdk(stack_alias, event)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let stack_events: Vec<_> = stack_events
+                    .into_iter()
+                    .filter(|event| {
+                        match event {
+                            StackEvent::Stack { details, .. } => details.stack_id() == root_stack_id,
+                            StackEvent::Resource{ .. } => true,
+                        }
+                    })
+                    .collect();
 
-                if let Some(stack_event) = stack_events.first() {
-                    since = *stack_event.timestamp();
+                if let Some(latest) = stack_events.iter().map(StackEvent::timestamp).max() {
+                    if *latest > since {
+                        since = *latest;
+                        seen_at_since.clear();
+                    }
                 }
+                seen_at_since.extend(
+                    stack_events
+                        .iter()
+                        .filter(|stack_event| *stack_event.timestamp() == since)
+                        .map(|stack_event| stack_event.event_id().to_string()),
+                );
 
                 for stack_event in stack_events.into_iter().rev() {
                     let is_terminal = stack_event.is_terminal();
@@ -244,6 +364,7 @@ where
             stack_error_status: None,
             stack_error_status_reason: None,
             resource_error_events: Vec::new(),
+            rollback_started_at: None,
         }
     }
 
@@ -254,8 +375,9 @@ where
                 stack_status,
                 stack_status_reason: self
                     .stack_error_status_reason
-                    .expect("stack op failed with no reasons"),
+                    .unwrap_or_else(|| "no reason reported".to_string()),
                 resource_events: self.resource_error_events,
+                rollback_started_at: self.rollback_started_at,
             }));
         }
 
@@ -270,11 +392,66 @@ where
     }
 }
 
+/// Describe the events for a stack that occurred at or after `since`.
+///
+/// Events are returned newest first, so we follow `next_token` until we see an event from before
+/// `since`. Timestamps are not unique, so events *at* `since` that have already been seen (i.e.
+/// those in `seen_at_since`) are filtered by ID.
+async fn describe_stack_events_since(
+    client: &aws_sdk_cloudformation::Client,
+    polling: &PollingConfig,
+    stack_id: String,
+    since: DateTime<Utc>,
+    seen_at_since: &HashSet<String>,
+) -> Result<Vec<aws_sdk_cloudformation::types::StackEvent>, StackEventsError> {
+    let mut stack_events = Vec::new();
+    let mut next_token = None;
+    loop {
+        let output = polling
+            .retry_throttled(polling.stack_events_interval, || {
+                client
+                    .describe_stack_events()
+                    .stack_name(stack_id.clone())
+                    .set_next_token(next_token.clone())
+                    .send()
+            })
+            .await?;
+        let mut reached_since = false;
+        for event in output.stack_events.expected(
+            DESCRIBE_STACK_EVENTS,
+            "DescribeStackEventsOutput without stack_events",
+        )? {
+            let timestamp = event
+                .timestamp
+                .expected(DESCRIBE_STACK_EVENTS, "StackEvent without timestamp")?
+                .to_chrono_utc()
+                .expected(DESCRIBE_STACK_EVENTS, "StackEvent with invalid timestamp")?;
+            if timestamp < since {
+                reached_since = true;
+                break;
+            }
+            if timestamp == since
+                && event
+                    .event_id()
+                    .is_some_and(|event_id| seen_at_since.contains(event_id))
+            {
+                continue;
+            }
+            stack_events.push(event);
+        }
+
+        next_token = output.next_token;
+        if reached_since || next_token.is_none() {
+            return Ok(stack_events);
+        }
+    }
+}
+
 impl<F> Stream for StackOperation<'_, F>
 where
-    F: Fn(StackStatus) -> StackOperationStatus + Unpin,
+    F: Fn(&StackStatus) -> StackOperationStatus + Unpin,
 {
-    type Item = Result<StackEvent, SdkError<DescribeStackEventsError>>;
+    type Item = Result<StackEvent, StackEventsError>;
 
     fn poll_next(
         mut self: Pin<&mut Self>,
@@ -292,24 +469,36 @@ where
                     } => {
                         if resource_status.sentiment().is_negative() {
                             self.resource_error_events
-                                .push((*resource_status, details.clone()));
+                                .push((resource_status.clone(), details.clone()));
                         }
                     }
                     StackEvent::Stack {
                         resource_status, ..
                     } if event.stack_id() == self.stack_id => {
+                        if *resource_status == StackStatus::UpdateRollbackInProgress
+                            && self.rollback_started_at.is_none()
+                        {
+                            self.rollback_started_at = Some(*event.timestamp());
+                        }
                         if resource_status.sentiment().is_negative() {
                             if let Some(reason) = event.resource_status_reason() {
                                 self.stack_error_status_reason.replace(reason.to_string());
                             }
                         }
-                        match (self.check_progress)(*resource_status) {
+                        match (self.check_progress)(resource_status) {
                             StackOperationStatus::InProgress | StackOperationStatus::Complete => {}
                             StackOperationStatus::Failed => {
-                                self.stack_error_status = Some(*resource_status);
+                                self.stack_error_status = Some(resource_status.clone());
                             }
                             StackOperationStatus::Unexpected => {
-                                panic!("stack has unexpected status for: {}", resource_status);
+                                return task::Poll::Ready(Some(Err(UnexpectedResponse::new(
+                                    DESCRIBE_STACK_EVENTS,
+                                    format!(
+                                        "stack {} has unexpected status for operation: {}",
+                                        self.stack_id, resource_status
+                                    ),
+                                )
+                                .into())));
                             }
                         }
                     }
@@ -321,3 +510,94 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use crate::{ResourceStatus, StackEventDetails, StackStatus};
+
+    use super::StackFailure;
+
+    fn details(
+        stack_alias: Option<&str>,
+        logical_resource_id: &str,
+        resource_type: &str,
+        timestamp: DateTime<Utc>,
+    ) -> StackEventDetails {
+        StackEventDetails {
+            client_request_token: None,
+            event_id: format!("{logical_resource_id}-{}", timestamp.timestamp()),
+            logical_resource_id: logical_resource_id.to_string(),
+            physical_resource_id: None,
+            resource_status_reason: None,
+            resource_type: resource_type.to_string(),
+            stack_id: "arn:stack".to_string(),
+            stack_name: "my-stack".to_string(),
+            stack_alias: stack_alias.map(str::to_string),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn resources_to_skip() {
+        let at = |secs| Utc.timestamp_opt(secs, 0).unwrap();
+        let mut failure = StackFailure {
+            stack_id: "arn:stack".to_string(),
+            stack_status: StackStatus::UpdateRollbackFailed,
+            stack_status_reason: "oh no".to_string(),
+            resource_events: vec![
+                // Failed during the update, rather than the rollback.
+                (
+                    ResourceStatus::UpdateFailed,
+                    details(None, "Bucket", "AWS::S3::Bucket", at(1)),
+                ),
+                (
+                    ResourceStatus::UpdateFailed,
+                    details(None, "Subnet", "AWS::EC2::Subnet", at(3)),
+                ),
+                (
+                    ResourceStatus::UpdateFailed,
+                    details(Some("Outer/Inner"), "Queue", "AWS::SQS::Queue", at(4)),
+                ),
+                (
+                    ResourceStatus::UpdateFailed,
+                    details(None, "Outer", "AWS::CloudFormation::Stack", at(5)),
+                ),
+                (
+                    ResourceStatus::DeleteFailed,
+                    details(None, "Topic", "AWS::SNS::Topic", at(6)),
+                ),
+                (
+                    ResourceStatus::UpdateFailed,
+                    details(None, "Subnet", "AWS::EC2::Subnet", at(7)),
+                ),
+            ],
+            rollback_started_at: Some(at(2)),
+        };
+        assert_eq!(failure.resources_to_skip(), vec!["Subnet", "Inner.Queue"]);
+
+        failure.stack_status = StackStatus::UpdateRollbackComplete;
+        assert!(failure.resources_to_skip().is_empty());
+
+        failure.stack_status = StackStatus::UpdateRollbackFailed;
+        failure.rollback_started_at = None;
+        assert!(failure.resources_to_skip().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_without_rollback_started_at() {
+        let failure: StackFailure = serde_json::from_str(
+            r#"{
+                "stack_id": "arn:stack",
+                "stack_status": "UPDATE_ROLLBACK_FAILED",
+                "stack_status_reason": "oh no",
+                "resource_events": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(failure.rollback_started_at, None);
+        assert!(failure.resources_to_skip().is_empty());
+    }
+}
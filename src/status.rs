@@ -22,6 +22,7 @@ pub trait Status: std::fmt::Debug + std::fmt::Display + private::Sealed {
 
 /// An indication of whether a status is positive, negative, or neutral for the affected resource.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StatusSentiment {
     Positive,
     Neutral,
@@ -49,8 +50,9 @@ impl StatusSentiment {
 }
 
 /// Possible change set statuses.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[derive(Clone, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
 #[display(style = "SNAKE_CASE")]
+#[non_exhaustive]
 pub enum ChangeSetStatus {
     CreatePending,
     CreateInProgress,
@@ -60,6 +62,13 @@ pub enum ChangeSetStatus {
     DeleteComplete,
     DeleteFailed,
     Failed,
+
+    /// A status that this version of the library doesn't recognise.
+    ///
+    /// This carries the raw status string, so that new statuses introduced by CloudFormation don't
+    /// cause errors. [`Status`] methods return conservative answers for unknown statuses.
+    #[display("{0}")]
+    Unknown(String),
 }
 
 impl Status for ChangeSetStatus {
@@ -70,6 +79,7 @@ impl Status for ChangeSetStatus {
             | Self::DeletePending
             | Self::DeleteInProgress => false,
             Self::CreateComplete | Self::DeleteComplete | Self::DeleteFailed | Self::Failed => true,
+            Self::Unknown(status) => unknown_is_settled(status),
         }
     }
 
@@ -81,13 +91,15 @@ impl Status for ChangeSetStatus {
             | Self::DeletePending
             | Self::DeleteInProgress => StatusSentiment::Neutral,
             Self::DeleteFailed | Self::Failed => StatusSentiment::Negative,
+            Self::Unknown(status) => unknown_sentiment(status),
         }
     }
 }
 
 /// Possible stack statuses.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[derive(Clone, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
 #[display(style = "SNAKE_CASE")]
+#[non_exhaustive]
 pub enum StackStatus {
     CreateInProgress,
     CreateFailed,
@@ -112,6 +124,13 @@ pub enum StackStatus {
     ImportRollbackInProgress,
     ImportRollbackFailed,
     ImportRollbackComplete,
+
+    /// A status that this version of the library doesn't recognise.
+    ///
+    /// This carries the raw status string, so that new statuses introduced by CloudFormation don't
+    /// cause errors. [`Status`] methods return conservative answers for unknown statuses.
+    #[display("{0}")]
+    Unknown(String),
 }
 
 impl Status for StackStatus {
@@ -140,6 +159,7 @@ impl Status for StackStatus {
             | Self::ImportComplete
             | Self::ImportRollbackFailed
             | Self::ImportRollbackComplete => true,
+            Self::Unknown(status) => unknown_is_settled(status),
         }
     }
 
@@ -168,6 +188,7 @@ impl Status for StackStatus {
             | Self::ImportRollbackInProgress
             | Self::ImportRollbackFailed
             | Self::ImportRollbackComplete => StatusSentiment::Negative,
+            Self::Unknown(status) => unknown_sentiment(status),
         }
     }
 }
@@ -214,14 +235,16 @@ impl TryFrom<StackStatus> for BlockedStackStatus {
             | StackStatus::ImportComplete
             | StackStatus::ImportRollbackInProgress
             | StackStatus::ImportRollbackFailed
-            | StackStatus::ImportRollbackComplete => Err(status),
+            | StackStatus::ImportRollbackComplete
+            | StackStatus::Unknown(_) => Err(status),
         }
     }
 }
 
 /// Possible resource statuses.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
+#[derive(Clone, Debug, Eq, PartialEq, parse_display::Display, parse_display::FromStr)]
 #[display(style = "SNAKE_CASE")]
+#[non_exhaustive]
 pub enum ResourceStatus {
     CreateInProgress,
     CreateFailed,
@@ -239,6 +262,13 @@ pub enum ResourceStatus {
     ImportRollbackInProgress,
     ImportRollbackFailed,
     ImportRollbackComplete,
+
+    /// A status that this version of the library doesn't recognise.
+    ///
+    /// This carries the raw status string, so that new statuses introduced by CloudFormation don't
+    /// cause errors. [`Status`] methods return conservative answers for unknown statuses.
+    #[display("{0}")]
+    Unknown(String),
 }
 
 impl Status for ResourceStatus {
@@ -260,6 +290,7 @@ impl Status for ResourceStatus {
             | Self::ImportComplete
             | Self::ImportRollbackFailed
             | Self::ImportRollbackComplete => true,
+            Self::Unknown(status) => unknown_is_settled(status),
         }
     }
 
@@ -281,10 +312,30 @@ impl Status for ResourceStatus {
             | Self::ImportRollbackInProgress
             | Self::ImportRollbackFailed
             | Self::ImportRollbackComplete => StatusSentiment::Negative,
+            Self::Unknown(status) => unknown_sentiment(status),
         }
     }
 }
 
+/// Guess whether an unknown status is settled, based on CloudFormation's naming conventions.
+fn unknown_is_settled(status: &str) -> bool {
+    ["_COMPLETE", "_FAILED", "_SKIPPED"]
+        .iter()
+        .any(|suffix| status.ends_with(suffix))
+}
+
+/// Guess the sentiment of an unknown status, based on CloudFormation's naming conventions.
+///
+/// Unknown statuses are never considered positive, since it's better to draw attention to a status
+/// we don't understand than to let it pass silently.
+fn unknown_sentiment(status: &str) -> StatusSentiment {
+    if status.contains("FAILED") || status.contains("ROLLBACK") {
+        StatusSentiment::Negative
+    } else {
+        StatusSentiment::Neutral
+    }
+}
+
 mod private {
     /// An unreachable trait used to prevent some traits from being implemented outside the crate.
     pub trait Sealed {}
@@ -309,7 +360,10 @@ mod tests {
             "UPDATE_ROLLBACK_COMPLETE_CLEANUP_IN_PROGRESS".parse(),
             Ok(StackStatus::UpdateRollbackCompleteCleanupInProgress)
         );
-        assert!("oh no".parse::<StackStatus>().is_err());
+        assert_eq!(
+            "oh no".parse(),
+            Ok(StackStatus::Unknown("oh no".to_string()))
+        );
     }
 
     #[test]
@@ -323,6 +377,25 @@ mod tests {
             "IMPORT_ROLLBACK_IN_PROGRESS".parse(),
             Ok(ResourceStatus::ImportRollbackInProgress)
         );
-        assert!("oh no".parse::<ResourceStatus>().is_err());
+        assert_eq!(
+            "oh no".parse(),
+            Ok(ResourceStatus::Unknown("oh no".to_string()))
+        );
+    }
+
+    #[test]
+    fn unknown_status() {
+        let status: StackStatus = "UPDATE_FROBNICATE_IN_PROGRESS".parse().unwrap();
+        assert_eq!(status.to_string(), "UPDATE_FROBNICATE_IN_PROGRESS");
+        assert!(!status.is_settled());
+        assert_eq!(status.sentiment(), StatusSentiment::Neutral);
+
+        let status: ResourceStatus = "ROLLBACK_FROBNICATE_FAILED".parse().unwrap();
+        assert!(status.is_settled());
+        assert_eq!(status.sentiment(), StatusSentiment::Negative);
+
+        let status: ChangeSetStatus = "FROBNICATE_COMPLETE".parse().unwrap();
+        assert!(status.is_settled());
+        assert_eq!(status.sentiment(), StatusSentiment::Neutral);
     }
 }
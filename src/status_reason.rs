@@ -1,12 +1,11 @@
 //! Detailed status reasons.
 
-use std::fmt;
+use std::{fmt, sync::LazyLock};
 
 use aws_config::SdkConfig;
 use aws_sdk_sts::{
     error::SdkError, operation::decode_authorization_message::DecodeAuthorizationMessageError,
 };
-use lazy_static::lazy_static;
 use regex::Regex;
 
 /// A wrapper around a status reason that offers additional detail.
@@ -70,22 +69,24 @@ pub enum StatusReasonDetail<'a> {
 
 impl<'a> StatusReasonDetail<'a> {
     fn new(status_reason: &'a str) -> Option<Self> {
-        lazy_static! {
-            static ref CREATION_CANCELLED: Regex =
-                Regex::new(r"(?i)Resource creation cancelled").unwrap();
+        static CREATION_CANCELLED: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"(?i)Resource creation cancelled").unwrap());
 
-            static ref MISSING_PERMISSION_1: Regex =
-                Regex::new(r"(?i)API: (?P<permission>[a-z0-9]+:[a-z0-9]+)\b").unwrap();
+        static MISSING_PERMISSION_1: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?i)API: (?P<permission>[a-z0-9]+:[a-z0-9]+)\b").unwrap()
+        });
 
-            static ref MISSING_PERMISSION_2: Regex =
-                Regex::new(r"(?i)User: (?P<principal>[a-z0-9:/-]+) is not authorized to perform: (?P<permission>[a-z0-9]+:[a-z0-9]+)").unwrap();
+        static MISSING_PERMISSION_2: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?i)User: (?P<principal>[a-z0-9:/-]+) is not authorized to perform: (?P<permission>[a-z0-9]+:[a-z0-9]+)").unwrap()
+        });
 
-            static ref RESOURCE_ERRORS: Regex =
-                Regex::new(r"(?i)The following resource\(s\) failed to (?:create|delete|update): \[(?P<logical_resource_ids>[a-z0-9]+(?:, *[a-z0-9]+)*)\]").unwrap();
+        static RESOURCE_ERRORS: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?i)The following resource\(s\) failed to (?:create|delete|update): \[(?P<logical_resource_ids>[a-z0-9]+(?:, *[a-z0-9]+)*)\]").unwrap()
+        });
 
-            static ref ENCODED_AUTHORIZATION_MESSAGE: Regex =
-                Regex::new("(?i)Encoded authorization failure message: (?P<encoded_authorization_message>[a-z0-9_-]+)").unwrap();
-        }
+        static ENCODED_AUTHORIZATION_MESSAGE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new("(?i)Encoded authorization failure message: (?P<encoded_authorization_message>[a-z0-9_-]+)").unwrap()
+        });
 
         if CREATION_CANCELLED.is_match(status_reason) {
             return Some(Self::CreationCancelled);
@@ -213,7 +214,7 @@ impl<'a> EncodedAuthorizationMessage<'a> {
 
 /// The error returned by [`EncodedAuthorizationMessage::decode`].
 #[derive(Debug)]
-pub struct EncodedAuthorizationMessageDecodeError(Box<dyn std::error::Error>);
+pub struct EncodedAuthorizationMessageDecodeError(Box<dyn std::error::Error + Send + Sync>);
 
 impl EncodedAuthorizationMessageDecodeError {
     fn from_sdk(error: SdkError<DecodeAuthorizationMessageError>) -> Self {
@@ -242,9 +243,8 @@ pub struct ResourceErrors<'a> {
 impl<'a> ResourceErrors<'a> {
     /// The logical resource IDs of resources that failed.
     pub fn logical_resource_ids(&self) -> impl Iterator<Item = &'a str> {
-        lazy_static! {
-            static ref LOGICAL_RESOURCE_ID: Regex = Regex::new("(?i)[a-z0-9]+").unwrap();
-        }
+        static LOGICAL_RESOURCE_ID: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new("(?i)[a-z0-9]+").unwrap());
 
         LOGICAL_RESOURCE_ID
             .find_iter(self.logical_resource_ids)
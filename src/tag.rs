@@ -1,5 +1,8 @@
+use crate::unexpected_response::{Expected, UnexpectedResponse};
+
 /// A resource tag.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tag {
     /// The tag key.
     pub key: String,
@@ -9,11 +12,14 @@ pub struct Tag {
 }
 
 impl Tag {
-    pub(crate) fn from_sdk(tag: aws_sdk_cloudformation::types::Tag) -> Self {
-        Self {
-            key: tag.key.expect("Tag without key"),
-            value: tag.value.expect("Tag without value"),
-        }
+    pub(crate) fn from_sdk(
+        api: &'static str,
+        tag: aws_sdk_cloudformation::types::Tag,
+    ) -> Result<Self, UnexpectedResponse> {
+        Ok(Self {
+            key: tag.key.expected(api, "Tag without key")?,
+            value: tag.value.expected(api, "Tag without value")?,
+        })
     }
 
     pub(crate) fn into_sdk(self) -> aws_sdk_cloudformation::types::Tag {
@@ -0,0 +1,1137 @@
+//! A local model of CloudFormation templates, with offline validation.
+//!
+//! Mistakes in templates, such as a `Ref` to a resource that doesn't exist, are normally only
+//! reported by CloudFormation when creating a change set. [`Template::parse`] and
+//! [`Template::validate`] can be used to catch many such mistakes locally, before any API calls
+//! are made.
+//!
+//! ```
+//! use cloudformatious::template::{DiagnosticKind, Template};
+//!
+//! let template = Template::parse(
+//!     r#"
+//! Resources:
+//!   Topic:
+//!     Type: AWS::SNS::Topic
+//!     Properties:
+//!       TopicName: !Sub ${AWS::StackName}-${Environment}
+//! "#,
+//! )?;
+//!
+//! let diagnostics = template.validate();
+//! assert_eq!(diagnostics.len(), 1);
+//! assert_eq!(
+//!     diagnostics[0].kind,
+//!     DiagnosticKind::UnresolvedRef {
+//!         target: "Environment".to_string()
+//!     }
+//! );
+//! assert_eq!(
+//!     diagnostics[0].to_string(),
+//!     "6:23: reference to undefined parameter or resource Environment"
+//! );
+//! # Ok::<_, cloudformatious::template::ParseError>(())
+//! ```
+
+mod capabilities;
+mod parameters;
+mod parse;
+mod validate;
+
+use std::fmt;
+
+use crate::Capability;
+
+/// A parsed CloudFormation template.
+///
+/// Templates can be parsed from JSON or YAML with [`parse`](Self::parse). Only the sections of the
+/// template that are relevant for validation are modelled, and any other sections (such as
+/// `Mappings` or `Metadata`) are ignored.
+///
+/// Entries of each section are kept in the order they appear in the template, including any
+/// duplicates, which are reported by [`validate`](Self::validate).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    /// The `AWSTemplateFormatVersion` of the template, if any.
+    pub format_version: Option<String>,
+
+    /// The `Description` of the template, if any.
+    pub description: Option<String>,
+
+    /// The macros named in the `Transform` section of the template.
+    ///
+    /// A single transform, such as `AWS::Serverless-2016-10-31`, is represented as a list of one.
+    pub transform: Vec<String>,
+
+    /// The entries of the `Parameters` section.
+    pub parameters: Vec<Parameter>,
+
+    /// The entries of the `Conditions` section.
+    pub conditions: Vec<Condition>,
+
+    /// The entries of the `Resources` section.
+    pub resources: Vec<Resource>,
+
+    /// The entries of the `Outputs` section.
+    pub outputs: Vec<Output>,
+
+    /// Warnings about the template's source, reported by `validate`.
+    warnings: Vec<Diagnostic>,
+
+    /// Whether the template uses macros, see `uses_macros`.
+    uses_macros: bool,
+}
+
+impl Template {
+    /// Parse a JSON or YAML template.
+    ///
+    /// YAML templates may use the short form of intrinsic functions (e.g. `!Ref Bucket`), which is
+    /// converted to the equivalent full form (e.g. `{"Ref": "Bucket"}`).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if the source isn't valid YAML (or JSON), or if the template
+    /// doesn't have the expected structure (e.g. a resource without a `Type`).
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        parse::parse(source)
+    }
+
+    /// Check the template for mistakes that CloudFormation would reject.
+    ///
+    /// The following are reported:
+    ///
+    /// - Logical IDs that are used more than once.
+    /// - `Ref`s (including in `Fn::Sub`) to parameters or resources that aren't defined.
+    /// - `Fn::GetAtt`s (including in `Fn::Sub`) and `DependsOn`s naming resources that aren't
+    ///   defined.
+    /// - Conditions that are referred to but not defined.
+    /// - Templates that look like JSON but aren't valid JSON (CloudFormation will parse these as
+    ///   YAML, which may not be what was intended).
+    ///
+    /// Templates that [use macros](Self::uses_macros) may refer to resources and conditions that
+    /// are created by the macros, so references in such templates are only reported as warnings.
+    #[must_use]
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validate::validate(self)
+    }
+
+    /// Check input `parameters` against the template's `Parameters` section.
+    ///
+    /// Any problems that CloudFormation would reject when creating a change set are returned.
+    /// These are input parameters that the template doesn't declare, declared parameters without
+    /// a `Default` that are missing from the input, and values that violate the parameter's
+    /// constraints (`AllowedValues`, `AllowedPattern`, `MinLength`, `MaxLength`, `MinValue`, and
+    /// `MaxValue`). For `List<...>` and `CommaDelimitedList` parameters, the constraints are
+    /// checked for each item in the list.
+    ///
+    /// The values of `NoEcho` parameters are not included in the returned errors. Parameters that
+    /// [use their previous value](crate::Parameter::use_previous_value) count as given, but
+    /// their values aren't checked.
+    ///
+    /// ```
+    /// use cloudformatious::{template::{ParameterErrorKind, Template}, Parameter};
+    ///
+    /// let template = Template::parse(
+    ///     r#"
+    /// Parameters:
+    ///   Environment:
+    ///     Type: String
+    ///     AllowedValues: [staging, production]
+    /// Resources:
+    ///   Topic:
+    ///     Type: AWS::SNS::Topic
+    /// "#,
+    /// )?;
+    ///
+    /// let errors = template.check_parameters(&[Parameter::new("Environment", "prod")]);
+    /// assert_eq!(errors.len(), 1);
+    /// assert!(matches!(errors[0].kind, ParameterErrorKind::NotAllowed { .. }));
+    /// assert_eq!(
+    ///     errors[0].to_string(),
+    ///     r#"parameter Environment: value "prod" is not one of the allowed values: staging, production"#
+    /// );
+    /// # Ok::<_, cloudformatious::template::ParseError>(())
+    /// ```
+    #[must_use]
+    pub fn check_parameters(&self, parameters: &[crate::Parameter]) -> Vec<ParameterError> {
+        parameters::check(self, parameters)
+    }
+
+    /// Get the capabilities that must be acknowledged to apply the template.
+    ///
+    /// The smallest set of capabilities is returned, i.e. at most one of [`Capability::Iam`] and
+    /// [`Capability::NamedIam`]:
+    ///
+    /// - [`Capability::Iam`] if the template contains any of the IAM resources listed in the
+    ///   [`Capability`] documentation.
+    /// - [`Capability::NamedIam`] if any of those resources have custom names (e.g. a `RoleName`),
+    ///   or if the template contains nested stacks (`AWS::CloudFormation::Stack`), since the nested
+    ///   stacks' templates can't be inspected.
+    /// - [`Capability::AutoExpand`] if the template [uses macros](Self::uses_macros).
+    ///
+    /// ```
+    /// use cloudformatious::{template::Template, Capability};
+    ///
+    /// let template = Template::parse(
+    ///     r#"
+    /// Transform: AWS::Serverless-2016-10-31
+    /// Resources:
+    ///   Role:
+    ///     Type: AWS::IAM::Role
+    ///     Properties:
+    ///       RoleName: my-role
+    ///       AssumeRolePolicyDocument: {}
+    /// "#,
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     template.required_capabilities(),
+    ///     [Capability::NamedIam, Capability::AutoExpand]
+    /// );
+    /// # Ok::<_, cloudformatious::template::ParseError>(())
+    /// ```
+    #[must_use]
+    pub fn required_capabilities(&self) -> Vec<Capability> {
+        capabilities::required(self)
+    }
+
+    /// Whether or not the template uses macros.
+    ///
+    /// This is the case if the template has a [`transform`](Self::transform), or uses
+    /// `Fn::Transform` (e.g. to include snippets with `AWS::Include`).
+    #[must_use]
+    pub fn uses_macros(&self) -> bool {
+        self.uses_macros
+    }
+
+    /// Get the parameter with the given logical ID, if any.
+    #[must_use]
+    pub fn parameter(&self, logical_id: &str) -> Option<&Parameter> {
+        self.parameters
+            .iter()
+            .find(|parameter| parameter.logical_id == logical_id)
+    }
+
+    /// Get the resource with the given logical ID, if any.
+    #[must_use]
+    pub fn resource(&self, logical_id: &str) -> Option<&Resource> {
+        self.resources
+            .iter()
+            .find(|resource| resource.logical_id == logical_id)
+    }
+}
+
+/// An entry in the `Parameters` section of a template.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    /// The logical ID of the parameter.
+    pub logical_id: String,
+
+    /// The location of the parameter's logical ID.
+    pub location: Location,
+
+    /// The parameter's `Type`, e.g. `String` or `AWS::EC2::VPC::Id`.
+    pub parameter_type: String,
+
+    /// The parameter's `Description`, if any.
+    pub description: Option<String>,
+
+    /// The parameter's `Default` value, if any.
+    pub default: Option<String>,
+
+    /// The parameter's `AllowedValues`, if any.
+    pub allowed_values: Option<Vec<String>>,
+
+    /// The parameter's `AllowedPattern`, if any.
+    pub allowed_pattern: Option<String>,
+
+    /// The parameter's `ConstraintDescription`, if any.
+    pub constraint_description: Option<String>,
+
+    /// The parameter's `MinLength`, if any.
+    pub min_length: Option<u64>,
+
+    /// The parameter's `MaxLength`, if any.
+    pub max_length: Option<u64>,
+
+    /// The parameter's `MinValue`, if any.
+    pub min_value: Option<f64>,
+
+    /// The parameter's `MaxValue`, if any.
+    pub max_value: Option<f64>,
+
+    /// Whether or not the parameter is `NoEcho`.
+    pub no_echo: bool,
+}
+
+/// An entry in the `Conditions` section of a template.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    /// The logical ID of the condition.
+    pub logical_id: String,
+
+    /// The location of the condition's logical ID.
+    pub location: Location,
+
+    /// The condition's definition, e.g. `{"Fn::Equals": [...]}`.
+    pub definition: Node,
+}
+
+/// An entry in the `Resources` section of a template.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resource {
+    /// The logical ID of the resource.
+    pub logical_id: String,
+
+    /// The location of the resource's logical ID.
+    pub location: Location,
+
+    /// The resource's `Type`, e.g. `AWS::S3::Bucket`.
+    pub resource_type: String,
+
+    /// The name of the resource's `Condition`, if any.
+    pub condition: Option<String>,
+
+    /// The logical IDs of the resources named in the resource's `DependsOn` attribute.
+    pub depends_on: Vec<String>,
+
+    /// The resource's `Properties`, if any.
+    pub properties: Option<Node>,
+
+    /// The resource's `Metadata`, if any.
+    pub metadata: Option<Node>,
+}
+
+/// An entry in the `Outputs` section of a template.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Output {
+    /// The logical ID of the output.
+    pub logical_id: String,
+
+    /// The location of the output's logical ID.
+    pub location: Location,
+
+    /// The output's `Value`.
+    pub value: Node,
+
+    /// The output's `Description`, if any.
+    pub description: Option<String>,
+
+    /// The name of the output's `Condition`, if any.
+    pub condition: Option<String>,
+
+    /// The `Name` of the output's `Export`, if any.
+    pub export_name: Option<Node>,
+}
+
+/// A value in a template, along with its location.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    /// The value.
+    pub value: Value,
+
+    /// The location of the value in the template.
+    pub location: Location,
+}
+
+impl Node {
+    /// Get the value as a string, if it is one.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value of the first entry with the given `key`, if the value is a mapping.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        match &self.value {
+            Value::Mapping(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key.name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+/// A JSON or YAML value.
+///
+/// Intrinsic functions are represented as mappings with a single entry, e.g. `!Ref Bucket` is
+/// represented as a mapping from `Ref` to `Bucket`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A `null` value.
+    Null,
+
+    /// A boolean.
+    Bool(bool),
+
+    /// A number, as it was written in the template.
+    Number(String),
+
+    /// A string.
+    String(String),
+
+    /// A sequence of values.
+    Sequence(Vec<Node>),
+
+    /// A mapping, with entries in the order they appear in the template.
+    Mapping(Vec<(Key, Node)>),
+}
+
+/// A key in a mapping, along with its location.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Key {
+    /// The key.
+    pub name: String,
+
+    /// The location of the key in the template.
+    pub location: Location,
+}
+
+/// A location in a template.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    /// The line number, starting from 1.
+    pub line: usize,
+
+    /// The column number, starting from 1.
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A problem found in a template.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    /// Where the problem was found.
+    pub location: Location,
+
+    /// Whether CloudFormation would reject the template because of the problem.
+    pub severity: Severity,
+
+    /// What the problem is.
+    pub kind: DiagnosticKind,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "{}: {}", self.location, self.kind),
+            Severity::Warning => write!(f, "{}: warning: {}", self.location, self.kind),
+        }
+    }
+}
+
+/// The severity of a [`Diagnostic`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// CloudFormation will reject the template.
+    Error,
+
+    /// CloudFormation may accept the template, but it's likely to be a mistake.
+    Warning,
+}
+
+/// The kinds of problem that can be found in a template.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// The template isn't valid YAML (or JSON).
+    Syntax {
+        /// A description of the syntax error.
+        message: String,
+    },
+
+    /// The template starts like JSON, but isn't valid JSON.
+    ///
+    /// CloudFormation parses such templates as YAML, which may succeed (e.g. if a string is
+    /// unquoted) but may not mean what was intended.
+    InvalidJson {
+        /// A description of the JSON syntax error.
+        message: String,
+    },
+
+    /// Part of the template doesn't have the expected structure.
+    Malformed {
+        /// A description of what was expected.
+        message: String,
+    },
+
+    /// A logical ID is used more than once.
+    DuplicateLogicalId {
+        /// The duplicated logical ID.
+        logical_id: String,
+    },
+
+    /// A `Ref` refers to a parameter or resource that isn't defined.
+    UnresolvedRef {
+        /// The logical ID that was referred to.
+        target: String,
+    },
+
+    /// A `Fn::GetAtt` refers to a resource that isn't defined.
+    UnresolvedGetAtt {
+        /// The logical ID that was referred to.
+        target: String,
+    },
+
+    /// A `DependsOn` names a resource that isn't defined.
+    UnresolvedDependsOn {
+        /// The logical ID that was named.
+        target: String,
+    },
+
+    /// A condition is referred to that isn't defined.
+    UndefinedCondition {
+        /// The name of the condition.
+        condition: String,
+    },
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { message } => write!(f, "syntax error: {message}"),
+            Self::InvalidJson { message } => write!(
+                f,
+                "template looks like JSON but isn't valid JSON ({message}), so it will be parsed as YAML"
+            ),
+            Self::Malformed { message } => write!(f, "{message}"),
+            Self::DuplicateLogicalId { logical_id } => {
+                write!(f, "logical ID {logical_id} is used more than once")
+            }
+            Self::UnresolvedRef { target } => {
+                write!(f, "reference to undefined parameter or resource {target}")
+            }
+            Self::UnresolvedGetAtt { target } => {
+                write!(f, "attribute of undefined resource {target}")
+            }
+            Self::UnresolvedDependsOn { target } => {
+                write!(f, "dependency on undefined resource {target}")
+            }
+            Self::UndefinedCondition { condition } => {
+                write!(f, "reference to undefined condition {condition}")
+            }
+        }
+    }
+}
+
+/// The error returned by [`Template::parse`].
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ParseError {
+    /// The problems that prevented the template from being parsed.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid template")?;
+        for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+            write!(f, "{}{diagnostic}", if i == 0 { ": " } else { "; " })?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A problem with an input parameter, returned by [`Template::check_parameters`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterError {
+    /// The key of the parameter.
+    pub key: String,
+
+    /// The value of the parameter.
+    ///
+    /// This is `None` if the parameter is missing or not declared by the template, or if the
+    /// parameter is `NoEcho`.
+    pub value: Option<String>,
+
+    /// The parameter's `ConstraintDescription`, if it has one and the value violates one of its
+    /// constraints.
+    ///
+    /// As with CloudFormation, this is used to describe the violation instead of the constraint
+    /// itself.
+    pub constraint_description: Option<String>,
+
+    /// What the problem is.
+    pub kind: ParameterErrorKind,
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "parameter {}: ", self.key)?;
+        if let Some(value) = &self.value {
+            write!(f, "value {value:?} ")?;
+        }
+        match &self.constraint_description {
+            Some(constraint_description) => write!(f, "{constraint_description}"),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+/// The kinds of problem that can be found with an input parameter.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ParameterErrorKind {
+    /// The parameter isn't declared by the template.
+    Unknown,
+
+    /// The parameter is declared without a `Default`, but no value was given.
+    Missing,
+
+    /// The value isn't one of the parameter's `AllowedValues`.
+    NotAllowed {
+        /// The parameter's `AllowedValues`.
+        allowed_values: Vec<String>,
+    },
+
+    /// The value doesn't match the parameter's `AllowedPattern`.
+    PatternMismatch {
+        /// The parameter's `AllowedPattern`.
+        allowed_pattern: String,
+    },
+
+    /// The value is shorter than the parameter's `MinLength`.
+    TooShort {
+        /// The parameter's `MinLength`.
+        min_length: u64,
+    },
+
+    /// The value is longer than the parameter's `MaxLength`.
+    TooLong {
+        /// The parameter's `MaxLength`.
+        max_length: u64,
+    },
+
+    /// The value of a `Number` parameter isn't a number.
+    NotANumber,
+
+    /// The value is less than the parameter's `MinValue`.
+    TooSmall {
+        /// The parameter's `MinValue`.
+        min_value: f64,
+    },
+
+    /// The value is greater than the parameter's `MaxValue`.
+    TooLarge {
+        /// The parameter's `MaxValue`.
+        max_value: f64,
+    },
+}
+
+impl fmt::Display for ParameterErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "is not declared by the template"),
+            Self::Missing => write!(f, "must be given, as it has no default"),
+            Self::NotAllowed { allowed_values } => write!(
+                f,
+                "is not one of the allowed values: {}",
+                allowed_values.join(", ")
+            ),
+            Self::PatternMismatch { allowed_pattern } => {
+                write!(f, "does not match the allowed pattern {allowed_pattern}")
+            }
+            Self::TooShort { min_length } => {
+                write!(f, "is shorter than the minimum length of {min_length}")
+            }
+            Self::TooLong { max_length } => {
+                write!(f, "is longer than the maximum length of {max_length}")
+            }
+            Self::NotANumber => write!(f, "is not a number"),
+            Self::TooSmall { min_value } => {
+                write!(f, "is less than the minimum value of {min_value}")
+            }
+            Self::TooLarge { max_value } => {
+                write!(f, "is greater than the maximum value of {max_value}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Diagnostic, DiagnosticKind, Location, ParameterErrorKind, Severity, Template, Value,
+    };
+    use crate::Capability;
+
+    fn diagnostics(source: &str) -> Vec<(usize, Severity, DiagnosticKind)> {
+        Template::parse(source)
+            .unwrap()
+            .validate()
+            .into_iter()
+            .map(
+                |Diagnostic {
+                     location,
+                     severity,
+                     kind,
+                 }| (location.line, severity, kind),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn json_and_yaml_are_equivalent() {
+        let json = Template::parse(
+            r#"{
+                "Parameters": {
+                    "Name": {"Type": "String", "Default": 5, "AllowedValues": [5, "6"]}
+                },
+                "Resources": {
+                    "Topic": {
+                        "Type": "AWS::SNS::Topic",
+                        "DependsOn": "Queue",
+                        "Properties": {"TopicName": {"Fn::GetAtt": ["Queue", "QueueName"]}}
+                    },
+                    "Queue": {"Type": "AWS::SQS::Queue", "Properties": {"QueueName": {"Ref": "Name"}}}
+                }
+            }"#,
+        )
+        .unwrap();
+        let yaml = Template::parse(
+            r#"
+Parameters:
+  Name:
+    Type: String
+    Default: 5
+    AllowedValues: [5, "6"]
+Resources:
+  Topic:
+    Type: AWS::SNS::Topic
+    DependsOn: [Queue]
+    Properties:
+      TopicName: !GetAtt Queue.QueueName
+  Queue:
+    Type: AWS::SQS::Queue
+    Properties:
+      QueueName: !Ref Name
+"#,
+        )
+        .unwrap();
+
+        for template in [&json, &yaml] {
+            let parameter = template.parameter("Name").unwrap();
+            assert_eq!(parameter.parameter_type, "String");
+            assert_eq!(parameter.default.as_deref(), Some("5"));
+            assert_eq!(
+                parameter.allowed_values,
+                Some(vec!["5".to_string(), "6".to_string()])
+            );
+
+            let topic = template.resource("Topic").unwrap();
+            assert_eq!(topic.resource_type, "AWS::SNS::Topic");
+            assert_eq!(topic.depends_on, ["Queue"]);
+            let get_att = topic
+                .properties
+                .as_ref()
+                .and_then(|properties| properties.get("TopicName"))
+                .and_then(|name| name.get("Fn::GetAtt"))
+                .unwrap();
+            match &get_att.value {
+                Value::Sequence(items) => {
+                    let items: Vec<_> = items.iter().map(|item| item.as_str().unwrap()).collect();
+                    assert_eq!(items, ["Queue", "QueueName"]);
+                }
+                value => panic!("unexpected value: {:?}", value),
+            }
+
+            let queue_name = template.resource("Queue").unwrap().properties.as_ref();
+            let reference = queue_name
+                .and_then(|properties| properties.get("QueueName"))
+                .and_then(|name| name.get("Ref"))
+                .unwrap();
+            assert_eq!(reference.as_str(), Some("Name"));
+
+            assert_eq!(template.validate(), []);
+        }
+        assert_eq!(
+            yaml.resource("Topic").unwrap().location,
+            Location { line: 8, column: 3 }
+        );
+    }
+
+    #[test]
+    fn dangling_references() {
+        let source = r#"
+Conditions:
+  IsProd: !Equals [!Ref Environment, prod]
+  IsNotProd: !Not [!Condition IsProduction]
+Resources:
+  Topic:
+    Type: AWS::SNS::Topic
+    Condition: IsStaging
+    DependsOn: Queue
+    Properties:
+      TopicName: !Sub "${AWS::StackName}-${Queue.QueueName}-${Suffix}-${!Literal}"
+      DisplayName: !Sub
+        - "${Prefix}-${Name}"
+        - Prefix: !If [IsProd, prod, !GetAtt Bucket.Arn]
+Outputs:
+  TopicArn:
+    Value: !GetAtt Topic.TopicArn
+    Condition: IsProd
+"#;
+        assert_eq!(
+            diagnostics(source),
+            [
+                (
+                    3,
+                    Severity::Error,
+                    DiagnosticKind::UnresolvedRef {
+                        target: "Environment".to_string()
+                    }
+                ),
+                (
+                    4,
+                    Severity::Error,
+                    DiagnosticKind::UndefinedCondition {
+                        condition: "IsProduction".to_string()
+                    }
+                ),
+                (
+                    6,
+                    Severity::Error,
+                    DiagnosticKind::UndefinedCondition {
+                        condition: "IsStaging".to_string()
+                    }
+                ),
+                (
+                    6,
+                    Severity::Error,
+                    DiagnosticKind::UnresolvedDependsOn {
+                        target: "Queue".to_string()
+                    }
+                ),
+                (
+                    11,
+                    Severity::Error,
+                    DiagnosticKind::UnresolvedGetAtt {
+                        target: "Queue".to_string()
+                    }
+                ),
+                (
+                    11,
+                    Severity::Error,
+                    DiagnosticKind::UnresolvedRef {
+                        target: "Suffix".to_string()
+                    }
+                ),
+                (
+                    13,
+                    Severity::Error,
+                    DiagnosticKind::UnresolvedRef {
+                        target: "Name".to_string()
+                    }
+                ),
+                (
+                    14,
+                    Severity::Error,
+                    DiagnosticKind::UnresolvedGetAtt {
+                        target: "Bucket".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn transforms_downgrade_references_to_warnings() {
+        let source = r"
+Transform: AWS::Serverless-2016-10-31
+Resources:
+  Function:
+    Type: AWS::Serverless::Function
+    Properties:
+      Role: !GetAtt FunctionRole.Arn
+";
+        let template = Template::parse(source).unwrap();
+        assert_eq!(template.transform, ["AWS::Serverless-2016-10-31"]);
+        assert_eq!(
+            diagnostics(source),
+            [(
+                7,
+                Severity::Warning,
+                DiagnosticKind::UnresolvedGetAtt {
+                    target: "FunctionRole".to_string()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn duplicate_logical_ids() {
+        let source = r"
+Parameters:
+  Topic:
+    Type: String
+Resources:
+  Topic:
+    Type: AWS::SNS::Topic
+  Queue:
+    Type: AWS::SQS::Queue
+  Queue:
+    Type: AWS::SQS::Queue
+";
+        assert_eq!(
+            diagnostics(source),
+            [
+                (
+                    6,
+                    Severity::Error,
+                    DiagnosticKind::DuplicateLogicalId {
+                        logical_id: "Topic".to_string()
+                    }
+                ),
+                (
+                    10,
+                    Severity::Error,
+                    DiagnosticKind::DuplicateLogicalId {
+                        logical_id: "Queue".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unquoted_json_strings() {
+        let source = r#"{
+    "Conditions": {
+        "Never": { "Fn::Equals": [true, false] }
+    },
+    "Resources": {
+        "Fake": {
+            "Type": "Custom::Fake",
+            "Condition": Never
+        }
+    }
+}"#;
+        let template = Template::parse(source).unwrap();
+        assert_eq!(
+            template.resource("Fake").unwrap().condition.as_deref(),
+            Some("Never")
+        );
+        let diagnostics = template.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].location.line, 8);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(matches!(
+            diagnostics[0].kind,
+            DiagnosticKind::InvalidJson { .. }
+        ));
+
+        let source = source.replace("Never\n", "\"Never\"\n");
+        assert_eq!(Template::parse(&source).unwrap().validate(), []);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Template::parse("Resources:\n  Topic: [\n").unwrap_err();
+        assert_eq!(error.diagnostics.len(), 1);
+        assert!(matches!(
+            error.diagnostics[0].kind,
+            DiagnosticKind::Syntax { .. }
+        ));
+
+        let error = Template::parse(
+            r"
+Parameters:
+  Name:
+    Default: hello
+    MinLength: lots
+Resources:
+  Topic:
+    Properties: {}
+  Queue: !Ref Topic
+",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid template: \
+             5:16: Parameters.Name.MinLength must be a number; \
+             3:3: Parameters.Name must have a Type; \
+             7:3: Resources.Topic must have a Type; \
+             9:3: Resources.Queue must have a Type"
+        );
+    }
+
+    fn parameter(key: &str, value: &str) -> crate::Parameter {
+        crate::Parameter::new(key, value)
+    }
+
+    #[test]
+    fn check_parameters() {
+        let template = Template::parse(
+            r"
+Parameters:
+  Name:
+    Type: String
+    AllowedPattern: '[a-z]+'
+    MinLength: 3
+    MaxLength: 5
+    ConstraintDescription: must be 3-5 lowercase letters
+  Password:
+    Type: String
+    NoEcho: true
+    MinLength: 8
+  Count:
+    Type: Number
+    MinValue: 1
+    MaxValue: 10
+  Zones:
+    Type: List<AWS::EC2::AvailabilityZone::Name>
+    AllowedValues: [eu-west-1a, eu-west-1b]
+  Subnets:
+    Type: List<AWS::EC2::Subnet::Id>
+    Default: ''
+Resources:
+  Topic:
+    Type: AWS::SNS::Topic
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            template.check_parameters(&[
+                parameter("Name", "abc"),
+                parameter("Password", "hunter2hunter2"),
+                parameter("Count", "10"),
+                parameter("Zones", "eu-west-1a, eu-west-1b"),
+            ]),
+            []
+        );
+
+        let errors = template.check_parameters(&[
+            parameter("Name", "ABCDEF"),
+            parameter("Password", "hunter2"),
+            parameter("Count", "11"),
+            parameter("Zones", "eu-west-1a,eu-west-1c"),
+            parameter("Region", "eu-west-1"),
+        ]);
+        let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            [
+                r#"parameter Name: value "ABCDEF" must be 3-5 lowercase letters"#,
+                r#"parameter Name: value "ABCDEF" must be 3-5 lowercase letters"#,
+                "parameter Password: is shorter than the minimum length of 8",
+                r#"parameter Count: value "11" is greater than the maximum value of 10"#,
+                r#"parameter Zones: value "eu-west-1a,eu-west-1c" is not one of the allowed values: eu-west-1a, eu-west-1b"#,
+                "parameter Region: is not declared by the template",
+            ]
+        );
+
+        let errors = template.check_parameters(&[parameter("Count", "lots")]);
+        let errors: Vec<_> = errors
+            .into_iter()
+            .map(|error| (error.key, error.kind))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                ("Count".to_string(), ParameterErrorKind::NotANumber),
+                ("Name".to_string(), ParameterErrorKind::Missing),
+                ("Password".to_string(), ParameterErrorKind::Missing),
+                ("Zones".to_string(), ParameterErrorKind::Missing),
+            ]
+        );
+
+        // Previous values count as given, and can't be checked.
+        assert_eq!(
+            template.check_parameters(&[
+                crate::Parameter::previous("Name"),
+                crate::Parameter::previous("Password"),
+                crate::Parameter::previous("Count"),
+                crate::Parameter::previous("Zones"),
+            ]),
+            []
+        );
+        assert_eq!(
+            template.check_parameters(&[crate::Parameter::previous("Region")])[0].kind,
+            ParameterErrorKind::Unknown
+        );
+    }
+
+    #[test]
+    fn required_capabilities() {
+        let capabilities = |source: &str| Template::parse(source).unwrap().required_capabilities();
+
+        assert_eq!(
+            capabilities("Resources: {Topic: {Type: AWS::SNS::Topic}}"),
+            []
+        );
+        assert_eq!(
+            capabilities(
+                r"
+Resources:
+  Role:
+    Type: AWS::IAM::Role
+  Policy:
+    Type: AWS::IAM::ManagedPolicy
+    Properties:
+      Description: !Ref AWS::StackName
+"
+            ),
+            [Capability::Iam]
+        );
+        assert_eq!(
+            capabilities(
+                r"
+Resources:
+  Role:
+    Type: AWS::IAM::Role
+  User:
+    Type: AWS::IAM::User
+    Properties:
+      UserName: someone
+"
+            ),
+            [Capability::NamedIam]
+        );
+        assert_eq!(
+            capabilities(
+                r"
+Resources:
+  Nested:
+    Type: AWS::CloudFormation::Stack
+    Properties:
+      TemplateURL: https://example.com/template.json
+"
+            ),
+            [Capability::NamedIam]
+        );
+        assert_eq!(
+            capabilities(
+                r"
+Resources:
+  Fn::Transform:
+    Name: AWS::Include
+    Parameters:
+      Location: s3://bucket/snippet.yaml
+  Topic:
+    Type: AWS::SNS::Topic
+"
+            ),
+            [Capability::AutoExpand]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Inference of the capabilities required to apply a template.
+
+use super::Template;
+use crate::Capability;
+
+/// IAM resource types that require a capability, and the property that gives them a custom name.
+const IAM_RESOURCE_TYPES: &[(&str, Option<&str>)] = &[
+    ("AWS::IAM::AccessKey", None),
+    ("AWS::IAM::Group", Some("GroupName")),
+    ("AWS::IAM::InstanceProfile", Some("InstanceProfileName")),
+    ("AWS::IAM::ManagedPolicy", Some("ManagedPolicyName")),
+    ("AWS::IAM::Policy", None),
+    ("AWS::IAM::Role", Some("RoleName")),
+    ("AWS::IAM::User", Some("UserName")),
+    ("AWS::IAM::UserToGroupAddition", None),
+];
+
+pub(super) fn required(template: &Template) -> Vec<Capability> {
+    let mut iam = false;
+    let mut named_iam = false;
+    for resource in &template.resources {
+        // Nested stacks' templates can't be inspected, and may contain named IAM resources.
+        if resource.resource_type == "AWS::CloudFormation::Stack" {
+            named_iam = true;
+        }
+
+        let Some((_, name_property)) = IAM_RESOURCE_TYPES
+            .iter()
+            .find(|(resource_type, _)| *resource_type == resource.resource_type)
+        else {
+            continue;
+        };
+        iam = true;
+        if let (Some(properties), Some(name_property)) = (&resource.properties, name_property) {
+            if properties.get(name_property).is_some() {
+                named_iam = true;
+            }
+        }
+    }
+
+    let mut capabilities = Vec::new();
+    if named_iam {
+        capabilities.push(Capability::NamedIam);
+    } else if iam {
+        capabilities.push(Capability::Iam);
+    }
+    if template.uses_macros() {
+        capabilities.push(Capability::AutoExpand);
+    }
+    capabilities
+}
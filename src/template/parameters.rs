@@ -0,0 +1,120 @@
+//! Checking of input parameters against a template's declared parameters.
+
+use regex::Regex;
+
+use super::{Parameter, ParameterError, ParameterErrorKind, Template};
+
+pub(super) fn check(template: &Template, parameters: &[crate::Parameter]) -> Vec<ParameterError> {
+    let mut errors = Vec::new();
+
+    for input in parameters {
+        let Some(parameter) = template.parameter(&input.key) else {
+            errors.push(ParameterError {
+                key: input.key.clone(),
+                value: None,
+                constraint_description: None,
+                kind: ParameterErrorKind::Unknown,
+            });
+            continue;
+        };
+        // Previous values were checked when they were given.
+        if input.use_previous_value {
+            continue;
+        }
+        let value = &input.value;
+        errors.extend(violations(parameter, value).map(|kind| {
+            let is_constraint = !matches!(kind, ParameterErrorKind::NotANumber);
+            ParameterError {
+                key: input.key.clone(),
+                value: (!parameter.no_echo).then(|| value.clone()),
+                constraint_description: parameter
+                    .constraint_description
+                    .clone()
+                    .filter(|_| is_constraint),
+                kind,
+            }
+        }));
+    }
+
+    for parameter in &template.parameters {
+        let given = parameters
+            .iter()
+            .any(|input| input.key == parameter.logical_id);
+        if !given && parameter.default.is_none() {
+            errors.push(ParameterError {
+                key: parameter.logical_id.clone(),
+                value: None,
+                constraint_description: None,
+                kind: ParameterErrorKind::Missing,
+            });
+        }
+    }
+
+    errors
+}
+
+/// The constraints of `parameter` violated by `value`.
+fn violations<'a>(
+    parameter: &'a Parameter,
+    value: &'a str,
+) -> impl Iterator<Item = ParameterErrorKind> + 'a {
+    let parameter_type = parameter.parameter_type.as_str();
+
+    // The values of SSM parameter types are the names of SSM parameters, which we can't resolve.
+    let items: Vec<_> = if parameter_type.starts_with("AWS::SSM::Parameter::") {
+        Vec::new()
+    } else if parameter_type.starts_with("List<") || parameter_type == "CommaDelimitedList" {
+        value.split(',').map(str::trim).collect()
+    } else {
+        vec![value]
+    };
+    let is_number = matches!(parameter_type, "Number" | "List<Number>");
+
+    // Patterns must match the whole value, and invalid patterns are left for CloudFormation to
+    // report.
+    let pattern = parameter
+        .allowed_pattern
+        .as_ref()
+        .and_then(|pattern| Regex::new(&format!("^(?:{pattern})$")).ok());
+
+    items.into_iter().flat_map(move |item| {
+        let mut violations = Vec::new();
+        if let Some(allowed_values) = &parameter.allowed_values {
+            if !allowed_values.iter().any(|allowed| allowed == item) {
+                violations.push(ParameterErrorKind::NotAllowed {
+                    allowed_values: allowed_values.clone(),
+                });
+            }
+        }
+        if let (Some(allowed_pattern), Some(pattern)) = (&parameter.allowed_pattern, &pattern) {
+            if !pattern.is_match(item) {
+                violations.push(ParameterErrorKind::PatternMismatch {
+                    allowed_pattern: allowed_pattern.clone(),
+                });
+            }
+        }
+
+        let length = item.chars().count() as u64;
+        if let Some(min_length) = parameter.min_length.filter(|min| length < *min) {
+            violations.push(ParameterErrorKind::TooShort { min_length });
+        }
+        if let Some(max_length) = parameter.max_length.filter(|max| length > *max) {
+            violations.push(ParameterErrorKind::TooLong { max_length });
+        }
+
+        if is_number {
+            match item.parse::<f64>() {
+                Ok(number) => {
+                    if let Some(min_value) = parameter.min_value.filter(|min| number < *min) {
+                        violations.push(ParameterErrorKind::TooSmall { min_value });
+                    }
+                    if let Some(max_value) = parameter.max_value.filter(|max| number > *max) {
+                        violations.push(ParameterErrorKind::TooLarge { max_value });
+                    }
+                }
+                Err(_) => violations.push(ParameterErrorKind::NotANumber),
+            }
+        }
+        violations
+    })
+}
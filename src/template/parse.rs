@@ -0,0 +1,465 @@
+//! Parsing of JSON and YAML templates.
+
+use yaml_rust2::{
+    parser::{Event, Parser, Tag},
+    scanner::{Marker, TScalarStyle},
+};
+
+use super::{
+    Condition, Diagnostic, DiagnosticKind, Key, Location, Node, Output, Parameter, ParseError,
+    Resource, Severity, Template, Value,
+};
+
+pub(super) fn parse(source: &str) -> Result<Template, ParseError> {
+    let root = parse_node(source).map_err(|diagnostic| ParseError {
+        diagnostics: vec![diagnostic],
+    })?;
+
+    let mut warnings = Vec::new();
+    if source.trim_start().starts_with('{') {
+        if let Err(error) = serde_json::from_str::<serde_json::Value>(source) {
+            warnings.push(Diagnostic {
+                location: Location {
+                    line: error.line(),
+                    column: error.column(),
+                },
+                severity: Severity::Warning,
+                kind: DiagnosticKind::InvalidJson {
+                    message: error.to_string(),
+                },
+            });
+        }
+    }
+
+    let mut converter = Converter::default();
+    let mut template = converter.template(&root, warnings);
+    template.uses_macros = !template.transform.is_empty() || contains_transform(&root);
+    if converter.diagnostics.is_empty() {
+        Ok(template)
+    } else {
+        Err(ParseError {
+            diagnostics: converter.diagnostics,
+        })
+    }
+}
+
+/// Whether `node` or any of its children use `Fn::Transform`.
+fn contains_transform(node: &Node) -> bool {
+    match &node.value {
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => false,
+        Value::Sequence(items) => items.iter().any(contains_transform),
+        Value::Mapping(entries) => entries
+            .iter()
+            .any(|(key, value)| key.name == "Fn::Transform" || contains_transform(value)),
+    }
+}
+
+fn location(marker: Marker) -> Location {
+    Location {
+        line: marker.line(),
+        column: marker.col() + 1,
+    }
+}
+
+fn syntax_error(location: Location, message: impl Into<String>) -> Diagnostic {
+    Diagnostic {
+        location,
+        severity: Severity::Error,
+        kind: DiagnosticKind::Syntax {
+            message: message.into(),
+        },
+    }
+}
+
+/// Parse a YAML document into a [`Node`].
+fn parse_node(source: &str) -> Result<Node, Diagnostic> {
+    let mut events = Events(Parser::new_from_str(source));
+    loop {
+        match events.next()? {
+            (Event::StreamStart | Event::DocumentStart, _) => {}
+            (Event::StreamEnd, marker) => {
+                return Err(syntax_error(location(marker), "template is empty"))
+            }
+            (event, marker) => {
+                let root = events.node(event, marker)?;
+                match events.next()? {
+                    (Event::DocumentEnd, _) => {}
+                    (_, marker) => {
+                        return Err(syntax_error(location(marker), "expected end of document"))
+                    }
+                }
+                return match events.next()? {
+                    (Event::StreamEnd, _) => Ok(root),
+                    (_, marker) => Err(syntax_error(
+                        location(marker),
+                        "templates must contain a single document",
+                    )),
+                };
+            }
+        }
+    }
+}
+
+struct Events<'a>(Parser<std::str::Chars<'a>>);
+
+impl Events<'_> {
+    fn next(&mut self) -> Result<(Event, Marker), Diagnostic> {
+        self.0
+            .next_token()
+            .map_err(|error| syntax_error(location(*error.marker()), error.info()))
+    }
+
+    fn node(&mut self, event: Event, marker: Marker) -> Result<Node, Diagnostic> {
+        let mut location = location(marker);
+        let (value, tag) = match event {
+            Event::Scalar(value, style, _, tag) => (scalar(value, style, tag.as_ref()), tag),
+            Event::SequenceStart(_, tag) => {
+                let mut items = Vec::new();
+                loop {
+                    match self.next()? {
+                        (Event::SequenceEnd, _) => break,
+                        (event, marker) => items.push(self.node(event, marker)?),
+                    }
+                }
+                (Value::Sequence(items), tag)
+            }
+            Event::MappingStart(_, tag) => {
+                let mut entries = Vec::new();
+                loop {
+                    match self.next()? {
+                        (Event::MappingEnd, _) => break,
+                        (Event::Scalar(name, ..), marker) => {
+                            let key = Key {
+                                name,
+                                location: self::location(marker),
+                            };
+                            let (event, marker) = self.next()?;
+                            entries.push((key, self.node(event, marker)?));
+                        }
+                        (_, marker) => {
+                            return Err(syntax_error(
+                                self::location(marker),
+                                "mapping keys must be strings",
+                            ))
+                        }
+                    }
+                }
+                // The start of a block mapping is only reported after its first key.
+                if let Some((key, _)) = entries.first() {
+                    location = location.min(key.location);
+                }
+                (Value::Mapping(entries), tag)
+            }
+            Event::Alias(_) => {
+                return Err(syntax_error(location, "YAML aliases are not supported"));
+            }
+            event => {
+                return Err(syntax_error(location, format!("unexpected {event:?}")));
+            }
+        };
+        Ok(intrinsic(Node { value, location }, tag))
+    }
+}
+
+/// Resolve the value of a scalar, using the YAML core schema for plain scalars.
+fn scalar(value: String, style: TScalarStyle, tag: Option<&Tag>) -> Value {
+    if style != TScalarStyle::Plain || tag.is_some_and(|tag| tag.suffix == "str") {
+        return Value::String(value);
+    }
+    match value.as_str() {
+        "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+        "true" | "True" | "TRUE" => Value::Bool(true),
+        "false" | "False" | "FALSE" => Value::Bool(false),
+        _ if is_number(&value) => Value::Number(value),
+        _ => Value::String(value),
+    }
+}
+
+fn is_number(value: &str) -> bool {
+    value.bytes().any(|byte| byte.is_ascii_digit())
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || b"+-.eE".contains(&byte))
+        && value.parse::<f64>().is_ok()
+}
+
+/// Convert the short form of an intrinsic function (e.g. `!Ref Bucket`) into the full form.
+fn intrinsic(node: Node, tag: Option<Tag>) -> Node {
+    let function = match tag {
+        Some(Tag { handle, suffix }) if handle == "!" => suffix,
+        _ => return node,
+    };
+    let name = match function.as_str() {
+        "Ref" | "Condition" => function,
+        _ => format!("Fn::{function}"),
+    };
+    let location = node.location;
+    let argument = match (name.as_str(), node.value) {
+        // The short form of `Fn::GetAtt` can be written as `!GetAtt Resource.Attribute`.
+        ("Fn::GetAtt", Value::String(argument)) if argument.contains('.') => {
+            let (resource, attribute) = argument.split_once('.').unwrap();
+            Node {
+                value: Value::Sequence(vec![
+                    Node {
+                        value: Value::String(resource.to_string()),
+                        location,
+                    },
+                    Node {
+                        value: Value::String(attribute.to_string()),
+                        location,
+                    },
+                ]),
+                location,
+            }
+        }
+        (_, value) => Node { value, location },
+    };
+    Node {
+        value: Value::Mapping(vec![(Key { name, location }, argument)]),
+        location,
+    }
+}
+
+/// Conversion of parsed [`Node`]s into a [`Template`], collecting any structural problems.
+#[derive(Default)]
+struct Converter {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Converter {
+    fn malformed(&mut self, location: Location, message: String) {
+        self.diagnostics.push(Diagnostic {
+            location,
+            severity: Severity::Error,
+            kind: DiagnosticKind::Malformed { message },
+        });
+    }
+
+    fn template(&mut self, root: &Node, warnings: Vec<Diagnostic>) -> Template {
+        let mut template = Template {
+            format_version: None,
+            description: None,
+            transform: Vec::new(),
+            parameters: Vec::new(),
+            conditions: Vec::new(),
+            resources: Vec::new(),
+            outputs: Vec::new(),
+            warnings,
+            uses_macros: false,
+        };
+        for (key, node) in self.entries(root, "template") {
+            match key.name.as_str() {
+                "AWSTemplateFormatVersion" => {
+                    template.format_version = self.string(node, "AWSTemplateFormatVersion");
+                }
+                "Description" => template.description = self.string(node, "Description"),
+                "Transform" => template.transform = self.strings(node, "Transform"),
+                "Parameters" => {
+                    for (key, node) in self.section(node, "Parameters") {
+                        template.parameters.extend(self.parameter(key, node));
+                    }
+                }
+                "Conditions" => {
+                    template
+                        .conditions
+                        .extend(
+                            self.section(node, "Conditions")
+                                .map(|(key, node)| Condition {
+                                    logical_id: key.name.clone(),
+                                    location: key.location,
+                                    definition: node.clone(),
+                                }),
+                        );
+                }
+                "Resources" => {
+                    for (key, node) in self.section(node, "Resources") {
+                        template.resources.extend(self.resource(key, node));
+                    }
+                }
+                "Outputs" => {
+                    for (key, node) in self.section(node, "Outputs") {
+                        template.outputs.extend(self.output(key, node));
+                    }
+                }
+                _ => {}
+            }
+        }
+        template
+    }
+
+    fn parameter(&mut self, key: &Key, node: &Node) -> Option<Parameter> {
+        let what = format!("Parameters.{}", key.name);
+        let mut parameter = Parameter {
+            logical_id: key.name.clone(),
+            location: key.location,
+            parameter_type: String::new(),
+            description: None,
+            default: None,
+            allowed_values: None,
+            allowed_pattern: None,
+            constraint_description: None,
+            min_length: None,
+            max_length: None,
+            min_value: None,
+            max_value: None,
+            no_echo: false,
+        };
+        let mut parameter_type = None;
+        for (key, node) in self.entries(node, &what) {
+            let what = format!("{what}.{}", key.name);
+            match key.name.as_str() {
+                "Type" => parameter_type = self.string(node, &what),
+                "Description" => parameter.description = self.string(node, &what),
+                "Default" => parameter.default = self.string(node, &what),
+                "AllowedValues" => parameter.allowed_values = Some(self.strings(node, &what)),
+                "AllowedPattern" => parameter.allowed_pattern = self.string(node, &what),
+                "ConstraintDescription" => {
+                    parameter.constraint_description = self.string(node, &what);
+                }
+                "MinLength" => parameter.min_length = self.number(node, &what),
+                "MaxLength" => parameter.max_length = self.number(node, &what),
+                "MinValue" => parameter.min_value = self.number(node, &what),
+                "MaxValue" => parameter.max_value = self.number(node, &what),
+                "NoEcho" => parameter.no_echo = self.boolean(node, &what).unwrap_or_default(),
+                _ => {}
+            }
+        }
+        parameter.parameter_type = self.required(parameter_type, key, node, &what, "Type")?;
+        Some(parameter)
+    }
+
+    fn resource(&mut self, key: &Key, node: &Node) -> Option<Resource> {
+        let what = format!("Resources.{}", key.name);
+        let mut resource = Resource {
+            logical_id: key.name.clone(),
+            location: key.location,
+            resource_type: String::new(),
+            condition: None,
+            depends_on: Vec::new(),
+            properties: None,
+            metadata: None,
+        };
+        let mut resource_type = None;
+        for (key, node) in self.entries(node, &what) {
+            let what = format!("{what}.{}", key.name);
+            match key.name.as_str() {
+                "Type" => resource_type = self.string(node, &what),
+                "Condition" => resource.condition = self.string(node, &what),
+                "DependsOn" => resource.depends_on = self.strings(node, &what),
+                "Properties" => resource.properties = Some(node.clone()),
+                "Metadata" => resource.metadata = Some(node.clone()),
+                _ => {}
+            }
+        }
+        resource.resource_type = self.required(resource_type, key, node, &what, "Type")?;
+        Some(resource)
+    }
+
+    fn output(&mut self, key: &Key, node: &Node) -> Option<Output> {
+        let what = format!("Outputs.{}", key.name);
+        let (mut value, mut description, mut condition, mut export_name) = (None, None, None, None);
+        for (key, node) in self.entries(node, &what) {
+            let what = format!("{what}.{}", key.name);
+            match key.name.as_str() {
+                "Value" => value = Some(node.clone()),
+                "Description" => description = self.string(node, &what),
+                "Condition" => condition = self.string(node, &what),
+                "Export" => match node.get("Name") {
+                    Some(name) => export_name = Some(name.clone()),
+                    None => self.malformed(node.location, format!("{what} must have a Name")),
+                },
+                _ => {}
+            }
+        }
+        Some(Output {
+            logical_id: key.name.clone(),
+            location: key.location,
+            value: self.required(value, key, node, &what, "Value")?,
+            description,
+            condition,
+            export_name,
+        })
+    }
+
+    /// Report a missing `field` of the entry with the given `key`, if it's a mapping.
+    fn required<T>(
+        &mut self,
+        value: Option<T>,
+        key: &Key,
+        node: &Node,
+        what: &str,
+        field: &str,
+    ) -> Option<T> {
+        if value.is_none() && matches!(node.value, Value::Mapping(_)) {
+            self.malformed(key.location, format!("{what} must have a {field}"));
+        }
+        value
+    }
+
+    /// The entries of a section, excluding any `Fn::Transform`s (e.g. of `AWS::Include`).
+    fn section<'a>(
+        &mut self,
+        node: &'a Node,
+        what: &str,
+    ) -> impl Iterator<Item = &'a (Key, Node)> + 'a {
+        self.entries(node, what)
+            .iter()
+            .filter(|(key, _)| key.name != "Fn::Transform")
+    }
+
+    fn entries<'a>(&mut self, node: &'a Node, what: &str) -> &'a [(Key, Node)] {
+        if let Value::Mapping(entries) = &node.value {
+            entries
+        } else {
+            self.malformed(node.location, format!("{what} must be a mapping"));
+            &[]
+        }
+    }
+
+    fn string(&mut self, node: &Node, what: &str) -> Option<String> {
+        match &node.value {
+            Value::String(value) | Value::Number(value) => Some(value.clone()),
+            Value::Bool(value) => Some(value.to_string()),
+            _ => {
+                self.malformed(node.location, format!("{what} must be a string"));
+                None
+            }
+        }
+    }
+
+    /// Read a string or a sequence of strings.
+    fn strings(&mut self, node: &Node, what: &str) -> Vec<String> {
+        match &node.value {
+            Value::Sequence(items) => items
+                .iter()
+                .filter_map(|item| self.string(item, what))
+                .collect(),
+            _ => self.string(node, what).into_iter().collect(),
+        }
+    }
+
+    /// Read a number, which may also be written as a string.
+    fn number<T: std::str::FromStr>(&mut self, node: &Node, what: &str) -> Option<T> {
+        let parsed = match &node.value {
+            Value::String(value) | Value::Number(value) => value.parse().ok(),
+            _ => None,
+        };
+        if parsed.is_none() {
+            self.malformed(node.location, format!("{what} must be a number"));
+        }
+        parsed
+    }
+
+    /// Read a boolean, which may also be written as a string.
+    fn boolean(&mut self, node: &Node, what: &str) -> Option<bool> {
+        match &node.value {
+            Value::Bool(value) => Some(*value),
+            Value::String(value) if value.eq_ignore_ascii_case("true") => Some(true),
+            Value::String(value) if value.eq_ignore_ascii_case("false") => Some(false),
+            _ => {
+                self.malformed(node.location, format!("{what} must be a boolean"));
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Offline validation of templates.
+
+use std::collections::HashSet;
+
+use super::{Diagnostic, DiagnosticKind, Location, Node, Severity, Template, Value};
+
+/// Pseudo parameters, which can be referred to by `Ref` in any template.
+const PSEUDO_PARAMETERS: &[&str] = &[
+    "AWS::AccountId",
+    "AWS::NotificationARNs",
+    "AWS::NoValue",
+    "AWS::Partition",
+    "AWS::Region",
+    "AWS::StackId",
+    "AWS::StackName",
+    "AWS::URLSuffix",
+];
+
+pub(super) fn validate(template: &Template) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        template,
+        // Macros may add resources and conditions that we can't see.
+        reference_severity: if template.uses_macros {
+            Severity::Warning
+        } else {
+            Severity::Error
+        },
+        diagnostics: template.warnings.clone(),
+    };
+    if template.resources.is_empty() {
+        validator.report(
+            Location { line: 1, column: 1 },
+            Severity::Error,
+            DiagnosticKind::Malformed {
+                message: "template must have at least one resource".to_string(),
+            },
+        );
+    }
+    validator.logical_ids();
+    validator.references();
+    validator.diagnostics
+}
+
+struct Validator<'a> {
+    template: &'a Template,
+    reference_severity: Severity,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, location: Location, severity: Severity, kind: DiagnosticKind) {
+        self.diagnostics.push(Diagnostic {
+            location,
+            severity,
+            kind,
+        });
+    }
+
+    fn logical_ids(&mut self) {
+        let template = self.template;
+
+        // Parameters and resources share a namespace, since either can be the target of a `Ref`.
+        self.duplicates(
+            template
+                .parameters
+                .iter()
+                .map(|parameter| (&parameter.logical_id, parameter.location))
+                .chain(
+                    template
+                        .resources
+                        .iter()
+                        .map(|resource| (&resource.logical_id, resource.location)),
+                ),
+        );
+        self.duplicates(
+            template
+                .conditions
+                .iter()
+                .map(|condition| (&condition.logical_id, condition.location)),
+        );
+        self.duplicates(
+            template
+                .outputs
+                .iter()
+                .map(|output| (&output.logical_id, output.location)),
+        );
+    }
+
+    fn duplicates<'a>(&mut self, logical_ids: impl Iterator<Item = (&'a String, Location)>) {
+        let mut seen = HashSet::new();
+        for (logical_id, location) in logical_ids {
+            if !seen.insert(logical_id) {
+                self.report(
+                    location,
+                    Severity::Error,
+                    DiagnosticKind::DuplicateLogicalId {
+                        logical_id: logical_id.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn references(&mut self) {
+        let template = self.template;
+
+        for condition in &template.conditions {
+            self.walk(&condition.definition, true);
+        }
+
+        for resource in &template.resources {
+            if let Some(condition) = &resource.condition {
+                self.condition(condition, resource.location);
+            }
+            for target in &resource.depends_on {
+                if template.resource(target).is_none() {
+                    self.report(
+                        resource.location,
+                        self.reference_severity,
+                        DiagnosticKind::UnresolvedDependsOn {
+                            target: target.clone(),
+                        },
+                    );
+                }
+            }
+            if let Some(properties) = &resource.properties {
+                self.walk(properties, false);
+            }
+        }
+
+        for output in &template.outputs {
+            if let Some(condition) = &output.condition {
+                self.condition(condition, output.location);
+            }
+            self.walk(&output.value, false);
+            if let Some(export_name) = &output.export_name {
+                self.walk(export_name, false);
+            }
+        }
+    }
+
+    /// Check the intrinsic functions in `node` and its children.
+    ///
+    /// `in_conditions` indicates that `node` is part of the `Conditions` section, in which
+    /// `{"Condition": ...}` refers to another condition.
+    fn walk(&mut self, node: &Node, in_conditions: bool) {
+        match &node.value {
+            Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {}
+            Value::Sequence(items) => {
+                for item in items {
+                    self.walk(item, in_conditions);
+                }
+            }
+            Value::Mapping(entries) => {
+                if let [(key, argument)] = entries.as_slice() {
+                    self.intrinsic(&key.name, argument, in_conditions);
+                }
+                for (_, value) in entries {
+                    self.walk(value, in_conditions);
+                }
+            }
+        }
+    }
+
+    fn intrinsic(&mut self, function: &str, argument: &Node, in_conditions: bool) {
+        match (function, &argument.value) {
+            ("Ref", Value::String(target)) => self.reference(target, argument.location),
+            ("Fn::GetAtt", Value::Sequence(items)) => {
+                if let Some(target) = items.first().and_then(Node::as_str) {
+                    self.attribute(target, argument.location);
+                }
+            }
+            ("Fn::GetAtt", Value::String(name)) => {
+                let target = name
+                    .split_once('.')
+                    .map_or(name.as_str(), |(target, _)| target);
+                self.attribute(target, argument.location);
+            }
+            ("Fn::Sub", Value::String(string)) => {
+                self.substitutions(string, &[], argument.location);
+            }
+            ("Fn::Sub", Value::Sequence(items)) => {
+                if let Some(string) = items.first().and_then(Node::as_str) {
+                    let variables = match items.get(1).map(|variables| &variables.value) {
+                        Some(Value::Mapping(entries)) => {
+                            entries.iter().map(|(key, _)| key.name.as_str()).collect()
+                        }
+                        _ => Vec::new(),
+                    };
+                    self.substitutions(string, &variables, argument.location);
+                }
+            }
+            ("Fn::If", Value::Sequence(items)) => {
+                if let Some(condition) = items.first() {
+                    if let Some(name) = condition.as_str() {
+                        self.condition(name, condition.location);
+                    }
+                }
+            }
+            ("Condition", Value::String(name)) if in_conditions => {
+                self.condition(name, argument.location);
+            }
+            _ => {}
+        }
+    }
+
+    /// Check the `${...}` references in a `Fn::Sub` string.
+    fn substitutions(&mut self, string: &str, variables: &[&str], location: Location) {
+        let mut rest = string;
+        while let Some(start) = rest.find("${") {
+            rest = &rest[start + 2..];
+            let Some(end) = rest.find('}') else {
+                break;
+            };
+            let name = rest[..end].trim();
+            rest = &rest[end + 1..];
+
+            // `${!Literal}` is written as `${Literal}` without substitution.
+            if name.starts_with('!') || variables.contains(&name) {
+                continue;
+            }
+            match name.split_once('.') {
+                Some((target, _)) => self.attribute(target, location),
+                None => self.reference(name, location),
+            }
+        }
+    }
+
+    fn reference(&mut self, target: &str, location: Location) {
+        let template = self.template;
+        if PSEUDO_PARAMETERS.contains(&target)
+            || template.parameter(target).is_some()
+            || template.resource(target).is_some()
+        {
+            return;
+        }
+        self.report(
+            location,
+            self.reference_severity,
+            DiagnosticKind::UnresolvedRef {
+                target: target.to_string(),
+            },
+        );
+    }
+
+    fn attribute(&mut self, target: &str, location: Location) {
+        if self.template.resource(target).is_none() {
+            self.report(
+                location,
+                self.reference_severity,
+                DiagnosticKind::UnresolvedGetAtt {
+                    target: target.to_string(),
+                },
+            );
+        }
+    }
+
+    fn condition(&mut self, name: &str, location: Location) {
+        let defined = self
+            .template
+            .conditions
+            .iter()
+            .any(|condition| condition.logical_id == name);
+        if !defined {
+            self.report(
+                location,
+                self.reference_severity,
+                DiagnosticKind::UndefinedCondition {
+                    condition: name.to_string(),
+                },
+            );
+        }
+    }
+}
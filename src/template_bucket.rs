@@ -0,0 +1,419 @@
+//! Staging of large templates in S3.
+
+use std::time::Duration;
+
+use aws_sdk_s3::{presigning::PresigningConfig, primitives::ByteStream};
+use sha2::{Digest, Sha256};
+
+use crate::apply_stack::ResolvedTemplateSource;
+
+/// The maximum size of a template body that can be passed inline, in bytes.
+pub(crate) const MAX_INLINE_TEMPLATE_BODY: usize = 51_200;
+
+/// An S3 location in which to stage templates that are too large to be passed inline.
+///
+/// See [`ApplyStackInput::template_bucket`](crate::ApplyStackInput::template_bucket) for more
+/// information.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemplateBucket {
+    /// The name of the bucket.
+    ///
+    /// The bucket should be in the same region as the stack.
+    pub bucket: String,
+
+    /// A prefix for the keys of staged templates, e.g. `cloudformation/`.
+    ///
+    /// The prefix is used verbatim, so it should usually end with a `/`.
+    pub prefix: String,
+}
+
+impl TemplateBucket {
+    /// Construct a template bucket for the given `bucket`, with no prefix.
+    pub fn new(bucket: impl Into<String>) -> Self {
+        Self {
+            bucket: bucket.into(),
+            prefix: String::new(),
+        }
+    }
+
+    /// Set the value for `prefix`.
+    ///
+    /// **Note:** this consumes and returns `self` for chaining.
+    #[must_use]
+    pub fn set_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// The key under which a template with the given `body` is staged.
+    ///
+    /// Keys are derived from the SHA-256 digest of the body, so re-applying an unchanged template
+    /// reuses the existing object.
+    fn key(&self, body: &str) -> String {
+        format!("{}{:x}.template", self.prefix, Sha256::digest(body))
+    }
+
+    /// Upload an inline template `source` if it's too large to be passed inline.
+    ///
+    /// Other sources are returned unchanged, as are inline bodies within the limit. The URL of a
+    /// staged template is resolved by the S3 `client`, so it respects the client's region,
+    /// endpoint URL, and addressing style.
+    pub(crate) async fn stage(
+        &self,
+        client: &aws_sdk_s3::Client,
+        source: ResolvedTemplateSource,
+    ) -> Result<ResolvedTemplateSource, Box<dyn std::error::Error + Send + Sync>> {
+        let body = match source {
+            ResolvedTemplateSource::Inline { body } if body.len() > MAX_INLINE_TEMPLATE_BODY => {
+                body
+            }
+            source => return Ok(source),
+        };
+
+        let key = self.key(&body);
+        let exists = match client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+        {
+            Ok(_) => true,
+            Err(error)
+                if error.as_service_error().is_some_and(
+                    aws_sdk_s3::operation::head_object::HeadObjectError::is_not_found,
+                ) =>
+            {
+                false
+            }
+            Err(error) => return Err(error.into()),
+        };
+        if !exists {
+            client
+                .put_object()
+                .bucket(&self.bucket)
+                .key(&key)
+                .body(ByteStream::from(body.into_bytes()))
+                .send()
+                .await?;
+        }
+
+        Ok(ResolvedTemplateSource::S3 {
+            url: self.url(client, &key).await?,
+        })
+    }
+
+    /// The URL of the object with the given `key`, as resolved by the S3 `client`.
+    ///
+    /// The SDK doesn't expose endpoint resolution directly, so this presigns a `GetObject` request
+    /// and strips the signature from its URL.
+    async fn url(
+        &self,
+        client: &aws_sdk_s3::Client,
+        key: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let request = client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(PresigningConfig::expires_in(Duration::from_secs(60))?)
+            .await?;
+        let url = request.uri();
+        Ok(url.split_once('?').map_or(url, |(url, _)| url).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use assert_matches::assert_matches;
+    use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+    use aws_smithy_http_client::test_util::infallible_client_fn;
+    use aws_smithy_types::body::SdkBody;
+
+    use super::{TemplateBucket, MAX_INLINE_TEMPLATE_BODY};
+    use crate::{
+        apply_stack::{create_change_set_internal, ResolvedTemplateSource, S3Client},
+        ApplyStackError, ApplyStackInput, PollingConfig, TemplateSource,
+    };
+
+    type Objects = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// An S3 client backed by an in-memory S3 stand-in, with a path-style local endpoint.
+    fn s3_stand_in() -> (aws_sdk_s3::Client, Objects) {
+        s3_stand_in_with(|config| {
+            config
+                .endpoint_url("http://localhost:4566")
+                .force_path_style(true)
+                .region(Region::new("eu-west-1"))
+        })
+    }
+
+    /// An S3 client backed by an in-memory S3 stand-in, with additional `configure`ation.
+    ///
+    /// Objects are keyed by their URL (without query), regardless of the client's endpoint.
+    fn s3_stand_in_with(
+        configure: impl FnOnce(aws_sdk_s3::config::Builder) -> aws_sdk_s3::config::Builder,
+    ) -> (aws_sdk_s3::Client, Objects) {
+        let objects = Objects::default();
+        let http_client = {
+            let objects = Arc::clone(&objects);
+            infallible_client_fn(move |request: http::Request<SdkBody>| {
+                let uri = request.uri();
+                let url = format!(
+                    "{}://{}{}",
+                    uri.scheme_str().unwrap(),
+                    uri.authority().unwrap(),
+                    uri.path()
+                );
+                let mut objects = objects.lock().unwrap();
+                let status = match request.method().as_str() {
+                    "HEAD" if objects.contains_key(&url) => 200,
+                    "HEAD" => 404,
+                    "PUT" => {
+                        let body = request.body().bytes().unwrap().to_vec();
+                        objects.insert(url, body);
+                        200
+                    }
+                    method => panic!("unexpected {} {}", method, url),
+                };
+                http::Response::builder()
+                    .status(status)
+                    .body(SdkBody::empty())
+                    .unwrap()
+            })
+        };
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .credentials_provider(Credentials::new("AKIDTEST", "secret", None, None, "test"))
+            .http_client(http_client);
+        let config = configure(config).build();
+        (aws_sdk_s3::Client::from_conf(config), objects)
+    }
+
+    fn large_body() -> String {
+        format!(
+            r#"{{"Description":"{}"}}"#,
+            "x".repeat(MAX_INLINE_TEMPLATE_BODY)
+        )
+    }
+
+    #[tokio::test]
+    async fn small_templates_are_inline() {
+        let (client, objects) = s3_stand_in();
+        let bucket = TemplateBucket::new("templates");
+
+        let source = bucket
+            .stage(
+                &client,
+                ResolvedTemplateSource::Inline {
+                    body: "{}".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        assert_matches!(source, ResolvedTemplateSource::Inline { body } if body == "{}");
+        assert!(objects.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn large_templates_are_uploaded() {
+        let (client, objects) = s3_stand_in();
+        let bucket = TemplateBucket::new("templates").set_prefix("cloudformation/");
+        let body = large_body();
+        let key = bucket.key(&body);
+        assert!(key.starts_with("cloudformation/"));
+
+        let source = bucket
+            .stage(
+                &client,
+                ResolvedTemplateSource::Inline { body: body.clone() },
+            )
+            .await
+            .unwrap();
+        assert_matches!(source, ResolvedTemplateSource::S3 { url } => {
+            assert_eq!(url, format!("http://localhost:4566/templates/{key}"));
+            assert_eq!(objects.lock().unwrap().get(&url), Some(&body.into_bytes()));
+        });
+    }
+
+    #[tokio::test]
+    async fn staged_template_urls_follow_the_client_endpoint() {
+        let (client, objects) = s3_stand_in_with(|config| config.region(Region::new("cn-north-1")));
+        let bucket = TemplateBucket::new("templates").set_prefix("cloud formation/");
+        let body = large_body();
+        let key = bucket.key(&body).replace(' ', "%20");
+
+        let source = bucket
+            .stage(
+                &client,
+                ResolvedTemplateSource::Inline { body: body.clone() },
+            )
+            .await
+            .unwrap();
+        assert_matches!(source, ResolvedTemplateSource::S3 { url } => {
+            assert_eq!(
+                url,
+                format!("https://templates.s3.cn-north-1.amazonaws.com.cn/{key}")
+            );
+            assert_eq!(objects.lock().unwrap().get(&url), Some(&body.into_bytes()));
+        });
+    }
+
+    #[tokio::test]
+    async fn staged_templates_are_reused() {
+        let (client, objects) = s3_stand_in();
+        let bucket = TemplateBucket::new("templates");
+        let body = large_body();
+        let key = format!("http://localhost:4566/templates/{}", bucket.key(&body));
+        objects
+            .lock()
+            .unwrap()
+            .insert(key.clone(), b"already staged".to_vec());
+
+        let source = bucket
+            .stage(&client, ResolvedTemplateSource::Inline { body })
+            .await
+            .unwrap();
+        assert_matches!(source, ResolvedTemplateSource::S3 { .. });
+        assert_eq!(
+            objects.lock().unwrap().get(&key),
+            Some(&b"already staged".to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_templates_are_not_staged() {
+        let http_client = infallible_client_fn(
+            |request: http::Request<SdkBody>| -> http::Response<SdkBody> {
+                panic!("unexpected request to {}", request.uri())
+            },
+        );
+        let cloudformation = aws_sdk_cloudformation::Client::from_conf(
+            aws_sdk_cloudformation::Config::builder()
+                .behavior_version(aws_sdk_cloudformation::config::BehaviorVersion::latest())
+                .http_client(http_client.clone())
+                .build(),
+        );
+        let s3 = aws_sdk_s3::Client::from_conf(
+            aws_sdk_s3::Config::builder()
+                .behavior_version(BehaviorVersion::latest())
+                .http_client(http_client)
+                .build(),
+        );
+
+        let body = format!(
+            r#"{{
+                "Description": "{}",
+                "Resources": {{"Topic": {{"Type": "AWS::SNS::Topic", "Condition": "Never"}}}}
+            }}"#,
+            "x".repeat(MAX_INLINE_TEMPLATE_BODY)
+        );
+        let input = ApplyStackInput::new("stack", TemplateSource::inline(body))
+            .set_template_bucket(TemplateBucket::new("templates"))
+            .set_validate_template(true);
+        let result = create_change_set_internal(
+            &cloudformation,
+            &S3Client(s3),
+            PollingConfig::default(),
+            input,
+        )
+        .await;
+        assert_matches!(result.err(), Some(ApplyStackError::InvalidTemplate { .. }));
+    }
+
+    #[tokio::test]
+    async fn previous_parameters_of_large_templates_are_summarised_after_staging() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = {
+            let requests = Arc::clone(&requests);
+            infallible_client_fn(move |request: http::Request<SdkBody>| {
+                if request.method() == "HEAD" {
+                    // The template is already staged.
+                    return http::Response::builder()
+                        .status(200)
+                        .body(SdkBody::empty())
+                        .unwrap();
+                }
+                let body = String::from_utf8(request.body().bytes().unwrap().to_vec()).unwrap();
+                let (status, response) = if body.contains("Action=DescribeStacks") {
+                    (
+                        200,
+                        "<DescribeStacksResponse><DescribeStacksResult><Stacks><member>\
+                         <StackName>stack</StackName><StackStatus>UPDATE_COMPLETE</StackStatus>\
+                         <Parameters>\
+                         <member><ParameterKey>Secret</ParameterKey></member>\
+                         <member><ParameterKey>Removed</ParameterKey></member>\
+                         </Parameters>\
+                         </member></Stacks></DescribeStacksResult></DescribeStacksResponse>",
+                    )
+                } else if body.contains("Action=GetTemplateSummary") {
+                    (
+                        200,
+                        "<GetTemplateSummaryResponse><GetTemplateSummaryResult><Parameters>\
+                         <member><ParameterKey>Secret</ParameterKey></member>\
+                         </Parameters></GetTemplateSummaryResult></GetTemplateSummaryResponse>",
+                    )
+                } else {
+                    (
+                        400,
+                        "<ErrorResponse><Error><Type>Sender</Type><Code>ValidationError</Code>\
+                         <Message>Stop here</Message></Error></ErrorResponse>",
+                    )
+                };
+                requests.lock().unwrap().push(body);
+                http::Response::builder()
+                    .status(status)
+                    .body(SdkBody::from(response))
+                    .unwrap()
+            })
+        };
+        let credentials = Credentials::new("AKIDTEST", "secret", None, None, "test");
+        let region = Region::new("eu-west-1");
+        let cloudformation = aws_sdk_cloudformation::Client::from_conf(
+            aws_sdk_cloudformation::Config::builder()
+                .behavior_version(aws_sdk_cloudformation::config::BehaviorVersion::latest())
+                .credentials_provider(credentials.clone())
+                .region(region.clone())
+                .http_client(http_client.clone())
+                .build(),
+        );
+        let s3 = aws_sdk_s3::Client::from_conf(
+            aws_sdk_s3::Config::builder()
+                .behavior_version(BehaviorVersion::latest())
+                .credentials_provider(credentials)
+                .region(region)
+                .http_client(http_client)
+                .build(),
+        );
+
+        // The template isn't parsed, so it needn't be valid.
+        let body = "x".repeat(MAX_INLINE_TEMPLATE_BODY + 1);
+        let input = ApplyStackInput::new("stack", TemplateSource::inline(body))
+            .set_template_bucket(TemplateBucket::new("templates"))
+            .set_use_previous_parameters(true);
+        let result = create_change_set_internal(
+            &cloudformation,
+            &S3Client(s3),
+            PollingConfig::default(),
+            input,
+        )
+        .await;
+        assert!(result.is_err());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].contains("Action=GetTemplateSummary"));
+        assert!(requests[1].contains("TemplateURL="));
+        assert!(!requests[1].contains("TemplateBody="));
+        assert!(requests[2].contains("Action=CreateChangeSet"));
+        assert!(requests[2].contains("Parameters.member.1.ParameterKey=Secret"));
+        assert!(requests[2].contains("Parameters.member.1.UsePreviousValue=true"));
+        assert!(!requests[2].contains("Removed"));
+    }
+}
@@ -0,0 +1,46 @@
+//! Handling for API responses that don't match our expectations.
+
+use std::fmt;
+
+/// An API response didn't have the shape or values we expected.
+///
+/// The SDK models almost every field as optional, and CloudFormation adds new status values from
+/// time to time. Rather than panicking when a response surprises us, conversions return this error
+/// so that it can be surfaced from the operation that made the request.
+#[derive(Debug)]
+pub(crate) struct UnexpectedResponse {
+    pub(crate) api: &'static str,
+    pub(crate) detail: String,
+}
+
+impl UnexpectedResponse {
+    pub(crate) fn new(api: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            api,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for UnexpectedResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unexpected response from {}: {}", self.api, self.detail)
+    }
+}
+
+/// Convenience for converting missing or invalid response fields into [`UnexpectedResponse`]s.
+pub(crate) trait Expected<T> {
+    fn expected(self, api: &'static str, detail: &str) -> Result<T, UnexpectedResponse>;
+}
+
+impl<T> Expected<T> for Option<T> {
+    fn expected(self, api: &'static str, detail: &str) -> Result<T, UnexpectedResponse> {
+        self.ok_or_else(|| UnexpectedResponse::new(api, detail))
+    }
+}
+
+impl<T, E> Expected<T> for Result<T, E> {
+    fn expected(self, api: &'static str, detail: &str) -> Result<T, UnexpectedResponse> {
+        self.map_err(|_| UnexpectedResponse::new(api, detail))
+    }
+}
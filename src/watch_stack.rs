@@ -0,0 +1,375 @@
+use std::{fmt, future::Future, pin::Pin, task};
+
+use async_stream::try_stream;
+use aws_sdk_cloudformation::error::SdkError;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, TryFutureExt, TryStreamExt};
+
+use crate::{
+    apply_stack::DESCRIBE_STACKS,
+    stack::{
+        StackEventsError, StackOperation, StackOperationError, StackOperationStatus,
+        DESCRIBE_STACK_EVENTS,
+    },
+    unexpected_response::{Expected, UnexpectedResponse},
+    PollingConfig, StackEvent, StackFailure, StackStatus, StackWarning, Status, StatusSentiment,
+};
+
+/// Errors emitted by a `watch_stack` operation.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum WatchStackError {
+    /// A CloudFormation API error occurred.
+    ///
+    /// This is likely to be due to the stack not existing or missing CloudFormation permissions.
+    /// The inner error should have a descriptive message.
+    ///
+    /// **Note:** the inner error will always be some variant of [`SdkError`], but since they are
+    /// generic over the type of service errors we either need a variant per API used, or `Box`. If
+    /// you do need to programmatically match a particular API error you can use [`Box::downcast`].
+    CloudFormationApi(Box<dyn std::error::Error + Send + Sync>),
+
+    /// The watched stack operation failed.
+    Failure(StackFailure),
+
+    /// The watched stack operation succeeded with warnings.
+    ///
+    /// Note that the error includes the status the stack settled in, since the operation did
+    /// succeed.
+    Warning {
+        /// The status the stack settled in.
+        stack_status: StackStatus,
+
+        /// Details of what went wrong.
+        warning: StackWarning,
+    },
+
+    /// CloudFormation returned a response we didn't expect.
+    ///
+    /// This could be due to a missing field, or a value (such as a status) that we don't
+    /// recognise. It likely indicates a change in CloudFormation's behaviour that this library
+    /// hasn't caught up with.
+    UnexpectedResponse {
+        /// The CloudFormation API that returned the response, e.g. `DescribeStackEvents`.
+        api: &'static str,
+
+        /// A description of what was unexpected about the response.
+        detail: String,
+    },
+}
+
+impl WatchStackError {
+    fn from_sdk_error<E: std::error::Error + Send + Sync + 'static>(error: SdkError<E>) -> Self {
+        Self::CloudFormationApi(error.into())
+    }
+}
+
+impl From<UnexpectedResponse> for WatchStackError {
+    fn from(error: UnexpectedResponse) -> Self {
+        Self::UnexpectedResponse {
+            api: error.api,
+            detail: error.detail,
+        }
+    }
+}
+
+impl From<StackEventsError> for WatchStackError {
+    fn from(error: StackEventsError) -> Self {
+        match error {
+            StackEventsError::Api(error) => Self::from_sdk_error(*error),
+            StackEventsError::UnexpectedResponse(error) => error.into(),
+        }
+    }
+}
+
+impl fmt::Display for WatchStackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CloudFormationApi(error) => {
+                write!(f, "CloudFormation API error: {error:#?}")
+            }
+            Self::Failure(failure) => write!(f, "{failure}"),
+            Self::Warning { warning, .. } => write!(f, "{warning}"),
+            Self::UnexpectedResponse { api, detail } => {
+                write!(f, "unexpected response from {api}: {detail}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for WatchStackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CloudFormationApi(error) => Some(error.as_ref()),
+            Self::Failure { .. } | Self::Warning { .. } | Self::UnexpectedResponse { .. } => None,
+        }
+    }
+}
+
+/// An ongoing `watch_stack` operation.
+///
+/// This implements `Future`, which will simply wait for the watched stack operation to conclude and
+/// resolve to the status the stack settled in. If you want to observe progress, see
+/// [`WatchStack::events`].
+///
+/// Events are replayed from the start of the stack's current (or most recent) operation, so a
+/// process that restarts part way through an operation will see the same events and outcome as
+/// the process that started it. If the stack has already settled, the events of the most recent
+/// operation are replayed and the operation resolves immediately.
+pub struct WatchStack {
+    event_stream: Pin<Box<dyn Stream<Item = Result<WatchStackEvent, WatchStackError>> + Send>>,
+    output: Option<Result<StackStatus, WatchStackError>>,
+}
+
+impl WatchStack {
+    pub(crate) fn new(
+        client: aws_sdk_cloudformation::Client,
+        polling: PollingConfig,
+        stack_name: String,
+    ) -> Self {
+        let event_stream = try_stream! {
+            let (stack_id, mut stack_status) = describe_stack(&client, stack_name).await?;
+
+            // A stack awaiting the execution of its first change set has no operation to watch.
+            if stack_status == StackStatus::ReviewInProgress {
+                yield WatchStackEvent::Output(stack_status);
+                return;
+            }
+
+            let Some(start) = find_operation_start(&client, &polling, &stack_id).await? else {
+                yield WatchStackEvent::Output(stack_status);
+                return;
+            };
+
+            // Only `ContinueUpdateRollback` starts by rolling back from `UPDATE_ROLLBACK_FAILED`.
+            // Other rollbacks (e.g. after `CancelUpdateStack`) mean the update failed.
+            let check_progress = match (start.status, start.previous_status) {
+                (StackStatus::UpdateRollbackInProgress, Some(StackStatus::UpdateRollbackFailed)) => {
+                    check_rollback_progress
+                }
+                _ => check_operation_progress,
+            };
+            let mut operation =
+                StackOperation::new(&client, polling, stack_id, start.timestamp, check_progress);
+            while let Some(event) = operation.try_next().await? {
+                if let StackEvent::Stack { resource_status, .. } = &event {
+                    if event.is_terminal() {
+                        stack_status = resource_status.clone();
+                    }
+                }
+                yield WatchStackEvent::Event(event);
+            }
+
+            match operation.verify() {
+                Ok(()) => yield WatchStackEvent::Output(stack_status),
+                Err(StackOperationError::Failure(failure)) => {
+                    Err(WatchStackError::Failure(failure))?;
+                    unreachable!()
+                }
+                Err(StackOperationError::Warning(warning)) => {
+                    Err(WatchStackError::Warning { stack_status, warning })?;
+                    unreachable!()
+                }
+            }
+        };
+        Self {
+            event_stream: Box::pin(event_stream),
+            output: None,
+        }
+    }
+
+    /// Get a `Stream` of `StackEvent`s.
+    pub fn events(&mut self) -> WatchStackEvents<'_> {
+        WatchStackEvents(self)
+    }
+
+    fn poll_next_internal(&mut self, ctx: &mut task::Context) -> task::Poll<Option<StackEvent>> {
+        loop {
+            match self.event_stream.as_mut().poll_next(ctx) {
+                task::Poll::Pending => return task::Poll::Pending,
+                task::Poll::Ready(None) => return task::Poll::Ready(None),
+                task::Poll::Ready(Some(Ok(WatchStackEvent::Event(event)))) => {
+                    return task::Poll::Ready(Some(event))
+                }
+                task::Poll::Ready(Some(Ok(WatchStackEvent::Output(stack_status)))) => {
+                    self.output.replace(Ok(stack_status));
+                }
+                task::Poll::Ready(Some(Err(error))) => {
+                    self.output.replace(Err(error));
+                }
+            }
+        }
+    }
+}
+
+impl Future for WatchStack {
+    type Output = Result<StackStatus, WatchStackError>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut task::Context) -> task::Poll<Self::Output> {
+        loop {
+            match self.poll_next_internal(ctx) {
+                task::Poll::Pending => return task::Poll::Pending,
+                task::Poll::Ready(None) => {
+                    return task::Poll::Ready(
+                        self.output
+                            .take()
+                            .expect("end of stream without err or output"),
+                    )
+                }
+                task::Poll::Ready(Some(_)) => {}
+            }
+        }
+    }
+}
+
+/// Return value of [`WatchStack::events`].
+#[allow(clippy::module_name_repetitions)]
+pub struct WatchStackEvents<'watch>(&'watch mut WatchStack);
+
+impl Stream for WatchStackEvents<'_> {
+    type Item = StackEvent;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut task::Context,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.0.poll_next_internal(ctx)
+    }
+}
+
+/// Events emitted by a `watch_stack` operation internally.
+#[allow(clippy::large_enum_variant)]
+enum WatchStackEvent {
+    /// A stack event from the watched operation.
+    Event(StackEvent),
+
+    /// The status the stack settled in (meaning the operation has concluded successfully).
+    Output(StackStatus),
+}
+
+async fn describe_stack(
+    client: &aws_sdk_cloudformation::Client,
+    stack_name: String,
+) -> Result<(String, StackStatus), WatchStackError> {
+    let stack = client
+        .describe_stacks()
+        .stack_name(stack_name)
+        .send()
+        .map_err(WatchStackError::from_sdk_error)
+        .await?
+        .stacks
+        .and_then(|mut stacks| stacks.pop())
+        .expected(DESCRIBE_STACKS, "DescribeStacksOutput without stacks")?;
+
+    let stack_id = stack
+        .stack_id
+        .expected(DESCRIBE_STACKS, "Stack without stack_id")?;
+    let stack_status = stack
+        .stack_status
+        .expected(DESCRIBE_STACKS, "Stack without stack_status")?
+        .as_str()
+        .parse()
+        .expected(DESCRIBE_STACKS, "Stack with invalid stack_status")?;
+    Ok((stack_id, stack_status))
+}
+
+/// The first event of a stack operation, see [`find_operation_start`].
+struct OperationStart {
+    /// The stack status of the first event.
+    status: StackStatus,
+
+    /// The time of the first event.
+    timestamp: DateTime<Utc>,
+
+    /// The stack status of the event before the operation, if there is one.
+    previous_status: Option<StackStatus>,
+}
+
+/// Find the first event of the stack's current (or most recent) operation.
+///
+/// Stack events are returned newest first. An operation is considered to have started at the oldest
+/// event for the stack itself that shares the newest event's client request token, and which isn't
+/// preceded by a settled status (which would mark the end of the previous operation).
+async fn find_operation_start(
+    client: &aws_sdk_cloudformation::Client,
+    polling: &PollingConfig,
+    stack_id: &str,
+) -> Result<Option<OperationStart>, WatchStackError> {
+    let mut start = None;
+    let mut client_request_token = None;
+    let mut next_token = None;
+    loop {
+        let output = polling
+            .retry_throttled(polling.stack_events_interval, || {
+                client
+                    .describe_stack_events()
+                    .stack_name(stack_id)
+                    .set_next_token(next_token.clone())
+                    .send()
+            })
+            .map_err(WatchStackError::from_sdk_error)
+            .await?;
+        for event in output.stack_events.expected(
+            DESCRIBE_STACK_EVENTS,
+            "DescribeStackEventsOutput without stack_events",
+        )? {
+            let StackEvent::Stack {
+                resource_status,
+                details,
+            } = StackEvent::from_sdk(None, event)?
+            else {
+                continue;
+            };
+
+            if let Some(client_request_token) = &client_request_token {
+                if resource_status.is_settled()
+                    || resource_status == StackStatus::ReviewInProgress
+                    || details.client_request_token != *client_request_token
+                {
+                    return Ok(start.map(|start| OperationStart {
+                        previous_status: Some(resource_status),
+                        ..start
+                    }));
+                }
+            } else {
+                client_request_token = Some(details.client_request_token);
+            }
+            start = Some(OperationStart {
+                status: resource_status,
+                timestamp: details.timestamp,
+                previous_status: None,
+            });
+        }
+
+        next_token = output.next_token;
+        if next_token.is_none() {
+            return Ok(start);
+        }
+    }
+}
+
+/// Check the progress of an arbitrary stack operation.
+///
+/// Since we didn't start the operation we don't know exactly which statuses to expect, so we rely
+/// on the sentiment of the status once it has settled.
+fn check_operation_progress(stack_status: &StackStatus) -> StackOperationStatus {
+    if !stack_status.is_settled() {
+        return StackOperationStatus::InProgress;
+    }
+    match stack_status.sentiment() {
+        StatusSentiment::Positive => StackOperationStatus::Complete,
+        StatusSentiment::Negative => StackOperationStatus::Failed,
+        StatusSentiment::Neutral => StackOperationStatus::unknown(stack_status),
+    }
+}
+
+/// Check the progress of an operation that continues rolling back a failed update rollback.
+///
+/// This is the case for `ContinueUpdateRollback`, which succeeds when the stack settles in
+/// `UPDATE_ROLLBACK_COMPLETE`.
+fn check_rollback_progress(stack_status: &StackStatus) -> StackOperationStatus {
+    match stack_status {
+        StackStatus::UpdateRollbackComplete => StackOperationStatus::Complete,
+        _ => check_operation_progress(stack_status),
+    }
+}
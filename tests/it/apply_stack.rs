@@ -2,13 +2,13 @@ use futures_util::StreamExt;
 
 use cloudformatious::{
     change_set::{Action, ExecutionStatus},
-    ApplyStackError, ApplyStackInput, ChangeSetStatus, ResourceStatus, StackFailure, StackStatus,
-    TemplateSource,
+    ApplyStackError, ApplyStackInput, ChangeSetStatus, Parameter, ResourceStatus, StackFailure,
+    StackParameter, StackStatus, TemplateSource,
 };
 
 use crate::common::{
     clean_up, generated_name, get_client, stack_with_status, EMPTY_TEMPLATE,
-    EMPTY_TEMPLATE_WITH_TRANSFORM,
+    EMPTY_TEMPLATE_WITH_TRANSFORM, SECRETS_MANAGER_SECRET,
 };
 
 const FAILING_TEMPLATE: &str = r#"
@@ -300,7 +300,7 @@ async fn create_stack_fut_err_disable_rollback() -> Result<(), Box<dyn std::erro
             .map(|(status, details)| {
                 (
                     details.logical_resource_id(),
-                    *status,
+                    status.clone(),
                     details.resource_status_reason().inner(),
                 )
             })
@@ -339,7 +339,7 @@ async fn create_stack_fut_err() -> Result<(), Box<dyn std::error::Error>> {
             .map(|(status, details)| {
                 (
                     details.logical_resource_id(),
-                    *status,
+                    status.clone(),
                     details.resource_status_reason().inner(),
                 )
             })
@@ -402,7 +402,7 @@ async fn create_stack_stream_err() -> Result<(), Box<dyn std::error::Error>> {
             .map(|(status, details)| {
                 (
                     details.logical_resource_id(),
-                    *status,
+                    status.clone(),
                     details.resource_status_reason().inner(),
                 )
             })
@@ -460,7 +460,7 @@ async fn update_stack_fut_err() -> Result<(), Box<dyn std::error::Error>> {
             .map(|(status, details)| {
                 (
                     details.logical_resource_id(),
-                    *status,
+                    status.clone(),
                     details.resource_status_reason().inner(),
                 )
             })
@@ -504,7 +504,7 @@ async fn update_stack_fut_err_disable_rollback() -> Result<(), Box<dyn std::erro
             .map(|(status, details)| {
                 (
                     details.logical_resource_id(),
-                    *status,
+                    status.clone(),
                     details.resource_status_reason().inner(),
                 )
             })
@@ -540,3 +540,32 @@ async fn apply_to_update_rollback_complete_idempotent() -> Result<(), Box<dyn st
 
     Ok(())
 }
+
+#[tokio::test]
+async fn apply_use_previous_parameters() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(SECRETS_MANAGER_SECRET))
+        .set_parameters([Parameter::new("TagValue", "a")]);
+    client.apply_stack(input).await?;
+
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(SECRETS_MANAGER_SECRET))
+        .set_use_previous_parameters(true);
+    let mut apply = client.apply_stack(input);
+    let change_set = apply.change_set().await?;
+    assert_eq!(change_set.status, ChangeSetStatus::Failed);
+    let output = apply.await?;
+    assert_eq!(
+        output.parameters,
+        [StackParameter {
+            key: "TagValue".to_string(),
+            value: Some("a".to_string()),
+            resolved_value: None,
+        }]
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
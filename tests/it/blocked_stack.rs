@@ -1,9 +1,13 @@
 use std::convert::TryFrom;
 
 use assert_matches::assert_matches;
-use cloudformatious::{ApplyStackError, ApplyStackInput, BlockedStackStatus, Client, Parameter};
+use futures_util::StreamExt;
 
-use crate::common::{clean_up, get_client, stack_with_status, NON_EMPTY_TEMPLATE};
+use cloudformatious::{
+    ApplyStackError, ApplyStackInput, BlockedStackStatus, Client, Parameter, TemplateSource,
+};
+
+use crate::common::{clean_up, get_client, stack_with_status, EMPTY_TEMPLATE, NON_EMPTY_TEMPLATE};
 
 #[tokio::test]
 async fn create_failed() -> Result<(), Box<dyn std::error::Error>> {
@@ -47,6 +51,39 @@ async fn rollback_complete() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn rollback_complete_recreate() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+    let failure = stack_with_status::rollback_complete(&client).await;
+    let stack_name = failure.stack_id.split('/').nth(1).unwrap().to_string();
+
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(EMPTY_TEMPLATE))
+        .set_recreate_if_rollback_complete(true);
+    let mut apply = client.apply_stack(input);
+    let events: Vec<_> = apply
+        .events()
+        .filter(|event| futures_util::future::ready(event.logical_resource_id() == stack_name))
+        .map(|event| event.resource_status().to_string())
+        .collect()
+        .await;
+    let output = apply.await?;
+
+    assert_ne!(output.stack_id, failure.stack_id);
+    assert_eq!(
+        events,
+        vec![
+            "DELETE_IN_PROGRESS",
+            "DELETE_COMPLETE",
+            "CREATE_IN_PROGRESS",
+            "CREATE_COMPLETE",
+        ]
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn rollback_failed() -> Result<(), Box<dyn std::error::Error>> {
     let client = get_client().await;
@@ -138,10 +175,7 @@ async fn try_update(client: &Client, stack_name: &str) -> ApplyStackError {
                 stack_name,
                 cloudformatious::TemplateSource::inline(NON_EMPTY_TEMPLATE),
             )
-            .set_parameters([Parameter {
-                key: "CidrBlock".to_string(),
-                value: "10.0.0.0/28".to_string(),
-            }]),
+            .set_parameters([Parameter::new("CidrBlock", "10.0.0.0/28")]),
         )
         .await
         .unwrap_err()
@@ -0,0 +1,82 @@
+use futures_util::StreamExt;
+
+use cloudformatious::{
+    ApplyStackError, ApplyStackInput, CancelUpdateStackInput, Parameter, StackStatus,
+    TemplateSource,
+};
+
+use crate::common::{clean_up, generated_name, get_client, SECRETS_MANAGER_SECRET};
+
+fn secret_input(stack_name: &str, tag_value: &str) -> ApplyStackInput {
+    ApplyStackInput::new(stack_name, TemplateSource::inline(SECRETS_MANAGER_SECRET))
+        .set_parameters([Parameter::new("TagValue", tag_value)])
+}
+
+#[tokio::test]
+async fn apply_stack_cancel() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    client.apply_stack(secret_input(&stack_name, "a")).await?;
+
+    let mut apply = client.apply_stack(secret_input(&stack_name, "b"));
+    let cancel = apply.cancel_handle();
+    let mut events = Vec::new();
+    while let Some(event) = apply.events().next().await {
+        // Cancel while the stream is being polled, as soon as the update has started.
+        if events.is_empty() {
+            cancel.cancel().await?;
+        }
+        events.push(event.resource_status().to_string());
+    }
+    let error = apply.await.unwrap_err();
+
+    assert!(
+        matches!(
+            &error,
+            ApplyStackError::Cancelled { failure }
+                if failure.stack_status == StackStatus::UpdateRollbackComplete
+        ),
+        "unexpected error: {}",
+        error
+    );
+    assert_eq!(
+        events.last().map(String::as_str),
+        Some("UPDATE_ROLLBACK_COMPLETE")
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cancel_update_stack() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    client.apply_stack(secret_input(&stack_name, "a")).await?;
+
+    // Start the update, then abandon it as if it was started by another process.
+    let mut apply = client.apply_stack(secret_input(&stack_name, "b"));
+    apply.events().next().await;
+    drop(apply);
+
+    let mut cancel = client.cancel_update_stack(CancelUpdateStackInput::new(&stack_name));
+    let events: Vec<_> = cancel
+        .events()
+        .map(|event| event.resource_status().to_string())
+        .collect()
+        .await;
+    cancel.await?;
+
+    assert!(events.contains(&"UPDATE_ROLLBACK_IN_PROGRESS".to_string()));
+    assert_eq!(
+        events.last().map(String::as_str),
+        Some("UPDATE_ROLLBACK_COMPLETE")
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
@@ -5,7 +5,7 @@ use cloudformatious::{
         Action, Evaluation, ModifyDetail, ModifyScope, Replacement, ResourceChange,
         ResourceChangeDetail, ResourceTargetDefinition,
     },
-    ApplyStackInput, Parameter, Tag, TemplateSource,
+    ApplyStackInput, DescribeChangeSetInput, Parameter, Tag, TemplateSource,
 };
 
 use crate::common::{
@@ -18,10 +18,7 @@ async fn changes_tags_only() -> Result<(), Box<dyn std::error::Error>> {
 
     let stack_name = generated_name();
     let mut input = ApplyStackInput::new(&stack_name, TemplateSource::inline(NON_EMPTY_TEMPLATE))
-        .set_parameters([Parameter {
-            key: "CidrBlock".to_string(),
-            value: "10.0.0.16/28".to_string(),
-        }]);
+        .set_parameters([Parameter::new("CidrBlock", "10.0.0.16/28")]);
     let output = client.apply_stack(input.clone()).await?;
     let subnet_id = output
         .outputs
@@ -43,7 +40,11 @@ async fn changes_tags_only() -> Result<(), Box<dyn std::error::Error>> {
                 details: vec![ResourceChangeDetail {
                     change_source: None,
                     evaluation: Evaluation::Static,
+                    attribute_change_type: None,
                     target: ResourceTargetDefinition::Tags,
+                    path: None,
+                    before_value: None,
+                    after_value: None,
                 }],
                 replacement: Replacement::False,
                 scope: EnumSet::only(ModifyScope::Tags),
@@ -51,6 +52,10 @@ async fn changes_tags_only() -> Result<(), Box<dyn std::error::Error>> {
             logical_resource_id: "Subnet".to_string(),
             physical_resource_id: Some(subnet_id),
             resource_type: "AWS::EC2::Subnet".to_string(),
+            change_set_id: None,
+            nested_change_set: None,
+            before_context: None,
+            after_context: None,
         }]
     );
 
@@ -66,10 +71,7 @@ async fn secrets_manager_secret_tags_only() -> Result<(), Box<dyn std::error::Er
     let stack_name = generated_name();
     let mut input =
         ApplyStackInput::new(&stack_name, TemplateSource::inline(SECRETS_MANAGER_SECRET))
-            .set_parameters([Parameter {
-                key: "TagValue".to_string(),
-                value: "a".to_string(),
-            }]);
+            .set_parameters([Parameter::new("TagValue", "a")]);
 
     client.apply_stack(input.clone()).await?;
 
@@ -97,3 +99,73 @@ async fn secrets_manager_secret_tags_only() -> Result<(), Box<dyn std::error::Er
 
     Ok(())
 }
+
+#[tokio::test]
+async fn include_property_values() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let mut input =
+        ApplyStackInput::new(&stack_name, TemplateSource::inline(SECRETS_MANAGER_SECRET))
+            .set_parameters([Parameter::new("TagValue", "a")]);
+
+    client.apply_stack(input.clone()).await?;
+
+    input.parameters[0].value = "b".to_string();
+    input = input.set_include_property_values(true);
+    let change_set = client.apply_stack(input).change_set().await?;
+
+    let change = change_set
+        .changes
+        .into_iter()
+        .next()
+        .expect("missing resource change");
+    assert!(change.before_context.is_some());
+    assert!(change.after_context.is_some());
+
+    let details = match change.action {
+        Action::Modify(details) => details.details,
+        action => panic!("unexpected action: {:?}", action),
+    };
+    assert!(details.iter().any(|detail| {
+        detail.before_value == Some(serde_json::Value::String("a".to_string()))
+            && detail.after_value == Some(serde_json::Value::String("b".to_string()))
+    }));
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn large_change_set_is_complete() -> Result<(), Box<dyn std::error::Error>> {
+    const RESOURCE_COUNT: usize = 150;
+
+    let client = get_client().await;
+
+    // Wait condition handles are free and quick to create, and `DescribeChangeSet` returns at
+    // most 100 changes per page.
+    let resources: serde_json::Map<_, _> = (0..RESOURCE_COUNT)
+        .map(|i| {
+            (
+                format!("Handle{i}"),
+                serde_json::json!({ "Type": "AWS::CloudFormation::WaitConditionHandle" }),
+            )
+        })
+        .collect();
+    let template = serde_json::json!({ "Resources": resources }).to_string();
+
+    let stack_name = generated_name();
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(template));
+    let change_set = client.create_change_set(input).await?;
+    assert_eq!(change_set.changes.len(), RESOURCE_COUNT);
+
+    let described = client
+        .describe_change_set(DescribeChangeSetInput::new(&change_set.change_set_id))
+        .await?;
+    assert_eq!(described, change_set);
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use assert_matches::assert_matches;
+
+use cloudformatious::{
+    change_set::{ChangeSetPolicy, PolicyViolation, PolicyViolationKind},
+    ApplyStackError, ApplyStackInput, Parameter, TemplateSource,
+};
+
+use crate::common::{clean_up, generated_name, get_client, get_sdk_config, NON_EMPTY_TEMPLATE};
+
+#[tokio::test]
+async fn policy_violation() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let mut input = ApplyStackInput::new(&stack_name, TemplateSource::inline(NON_EMPTY_TEMPLATE))
+        .set_parameters([Parameter::new("CidrBlock", "10.0.0.48/28")]);
+    let output = client.apply_stack(input.clone()).await?;
+    let subnet_id = output
+        .outputs
+        .into_iter()
+        .find(|output| output.key == "SubnetId")
+        .expect("missing SubnetId output")
+        .value;
+
+    // Changing the CIDR block requires the subnet to be replaced.
+    input.parameters[0].value = "10.0.0.64/28".to_string();
+    input = input.set_change_set_policy(
+        ChangeSetPolicy::new().set_protected_resource_types(["AWS::EC2::Subnet"]),
+    );
+    let error = client.apply_stack(input).await.unwrap_err();
+    assert_matches!(error, ApplyStackError::PolicyViolation { violations } => {
+        assert_eq!(violations, vec![PolicyViolation {
+            logical_resource_id: "Subnet".to_string(),
+            physical_resource_id: Some(subnet_id),
+            resource_type: "AWS::EC2::Subnet".to_string(),
+            kind: PolicyViolationKind::Replaced,
+        }]);
+    });
+
+    let change_sets = aws_sdk_cloudformation::Client::new(&get_sdk_config().await)
+        .list_change_sets()
+        .stack_name(&stack_name)
+        .send()
+        .await?;
+    assert_eq!(change_sets.summaries.unwrap_or_default(), Vec::new());
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
@@ -12,7 +12,7 @@ pub const EMPTY_TEMPLATE: &str = r#"{
     "Resources": {
         "Fake": {
             "Type": "Custom::Fake",
-            "Condition": Never
+            "Condition": "Never"
         }
     }
 }"#;
@@ -25,7 +25,7 @@ pub const EMPTY_TEMPLATE_WITH_TRANSFORM: &str = r#"{
     "Resources": {
         "Fake": {
             "Type": "Custom::Fake",
-            "Condition": Never
+            "Condition": "Never"
         }
     }
 }"#;
@@ -79,10 +79,7 @@ pub async fn rollback_failed(client: &Client) -> StackFailure {
                 generated_name(),
                 TemplateSource::inline(ROLLBACK_FAILING_TEMPLATE),
             )
-            .set_parameters([Parameter {
-                key: "CidrBlock".to_string(),
-                value: "10.0.0.32/28".to_string(),
-            }])
+            .set_parameters([Parameter::new("CidrBlock", "10.0.0.32/28")])
             .set_role_arn(get_role_arn(TestingRole::DenyDeleteSubnet).await),
         )
         .await
@@ -96,10 +93,7 @@ pub async fn delete_failed(client: &Client) -> StackFailure {
     let output = client
         .apply_stack(
             ApplyStackInput::new(generated_name(), TemplateSource::inline(NON_EMPTY_TEMPLATE))
-                .set_parameters([Parameter {
-                    key: "CidrBlock".to_string(),
-                    value: "10.0.0.48/28".to_string(),
-                }]),
+                .set_parameters([Parameter::new("CidrBlock", "10.0.0.48/28")]),
         )
         .await
         .unwrap();
@@ -164,10 +158,7 @@ pub async fn update_rollback_failed(client: &Client) -> StackFailure {
     let output = client
         .apply_stack(
             ApplyStackInput::new(generated_name(), TemplateSource::inline(NON_EMPTY_TEMPLATE))
-                .set_parameters([Parameter {
-                    key: "CidrBlock".to_string(),
-                    value: "10.0.0.80/28".to_string(),
-                }]),
+                .set_parameters([Parameter::new("CidrBlock", "10.0.0.80/28")]),
         )
         .await
         .unwrap();
@@ -178,10 +169,7 @@ pub async fn update_rollback_failed(client: &Client) -> StackFailure {
                 output.stack_id,
                 TemplateSource::inline(ROLLBACK_FAILING_TEMPLATE),
             )
-            .set_parameters([Parameter {
-                key: "CidrBlock".to_string(),
-                value: "10.0.0.80/28".to_string(),
-            }]),
+            .set_parameters([Parameter::new("CidrBlock", "10.0.0.80/28")]),
         )
         .await
         .unwrap_err();
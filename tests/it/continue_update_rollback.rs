@@ -0,0 +1,37 @@
+use futures_util::StreamExt;
+
+use cloudformatious::ContinueUpdateRollbackInput;
+
+use crate::common::{clean_up, get_client, stack_with_status};
+
+#[tokio::test]
+async fn continue_update_rollback_skip_resources() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+    let failure = stack_with_status::update_rollback_failed(&client).await;
+
+    let resources_to_skip = failure.resources_to_skip();
+    assert!(!resources_to_skip.is_empty());
+
+    let input = ContinueUpdateRollbackInput::new(&failure.stack_id)
+        .set_resources_to_skip(resources_to_skip);
+    let mut rollback = client.continue_update_rollback(input);
+    let events: Vec<_> = rollback
+        .events()
+        .map(|event| event.resource_status().to_string())
+        .collect()
+        .await;
+    rollback.await?;
+
+    assert_eq!(
+        events.first().map(String::as_str),
+        Some("UPDATE_ROLLBACK_IN_PROGRESS")
+    );
+    assert_eq!(
+        events.last().map(String::as_str),
+        Some("UPDATE_ROLLBACK_COMPLETE")
+    );
+
+    clean_up(failure.stack_id).await?;
+
+    Ok(())
+}
@@ -41,10 +41,7 @@ async fn delete_stack_stream_ok() -> Result<(), Box<dyn std::error::Error>> {
 
     let stack_name = generated_name();
     let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(NON_EMPTY_TEMPLATE))
-        .set_parameters([Parameter {
-            key: "CidrBlock".to_string(),
-            value: "10.0.0.0/28".to_string(),
-        }]);
+        .set_parameters([Parameter::new("CidrBlock", "10.0.0.0/28")]);
     let stack = client.apply_stack(input).await?;
 
     let input = DeleteStackInput::new(&stack_name);
@@ -0,0 +1,40 @@
+use cloudformatious::{
+    drift::{ResourceDriftStatus, StackDriftStatus},
+    ApplyStackInput, Parameter, TemplateSource,
+};
+
+use crate::common::{clean_up, generated_name, get_client, SECRETS_MANAGER_SECRET};
+
+#[tokio::test]
+async fn detect_stack_drift_in_sync() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(SECRETS_MANAGER_SECRET))
+        .set_parameters([Parameter::new("TagValue", "a")]);
+    let output = client.apply_stack(input).await?;
+
+    let drift = client.detect_stack_drift(&stack_name).await?;
+
+    assert_eq!(drift.stack_id, output.stack_id);
+    assert_eq!(drift.stack_drift_status, StackDriftStatus::InSync);
+    assert_eq!(drift.drifted_resources().count(), 0);
+    let resource_drifts: Vec<_> = drift
+        .resource_drifts
+        .iter()
+        .map(|resource_drift| {
+            (
+                resource_drift.logical_resource_id.as_str(),
+                resource_drift.stack_resource_drift_status,
+            )
+        })
+        .collect();
+    assert_eq!(
+        resource_drifts,
+        vec![("Secret", ResourceDriftStatus::InSync)]
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
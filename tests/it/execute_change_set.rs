@@ -0,0 +1,72 @@
+use futures_util::StreamExt;
+
+use cloudformatious::{
+    change_set::ExecutionStatus, ApplyStackInput, ChangeSetStatus, ExecuteChangeSetInput,
+    StackStatus, TemplateSource,
+};
+
+use crate::common::{clean_up, generated_name, get_client, EMPTY_TEMPLATE};
+
+#[tokio::test]
+async fn create_then_execute_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(EMPTY_TEMPLATE));
+    let change_set = client.create_change_set(input).await?;
+    assert_eq!(change_set.status, ChangeSetStatus::CreateComplete);
+    assert_eq!(change_set.execution_status, ExecutionStatus::Available);
+
+    // Use a separate client to be sure nothing is shared between the two halves.
+    let client = get_client().await;
+    let mut execute =
+        client.execute_change_set(ExecuteChangeSetInput::new(&change_set.change_set_id));
+    let events: Vec<_> = execute
+        .events()
+        .map(|event| {
+            (
+                event.logical_resource_id().to_string(),
+                event.resource_status().to_string(),
+            )
+        })
+        .collect()
+        .await;
+    let output = execute.await?;
+
+    assert_eq!(output.stack_status, StackStatus::CreateComplete);
+    assert_eq!(output.change_set_id, change_set.change_set_id);
+    assert_eq!(
+        events,
+        vec![
+            (stack_name.clone(), "CREATE_IN_PROGRESS".to_string()),
+            (stack_name.clone(), "CREATE_COMPLETE".to_string()),
+        ]
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_then_execute_no_changes() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(EMPTY_TEMPLATE));
+    let output1 = client.apply_stack(input.clone()).await?;
+
+    let change_set = client.create_change_set(input).await?;
+    assert_eq!(change_set.status, ChangeSetStatus::Failed);
+    assert_eq!(change_set.execution_status, ExecutionStatus::Unavailable);
+
+    let output2 = client
+        .execute_change_set(ExecuteChangeSetInput::new(change_set.change_set_id))
+        .await?;
+    assert_eq!(output2.stack_status, StackStatus::CreateComplete);
+    assert_eq!(output1.last_updated_time, output2.last_updated_time);
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
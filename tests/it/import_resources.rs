@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use futures_util::StreamExt;
+
+use cloudformatious::{
+    change_set::Action, ApplyStackInput, DeleteStackInput, ResourceToImport, StackStatus,
+    TemplateSource,
+};
+
+use crate::common::{clean_up, generated_name, get_client};
+
+const RETAINED_TOPIC_TEMPLATE: &str = r#"{
+    "Resources": {
+        "Topic": {
+            "Type": "AWS::SNS::Topic",
+            "DeletionPolicy": "Retain",
+            "Properties": {}
+        }
+    },
+    "Outputs": {
+        "TopicArn": {
+            "Value": {"Ref": "Topic"}
+        }
+    }
+}"#;
+
+const IMPORTED_TOPIC_TEMPLATE: &str = r#"{
+    "Resources": {
+        "Topic": {
+            "Type": "AWS::SNS::Topic",
+            "DeletionPolicy": "Delete",
+            "Properties": {}
+        }
+    }
+}"#;
+
+#[tokio::test]
+async fn import_resources_ok() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    // Create a topic that outlives its stack, so there's something to import.
+    let stack_name = generated_name();
+    let output = client
+        .apply_stack(ApplyStackInput::new(
+            &stack_name,
+            TemplateSource::inline(RETAINED_TOPIC_TEMPLATE),
+        ))
+        .await?;
+    let topic_arn = output
+        .outputs
+        .into_iter()
+        .find(|output| output.key == "TopicArn")
+        .expect("missing TopicArn output")
+        .value;
+    client
+        .delete_stack(DeleteStackInput::new(&stack_name))
+        .await?;
+
+    let stack_name = generated_name();
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(IMPORTED_TOPIC_TEMPLATE))
+        .set_resources_to_import([ResourceToImport {
+            resource_type: "AWS::SNS::Topic".to_string(),
+            logical_resource_id: "Topic".to_string(),
+            resource_identifier: HashMap::from([("TopicArn".to_string(), topic_arn)]),
+        }]);
+    let mut apply = client.apply_stack(input);
+
+    let change_set = apply.change_set().await?;
+    assert_eq!(change_set.changes.len(), 1);
+    assert!(matches!(change_set.changes[0].action, Action::Import));
+
+    let events: Vec<_> = apply
+        .events()
+        .map(|event| {
+            (
+                event.logical_resource_id().to_string(),
+                event.resource_status().to_string(),
+            )
+        })
+        .collect()
+        .await;
+    let output = apply.await?;
+
+    assert_eq!(output.stack_status, StackStatus::ImportComplete);
+    assert_eq!(
+        events.first(),
+        Some(&(stack_name.clone(), "IMPORT_IN_PROGRESS".to_string()))
+    );
+    assert!(events.contains(&("Topic".to_string(), "IMPORT_COMPLETE".to_string())));
+    assert_eq!(
+        events.last(),
+        Some(&(stack_name.clone(), "IMPORT_COMPLETE".to_string()))
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
@@ -1,6 +1,13 @@
 mod apply_stack;
 mod blocked_stack;
+mod cancel_update_stack;
 mod change_set_detail;
+mod change_set_policy;
 mod common;
+mod continue_update_rollback;
 mod delete_stack;
+mod detect_stack_drift;
+mod execute_change_set;
+mod import_resources;
 mod status_reasons;
+mod watch_stack;
@@ -0,0 +1,118 @@
+use futures_util::StreamExt;
+
+use cloudformatious::{ApplyStackInput, Parameter, StackStatus, TemplateSource, WatchStackError};
+
+use crate::common::{
+    clean_up, generated_name, get_client, get_sdk_config, EMPTY_TEMPLATE, SECRETS_MANAGER_SECRET,
+};
+
+#[tokio::test]
+async fn watch_stack_in_progress() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(EMPTY_TEMPLATE));
+    let mut apply = client.apply_stack(input);
+
+    // Wait for the operation to start, then abandon it as if the process had restarted.
+    apply.events().next().await;
+    drop(apply);
+
+    let mut watch = client.watch_stack(&stack_name);
+    let events: Vec<_> = watch
+        .events()
+        .map(|event| {
+            (
+                event.logical_resource_id().to_string(),
+                event.resource_status().to_string(),
+            )
+        })
+        .collect()
+        .await;
+    let stack_status = watch.await?;
+
+    assert_eq!(stack_status, StackStatus::CreateComplete);
+    assert_eq!(
+        events,
+        vec![
+            (stack_name.clone(), "CREATE_IN_PROGRESS".to_string()),
+            (stack_name.clone(), "CREATE_COMPLETE".to_string()),
+        ]
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn watch_stack_settled() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let input = ApplyStackInput::new(&stack_name, TemplateSource::inline(EMPTY_TEMPLATE));
+    client.apply_stack(input).await?;
+
+    let mut watch = client.watch_stack(&stack_name);
+    let events: Vec<_> = watch
+        .events()
+        .map(|event| event.resource_status().to_string())
+        .collect()
+        .await;
+    let stack_status = watch.await?;
+
+    assert_eq!(stack_status, StackStatus::CreateComplete);
+    assert_eq!(events, vec!["CREATE_IN_PROGRESS", "CREATE_COMPLETE"]);
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn watch_stack_cancelled_update() -> Result<(), Box<dyn std::error::Error>> {
+    let client = get_client().await;
+
+    let stack_name = generated_name();
+    let input = |tag_value: &str| {
+        ApplyStackInput::new(&stack_name, TemplateSource::inline(SECRETS_MANAGER_SECRET))
+            .set_parameters([Parameter::new("TagValue", tag_value)])
+    };
+    client.apply_stack(input("a")).await?;
+
+    // Start an update, abandon it, and cancel it as if from another process.
+    let mut apply = client.apply_stack(input("b"));
+    apply.events().next().await;
+    drop(apply);
+    aws_sdk_cloudformation::Client::new(&get_sdk_config().await)
+        .cancel_update_stack()
+        .stack_name(&stack_name)
+        .send()
+        .await?;
+
+    let mut watch = client.watch_stack(&stack_name);
+    let events: Vec<_> = watch
+        .events()
+        .map(|event| event.resource_status().to_string())
+        .collect()
+        .await;
+    let error = watch.await.unwrap_err();
+
+    assert!(
+        matches!(
+            &error,
+            WatchStackError::Failure(failure)
+                if failure.stack_status == StackStatus::UpdateRollbackComplete
+        ),
+        "unexpected error: {}",
+        error
+    );
+    assert_eq!(
+        events.last().map(String::as_str),
+        Some("UPDATE_ROLLBACK_COMPLETE")
+    );
+
+    clean_up(stack_name).await?;
+
+    Ok(())
+}